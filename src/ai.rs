@@ -0,0 +1,258 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap
+};
+
+use crate::{
+    board::Board,
+    direction::Direction,
+    snake::Snake
+};
+
+const DIRECTIONS: [Direction; 4] = [Direction::Right, Direction::Down, Direction::Left, Direction::Up];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tier {
+    Greedy,
+    AStar,
+    Hamiltonian
+}
+
+impl Tier {
+    pub fn parse(name: &str) -> Option<Tier> {
+        match name {
+            "greedy" => Some(Tier::Greedy),
+            "astar" => Some(Tier::AStar),
+            "hamilton" => Some(Tier::Hamiltonian),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tier::Greedy => "greedy",
+            Tier::AStar => "astar",
+            Tier::Hamiltonian => "hamilton"
+        }
+    }
+}
+
+pub struct Ai {
+    tier: Tier,
+    cycle: Vec<(usize, usize)>,
+    order: Vec<usize>
+}
+
+impl Ai {
+    pub fn new(tier: Tier, width: usize, height: usize) -> Self {
+        let cycle = match tier {
+            Tier::Hamiltonian => hamiltonian_cycle(width, height),
+            _ => Vec::new()
+        };
+
+        let mut order = vec![0; if cycle.is_empty() { 0 } else { width * height }];
+        for (i, pos) in cycle.iter().enumerate() {
+            order[pos.0 * width + pos.1] = i;
+        }
+
+        Ai { tier, cycle, order }
+    }
+
+    pub fn choose(&self, board: &Board, snake: &Snake, target: (usize, usize), rivals: &[(usize, usize)], walls: bool) -> Direction {
+        let direction = match self.tier {
+            Tier::Greedy => self.greedy(board, snake, target, rivals, walls),
+            Tier::AStar => self.astar(board, snake, target, rivals, walls),
+            Tier::Hamiltonian => self.hamiltonian(board, snake, target, rivals, walls)
+        };
+
+        direction.unwrap_or(snake.direction())
+    }
+
+    fn greedy(&self, board: &Board, snake: &Snake, target: (usize, usize), rivals: &[(usize, usize)], walls: bool) -> Option<Direction> {
+        moves(board, snake, rivals, walls)
+            .into_iter()
+            .min_by_key(|(_, pos)| distance(board, *pos, target, walls))
+            .map(|(direction, _)| direction)
+    }
+
+    fn astar(&self, board: &Board, snake: &Snake, target: (usize, usize), rivals: &[(usize, usize)], walls: bool) -> Option<Direction> {
+        let safe = moves(board, snake, rivals, walls);
+        match path(board, snake, &safe, target, walls) {
+            Some((direction, pos)) if space(board, snake, pos, walls) >= snake.size() => {
+                return Some(direction);
+            },
+            _ => {}
+        }
+
+        safe.into_iter()
+            .max_by_key(|(_, pos)| space(board, snake, *pos, walls))
+            .map(|(direction, _)| direction)
+    }
+
+    fn hamiltonian(&self, board: &Board, snake: &Snake, target: (usize, usize), rivals: &[(usize, usize)], walls: bool) -> Option<Direction> {
+        if self.cycle.is_empty() {
+            return self.astar(board, snake, target, rivals, walls);
+        }
+
+        let safe = moves(board, snake, rivals, walls);
+        let head = self.order[index(board, snake.head())];
+        let next = self.cycle[(head + 1) % self.cycle.len()];
+        let follow = match safe.iter().find(|(_, pos)| *pos == next) {
+            Some(step) => *step,
+            None => {
+                return self.astar(board, snake, target, rivals, walls);
+            }
+        };
+
+        if snake.size() * 2 > self.cycle.len() {
+            return Some(follow.0);
+        }
+
+        let ahead = |pos: (usize, usize)| (self.order[index(board, pos)] + self.cycle.len() - head) % self.cycle.len();
+        let room = if snake.size() > 1 { ahead(snake.tail()).saturating_sub(snake.pending() + 3) } else { self.cycle.len() };
+        let shortcut = safe.into_iter()
+            .filter(|(_, pos)| ahead(*pos) <= ahead(target) && ahead(*pos) < room)
+            .max_by_key(|(_, pos)| ahead(*pos));
+
+        match shortcut {
+            Some((direction, _)) => Some(direction),
+            None => Some(follow.0)
+        }
+    }
+}
+
+fn hamiltonian_cycle(width: usize, height: usize) -> Vec<(usize, usize)> {
+    let (rows, columns, transposed) = if height.is_multiple_of(2) {
+        (height, width, false)
+    } else if width.is_multiple_of(2) {
+        (width, height, true)
+    } else {
+        return Vec::new();
+    };
+
+    let mut cycle: Vec<(usize, usize)> = (0..columns).map(|column| (0, column)).collect();
+    for row in 1..rows {
+        if row % 2 == 1 {
+            cycle.extend((1..columns).rev().map(|column| (row, column)));
+        } else {
+            cycle.extend((1..columns).map(|column| (row, column)));
+        }
+    }
+
+    cycle.extend((1..rows).rev().map(|row| (row, 0)));
+    if transposed {
+        cycle.iter().map(|pos| (pos.1, pos.0)).collect()
+    } else {
+        cycle
+    }
+}
+
+fn path(board: &Board, snake: &Snake, first: &[(Direction, (usize, usize))], target: (usize, usize), walls: bool) -> Option<(Direction, (usize, usize))> {
+    let mut origin: Vec<Option<usize>> = vec![None; board.width() * board.height()];
+    let mut queue = BinaryHeap::new();
+    for (i, (_, pos)) in first.iter().enumerate() {
+        origin[index(board, *pos)] = Some(i);
+        queue.push(Reverse((1 + distance(board, *pos, target, walls), 1, *pos)));
+    }
+
+    let tail = free_tail(snake);
+    while let Some(Reverse((_, cost, pos))) = queue.pop() {
+        let from = origin[index(board, pos)].unwrap();
+        if pos == target {
+            return Some(first[from]);
+        }
+
+        for direction in DIRECTIONS {
+            match neighbour(board, pos, direction, walls) {
+                Some(next) if origin[index(board, next)].is_none() && (board.is_open(next) || Some(next) == tail) => {
+                    origin[index(board, next)] = Some(from);
+                    queue.push(Reverse((cost + 1 + distance(board, next, target, walls), cost + 1, next)));
+                },
+                _ => {}
+            }
+        }
+    }
+
+    None
+}
+
+fn space(board: &Board, snake: &Snake, from: (usize, usize), walls: bool) -> usize {
+    let mut visited = vec![false; board.width() * board.height()];
+    let mut pending = vec![from];
+    visited[index(board, from)] = true;
+    visited[index(board, snake.head())] = true;
+
+    let tail = free_tail(snake);
+    let mut count = 0;
+    while let Some(pos) = pending.pop() {
+        count += 1;
+        for direction in DIRECTIONS {
+            match neighbour(board, pos, direction, walls) {
+                Some(next) if !visited[index(board, next)] && (board.is_open(next) || Some(next) == tail) => {
+                    visited[index(board, next)] = true;
+                    pending.push(next);
+                },
+                _ => {}
+            }
+        }
+    }
+
+    count
+}
+
+fn moves(board: &Board, snake: &Snake, rivals: &[(usize, usize)], walls: bool) -> Vec<(Direction, (usize, usize))> {
+    let tail = free_tail(snake);
+    let moves: Vec<(Direction, (usize, usize))> = DIRECTIONS.iter()
+        .filter(|direction| **direction != snake.direction().opposite())
+        .filter_map(|direction| neighbour(board, snake.head(), *direction, walls).map(|pos| (*direction, pos)))
+        .filter(|(_, pos)| board.is_open(*pos) || Some(*pos) == tail)
+        .collect();
+
+    let contested = |pos: (usize, usize)| rivals.iter().any(|rival| DIRECTIONS.iter().any(|direction| neighbour(board, *rival, *direction, walls) == Some(pos)));
+    let uncontested: Vec<(Direction, (usize, usize))> = moves.iter().copied().filter(|(_, pos)| !contested(*pos)).collect();
+    if uncontested.is_empty() {
+        moves
+    } else {
+        uncontested
+    }
+}
+
+fn free_tail(snake: &Snake) -> Option<(usize, usize)> {
+    if snake.pending() == 0 && snake.size() > 1 {
+        Some(snake.tail())
+    } else {
+        None
+    }
+}
+
+fn neighbour(board: &Board, pos: (usize, usize), direction: Direction, walls: bool) -> Option<(usize, usize)> {
+    let (height, width) = (board.height(), board.width());
+    let next = match direction {
+        Direction::Right => (pos.0, pos.1 + 1),
+        Direction::Down => (pos.0 + 1, pos.1),
+        Direction::Left => (pos.0, pos.1.wrapping_sub(1)),
+        Direction::Up => (pos.0.wrapping_sub(1), pos.1)
+    };
+
+    if next.0 < height && next.1 < width {
+        Some(next)
+    } else if walls {
+        None
+    } else {
+        Some(((next.0.wrapping_add(height)) % height, (next.1.wrapping_add(width)) % width))
+    }
+}
+
+fn index(board: &Board, pos: (usize, usize)) -> usize {
+    pos.0 * board.width() + pos.1
+}
+
+fn distance(board: &Board, from: (usize, usize), to: (usize, usize), walls: bool) -> usize {
+    let rows = from.0.abs_diff(to.0);
+    let columns = from.1.abs_diff(to.1);
+    if walls {
+        rows + columns
+    } else {
+        rows.min(board.height() - rows) + columns.min(board.width() - columns)
+    }
+}
@@ -1,101 +1,460 @@
-use crate::util::random_number;
+use std::collections::{
+    HashMap, VecDeque
+};
 
-pub const BOARD_SIZE: usize = 8;
+use crate::{
+    direction::Direction,
+    error::ProtocolError,
+    glyphs::{
+        is_emoji, Glyphs
+    },
+    level::is_valid_size,
+    util::random_number
+};
+
+pub const BOARD_WIDTH: usize = 8;
+pub const BOARD_HEIGHT: usize = 8;
+pub const MIN_BOARD_SIZE: usize = 5;
 pub const PLAYER_CHAR: char = '+';
 pub const OPPONENT_CHAR: char = '-';
+pub const SNAKE_CHARS: [char; 4] = [PLAYER_CHAR, OPPONENT_CHAR, '=', '~'];
 pub const TARGET_CHAR: char = 'o';
+pub const GOLDEN_CHAR: char = '@';
 pub const CRASH_CHAR: char = 'x';
+pub const WALL_CHAR: char = '#';
+pub const PORTAL_CHARS: std::ops::RangeInclusive<char> = 'A'..='Z';
+
+const RESET_STYLE: &str = "\x1b[0m";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cell {
+    Empty,
+    Wall,
+    Target,
+    Golden,
+    Crash,
+    Snake(u8),
+    Portal(u8)
+}
+
+impl Cell {
+    pub fn from(pixel: char) -> Option<Cell> {
+        match pixel {
+            ' ' => Some(Cell::Empty),
+            WALL_CHAR => Some(Cell::Wall),
+            TARGET_CHAR => Some(Cell::Target),
+            GOLDEN_CHAR => Some(Cell::Golden),
+            CRASH_CHAR => Some(Cell::Crash),
+            pixel if is_snake(pixel) => {
+                let index = SNAKE_CHARS.iter().position(|snake| *snake == pixel).unwrap();
+                Some(Cell::Snake(index as u8))
+            },
+            pixel if is_portal(pixel) => Some(Cell::Portal(pixel as u8)),
+            _ => None
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        matches!(self, Cell::Empty | Cell::Target | Cell::Golden)
+    }
+
+    pub fn pixel(&self) -> char {
+        match self {
+            Cell::Empty => ' ',
+            Cell::Wall => WALL_CHAR,
+            Cell::Target => TARGET_CHAR,
+            Cell::Golden => GOLDEN_CHAR,
+            Cell::Crash => CRASH_CHAR,
+            Cell::Snake(index) => SNAKE_CHARS[*index as usize],
+            Cell::Portal(letter) => *letter as char
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Slot {
+    Before,
+    Inside,
+    After
+}
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stroke {
+    pub pos: (usize, usize),
+    pub slot: Slot,
+    pub pixel: char,
+    pub glyph: char
+}
+
+#[derive(Clone)]
 pub struct Board {
-    pixels: Vec<Vec<char>>
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+    free: Vec<usize>,
+    free_count: usize,
+    occupied: usize
 }
 
 impl Board {
-    pub fn new() -> Self {
-        let mut pixels = Vec::new();
-        for _ in 0..BOARD_SIZE {
-            let mut row = Vec::new();
-            for _ in 0..BOARD_SIZE {
-                row.push(' ');
-            }
-            pixels.push(row);
-        }
+    pub fn new(width: usize, height: usize) -> Self {
+        let size = width * height;
+        let free = (0..=size).map(|i| i & i.wrapping_neg()).collect();
+        Board { width, height, cells: vec![Cell::Empty; size], free, free_count: size, occupied: 0 }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
 
-        Board { pixels }
+    pub fn height(&self) -> usize {
+        self.height
     }
 
     pub fn mark(&mut self, pos: (usize, usize), value: char) {
-        self.pixels[pos.0][pos.1] = value;
+        let index = self.index(pos);
+        match Cell::from(value) {
+            Some(cell) => self.set(index, cell),
+            None => {
+                panic!("bad pixel [Board::mark()]");
+            }
+        }
     }
 
     pub fn unmark(&mut self, pos: (usize, usize)) {
-        self.pixels[pos.0][pos.1] = ' ';
+        let index = self.index(pos);
+        self.set(index, Cell::Empty);
     }
 
     pub fn value(&self, pos: (usize, usize)) -> char {
-        self.pixels[pos.0][pos.1]
+        self.cells[self.index(pos)].pixel()
+    }
+
+    pub fn is_open(&self, pos: (usize, usize)) -> bool {
+        self.cells[self.index(pos)].is_open()
     }
 
     pub fn is_full(&self) -> bool {
-        for row in &self.pixels {
-            for pixel in row {
-                if *pixel == ' ' || *pixel == TARGET_CHAR {
-                    return false;
+        self.occupied == self.cells.len()
+    }
+
+    pub fn random_position(&self) -> Option<(usize, usize)> {
+        self.free_position(random_number())
+    }
+
+    pub fn free_position(&self, roll: u64) -> Option<(usize, usize)> {
+        match self.free_count {
+            0 => None,
+            count => Some(self.position(self.nth_free(roll as usize % count)))
+        }
+    }
+
+    pub fn reachable(&self, from: (usize, usize), walls: bool) -> Vec<Vec<bool>> {
+        let mut visited = vec![vec![false; self.width]; self.height];
+        let mut pending = vec![from];
+        visited[from.0][from.1] = true;
+
+        while let Some(pos) = pending.pop() {
+            for next in self.neighbours(pos, walls) {
+                if !visited[next.0][next.1] && self.cells[self.index(next)] != Cell::Wall {
+                    visited[next.0][next.1] = true;
+                    pending.push(next);
                 }
             }
         }
 
-        true
+        visited
     }
 
-    pub fn random_position(&self) -> Option<(usize, usize)> {
-        let mut available = Vec::new();
-        for i in 0..BOARD_SIZE {
-            for j in 0..BOARD_SIZE {
-                if self.pixels[i][j] == ' ' {
-                    available.push((i, j));
+    pub fn stays_connected(&self, wall: (usize, usize), walls: bool) -> bool {
+        let starts: Vec<(usize, usize)> = self.neighbours(wall, walls).into_iter()
+            .filter(|next| self.cells[self.index(*next)] != Cell::Wall)
+            .collect();
+
+        if starts.is_empty() {
+            return false;
+        }
+
+        let mut owners = HashMap::new();
+        let mut groups: Vec<usize> = (0..starts.len()).collect();
+        let mut pending: Vec<VecDeque<(usize, usize)>> = Vec::new();
+        for (search, start) in starts.iter().enumerate() {
+            owners.insert(*start, search);
+            pending.push(VecDeque::from([*start]));
+        }
+
+        loop {
+            if groups.iter().all(|group| *group == groups[0]) {
+                return true;
+            }
+
+            let exhausted = groups.iter().any(|group| {
+                (0..starts.len()).filter(|search| groups[*search] == *group).all(|search| pending[search].is_empty())
+            });
+            if exhausted {
+                return false;
+            }
+
+            for search in 0..starts.len() {
+                let pos = match pending[search].pop_front() {
+                    Some(pos) => pos,
+                    None => continue
+                };
+
+                for next in self.neighbours(pos, walls) {
+                    if next == wall || self.cells[self.index(next)] == Cell::Wall {
+                        continue;
+                    }
+
+                    match owners.get(&next) {
+                        Some(owner) => {
+                            let (from, to) = (groups[*owner], groups[search]);
+                            for group in groups.iter_mut() {
+                                if *group == from {
+                                    *group = to;
+                                }
+                            }
+                        },
+                        None => {
+                            owners.insert(next, search);
+                            pending[search].push_back(next);
+                        }
+                    }
                 }
             }
         }
+    }
+
+    pub fn is_connected(&self, walls: bool) -> bool {
+        let open: Vec<(usize, usize)> = (0..self.cells.len())
+            .filter(|index| self.cells[*index] != Cell::Wall)
+            .map(|index| self.position(index))
+            .collect();
 
-        match available.is_empty() {
-            false => Some(available[random_number() as usize % available.len()]),
-            true => None
+        if open.is_empty() {
+            return false;
         }
+
+        let reachable = self.reachable(open[0], walls);
+        open.iter().all(|pos| reachable[pos.0][pos.1])
     }
 
-    pub fn draw(&self) -> String {
-        let mut s = String::new();
+    #[allow(clippy::identity_op)]
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(4 + self.width * self.height);
+        buffer.push((self.width >> 8) as u8);
+        buffer.push((self.width >> 0) as u8);
+        buffer.push((self.height >> 8) as u8);
+        buffer.push((self.height >> 0) as u8);
 
-        s.push('+');
-        for _ in 0..BOARD_SIZE {
-            s.push(' ');
-            s.push('+');
-            s.push(' ');
+        for cell in &self.cells {
+            buffer.push(cell.pixel() as u8);
+        }
+
+        buffer
+    }
+
+    #[allow(clippy::identity_op)]
+    pub fn decode(buffer: &[u8]) -> Result<Board, ProtocolError> {
+        if buffer.len() < 4 {
+            return Err(ProtocolError::Truncated);
+        }
+
+        let width = ((buffer[0] as usize) << 8) | ((buffer[1] as usize) << 0);
+        let height = ((buffer[2] as usize) << 8) | ((buffer[3] as usize) << 0);
+        if !is_valid_size(width, height) {
+            return Err(ProtocolError::Malformed("board size"));
+        }
+
+        if buffer.len() != 4 + width * height {
+            return Err(ProtocolError::Malformed("board"));
         }
 
-        s.push('+');
-        s.push('\n');
-        for row in &self.pixels {
+        let mut board = Board::new(width, height);
+        for (index, byte) in buffer[4..].iter().enumerate() {
+            match Cell::from(*byte as char) {
+                Some(cell) => {
+                    board.set(index, cell);
+                },
+                None => {
+                    return Err(ProtocolError::Malformed("board cell"));
+                }
+            }
+        }
+
+        Ok(board)
+    }
+
+    pub fn draw(&self, color: bool, glyphs: &Glyphs) -> String {
+        self.draw_between(color, glyphs, &[], &[])
+    }
+
+    pub fn draw_between(&self, color: bool, glyphs: &Glyphs, strokes: &[Stroke], heads: &[((usize, usize), Direction)]) -> String {
+        let mut border = String::from("+");
+        for _ in 0..self.width {
+            border.push_str(" +");
+            for _ in 2..glyphs.cell_width() {
+                border.push(' ');
+            }
+        }
+
+        border.push_str("+\n");
+
+        let mut s = border.clone();
+        for (i, row) in self.cells.chunks(self.width).enumerate() {
             s.push('+');
-            for pixel in row {
-                s.push(' ');
-                s.push(*pixel);
-                s.push(' ');
+            for (j, cell) in row.iter().enumerate() {
+                let stroke = |slot| strokes.iter().find(|stroke| stroke.pos == (i, j) && stroke.slot == slot);
+                let head = match cell {
+                    Cell::Snake(_) => heads.iter().find(|head| head.0 == (i, j)).and_then(|head| glyphs.head(head.1)),
+                    _ => None
+                };
+
+                match stroke(Slot::Before) {
+                    Some(stroke) => push_pixel(&mut s, stroke.pixel, stroke.glyph, color),
+                    None => s.push(' ')
+                }
+
+                match stroke(Slot::Inside) {
+                    Some(stroke) if *cell == Cell::Empty => {
+                        push_pixel(&mut s, stroke.pixel, stroke.glyph, color);
+                        if glyphs.is_wide() {
+                            push_pixel(&mut s, stroke.pixel, stroke.glyph, color);
+                        }
+                    },
+                    _ => {
+                        let glyph = head.unwrap_or_else(|| glyphs.glyph(cell.pixel()));
+                        push_pixel(&mut s, cell.pixel(), glyph, color);
+                        if glyphs.is_wide() && !is_emoji(glyph) {
+                            s.push(' ');
+                        }
+                    }
+                }
+
+                match stroke(Slot::After) {
+                    Some(stroke) => push_pixel(&mut s, stroke.pixel, stroke.glyph, color),
+                    None => s.push(' ')
+                }
             }
             s.push('+');
             s.push('\n');
         }
 
-        s.push('+');
-        for _ in 0..BOARD_SIZE {
-            s.push(' ');
-            s.push('+');
-            s.push(' ');
+        s.push_str(&border);
+        s
+    }
+
+    fn neighbours(&self, pos: (usize, usize), walls: bool) -> Vec<(usize, usize)> {
+        let mut neighbours = Vec::new();
+        if pos.0 > 0 {
+            neighbours.push((pos.0 - 1, pos.1));
+        } else if !walls {
+            neighbours.push((self.height - 1, pos.1));
         }
 
-        s.push('+');
-        s.push('\n');
-        s
+        if pos.0 + 1 < self.height {
+            neighbours.push((pos.0 + 1, pos.1));
+        } else if !walls {
+            neighbours.push((0, pos.1));
+        }
+
+        if pos.1 > 0 {
+            neighbours.push((pos.0, pos.1 - 1));
+        } else if !walls {
+            neighbours.push((pos.0, self.width - 1));
+        }
+
+        if pos.1 + 1 < self.width {
+            neighbours.push((pos.0, pos.1 + 1));
+        } else if !walls {
+            neighbours.push((pos.0, 0));
+        }
+
+        neighbours
     }
+
+    fn index(&self, pos: (usize, usize)) -> usize {
+        pos.0 * self.width + pos.1
+    }
+
+    fn position(&self, index: usize) -> (usize, usize) {
+        (index / self.width, index % self.width)
+    }
+
+    fn set(&mut self, index: usize, cell: Cell) {
+        let was_free = self.cells[index] == Cell::Empty;
+        match (self.cells[index].is_open(), cell.is_open()) {
+            (true, false) => self.occupied += 1,
+            (false, true) => self.occupied -= 1,
+            _ => {}
+        }
+
+        self.cells[index] = cell;
+        if was_free == (cell == Cell::Empty) {
+            return;
+        }
+
+        let mut i = index + 1;
+        while i < self.free.len() {
+            match was_free {
+                true => self.free[i] -= 1,
+                false => self.free[i] += 1
+            }
+            i += i & i.wrapping_neg();
+        }
+
+        match was_free {
+            true => self.free_count -= 1,
+            false => self.free_count += 1
+        }
+    }
+
+    fn nth_free(&self, mut n: usize) -> usize {
+        let mut index = 0;
+        let mut step = self.free.len().next_power_of_two();
+        while step > 0 {
+            if index + step < self.free.len() && self.free[index + step] <= n {
+                index += step;
+                n -= self.free[index];
+            }
+            step >>= 1;
+        }
+
+        index
+    }
+}
+
+fn push_pixel(s: &mut String, pixel: char, glyph: char, color: bool) {
+    match style(pixel) {
+        Some(style) if color => {
+            s.push_str(style);
+            s.push(glyph);
+            s.push_str(RESET_STYLE);
+        },
+        _ => {
+            s.push(glyph);
+        }
+    }
+}
+
+fn style(pixel: char) -> Option<&'static str> {
+    match pixel {
+        PLAYER_CHAR => Some("\x1b[32m"),
+        OPPONENT_CHAR => Some("\x1b[31m"),
+        '=' => Some("\x1b[34m"),
+        '~' => Some("\x1b[36m"),
+        TARGET_CHAR => Some("\x1b[33m"),
+        GOLDEN_CHAR => Some("\x1b[1;33m"),
+        CRASH_CHAR => Some("\x1b[5;31m"),
+        pixel if is_portal(pixel) => Some("\x1b[35m"),
+        _ => None
+    }
+}
+
+pub fn is_snake(pixel: char) -> bool {
+    SNAKE_CHARS.contains(&pixel)
+}
+
+pub fn is_portal(pixel: char) -> bool {
+    PORTAL_CHARS.contains(&pixel)
 }
@@ -0,0 +1,88 @@
+use std::{
+    io::{
+        BufRead, BufReader, Write
+    },
+    process::{
+        Child, ChildStdin, Command, Stdio
+    },
+    sync::mpsc::{
+        channel, Receiver
+    },
+    thread::spawn,
+    time::Duration
+};
+
+use crate::{direction::Direction, error::SnakeError};
+
+pub struct Bot {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>
+}
+
+impl Bot {
+    pub fn spawn(cmd: &str) -> Result<Self, SnakeError> {
+        let mut parts = cmd.split_whitespace();
+        let program = match parts.next() {
+            Some(program) => program,
+            None => {
+                return Err(SnakeError::BadBot("empty command".into()));
+            }
+        };
+
+        let mut child = Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        let (tx, lines) = channel::<String>();
+        spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) => {
+                        if tx.send(line).is_err() {
+                            break;
+                        }
+                    },
+                    Err(_) => {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Bot { child, stdin, lines })
+    }
+
+    pub fn send_state(&mut self, state: &str) -> Result<(), SnakeError> {
+        writeln!(self.stdin, "{}", state)?;
+        self.stdin.flush()?;
+        Ok(())
+    }
+
+    pub fn recv_direction(&mut self, timeout: Duration) -> Option<Direction> {
+        match self.lines.recv_timeout(timeout) {
+            Ok(line) => parse_direction(&line),
+            Err(_) => None
+        }
+    }
+}
+
+impl Drop for Bot {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn parse_direction(line: &str) -> Option<Direction> {
+    let (_, rest) = line.split_once("\"direction\"")?;
+    let (_, rest) = rest.split_once(':')?;
+    let (_, rest) = rest.split_once('"')?;
+    let (name, _) = rest.split_once('"')?;
+    Direction::parse(name)
+}
@@ -0,0 +1,67 @@
+use std::{
+    fs::{
+        create_dir_all, read_to_string, write
+    },
+    time::Duration
+};
+
+use crate::{
+    config::GameConfig,
+    error::SnakeError,
+    util::data_dir
+};
+
+pub const CAMPAIGN_FILE: &str = "campaign.txt";
+
+pub struct Stage {
+    width: usize,
+    height: usize,
+    obstacles: usize,
+    pace: u64,
+    goal: usize
+}
+
+pub const STAGES: [Stage; 7] = [
+    Stage { width: 8, height: 8, obstacles: 0, pace: 400, goal: 5 },
+    Stage { width: 10, height: 8, obstacles: 0, pace: 350, goal: 7 },
+    Stage { width: 12, height: 10, obstacles: 5, pace: 350, goal: 9 },
+    Stage { width: 14, height: 10, obstacles: 10, pace: 300, goal: 11 },
+    Stage { width: 16, height: 12, obstacles: 15, pace: 250, goal: 13 },
+    Stage { width: 20, height: 14, obstacles: 20, pace: 200, goal: 16 },
+    Stage { width: 24, height: 16, obstacles: 25, pace: 150, goal: 20 }
+];
+
+impl Stage {
+    pub fn apply(&self, config: &mut GameConfig) {
+        config.width = self.width;
+        config.height = self.height;
+        config.obstacles = self.obstacles;
+        config.pace = Duration::from_millis(self.pace);
+        config.goal = Some(self.goal);
+    }
+}
+
+pub fn progress() -> usize {
+    match data_dir().and_then(|dir| read_to_string(dir.join(CAMPAIGN_FILE)).ok()) {
+        Some(text) => {
+            match text.trim().parse::<usize>() {
+                Ok(stage) => stage.min(STAGES.len() - 1),
+                Err(_) => 0
+            }
+        },
+        None => 0
+    }
+}
+
+pub fn save_progress(stage: usize) -> Result<(), SnakeError> {
+    let dir = match data_dir() {
+        Some(dir) => dir,
+        None => {
+            return Err(SnakeError::NoDataDir);
+        }
+    };
+
+    create_dir_all(&dir)?;
+    write(dir.join(CAMPAIGN_FILE), format!("{}\n", stage))?;
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use std::{
+    fs::File,
+    io::Write,
+    time::{
+        Instant, SystemTime, UNIX_EPOCH
+    }
+};
+
+use crate::{
+    error::SnakeError,
+    terminal::Terminal,
+    util::escape
+};
+
+pub struct CastTerminal {
+    inner: Box<dyn Terminal>,
+    file: File,
+    started: Instant
+}
+
+impl CastTerminal {
+    pub fn create(inner: Box<dyn Terminal>, path: &str, width: usize, height: usize) -> Result<Self, SnakeError> {
+        let mut file = File::create(path)?;
+        let timestamp = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(now) => now.as_secs(),
+            Err(_) => 0
+        };
+
+        writeln!(file, "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}}}", width, height, timestamp)?;
+        Ok(CastTerminal { inner, file, started: Instant::now() })
+    }
+
+    fn record(&mut self, data: &str) {
+        let time = self.started.elapsed().as_secs_f64();
+        let _ = writeln!(self.file, "[{:.6}, \"o\", \"{}\"]", time, escape(data));
+    }
+}
+
+impl Terminal for CastTerminal {
+    fn hide_cursor(&mut self) {
+        self.inner.hide_cursor();
+        self.record("\x1b[?25l");
+    }
+
+    fn show_cursor(&mut self) {
+        self.inner.show_cursor();
+        self.record("\x1b[?25h");
+    }
+
+    fn draw(&mut self, frame: &str) {
+        self.inner.draw(frame);
+        let lines: Vec<&str> = frame.lines().collect();
+        self.record(&format!("\x1b[2J\x1b[1;1H{}\r\n", lines.join("\r\n")));
+    }
+
+    fn has_color(&self) -> bool {
+        self.inner.has_color()
+    }
+
+    fn bell(&mut self) {
+        self.inner.bell();
+        self.record("\x07");
+    }
+}
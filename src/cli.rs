@@ -0,0 +1,727 @@
+use std::{
+    net::SocketAddr,
+    time::Duration
+};
+
+use crate::{
+    ai::Tier,
+    campaign::progress,
+    config::{
+        GameConfig, GameConfigBuilder, MAX_INPUT_DELAY, MAX_PACE, MAX_START_LENGTH, MAX_TIME_LIMIT, MAX_TIMEOUT, MIN_PACE
+    },
+    game::{
+        GameMode, SocketMode
+    },
+    leaderboard::is_valid_url,
+    level::{
+        is_valid_size, MAX_OBSTACLES
+    },
+    net::{
+        Lobby, MAX_NAME_LENGTH, MAX_PLAYERS
+    },
+    relay::MAX_CODE_LENGTH,
+    simulate::MAX_GAMES,
+    sync::SyncMode,
+    token::JoinToken
+};
+
+pub enum Command {
+    Game(GameMode, GameConfig, Option<u64>),
+    Spectate(SocketAddr, GameConfig),
+    Serve(u16, Option<String>, Option<u16>),
+    Discover,
+    List(SocketAddr, GameConfig, Option<u64>),
+    Stats,
+    Leaderboard(String),
+    Replay(String, GameConfig),
+    Simulate(GameConfig, Option<u64>, usize),
+    Help(Option<&'static str>)
+}
+
+struct Flag {
+    name: &'static str,
+    value: &'static str,
+    help: &'static str
+}
+
+struct Subcommand {
+    name: &'static str,
+    address: &'static str,
+    about: &'static str,
+    flags: &'static [Flag]
+}
+
+const DIFFICULTY: Flag = Flag { name: "--difficulty", value: "easy|normal|hard", help: "Pace, board size, obstacles and growth preset (default normal)" };
+const PACE: Flag = Flag { name: "--pace", value: "<ms>", help: "Milliseconds per tick (20 to 5000), the host's pace is used in multiplayer" };
+const SEED: Flag = Flag { name: "--seed", value: "<number>", help: "Seed for the random generator" };
+const BOARD: Flag = Flag { name: "--board", value: "<width>x<height>", help: "Board size" };
+const WALLS: Flag = Flag { name: "--walls", value: "", help: "Make the board edges deadly instead of wrapping around" };
+const LEVEL: Flag = Flag { name: "--level", value: "<path>", help: "Load the board layout from a level file" };
+const BOT_CMD: Flag = Flag { name: "--bot-cmd", value: "<program>", help: "Let an external program steer the snake" };
+const BOT: Flag = Flag { name: "--bot", value: "greedy|astar|hamilton", help: "Add a computer opponent of this strength as the last player" };
+const OBSTACLES: Flag = Flag { name: "--obstacles", value: "<percent>%", help: "Scatter random walls over the board (up to 50%)" };
+const START_LENGTH: Flag = Flag { name: "--start-length", value: "<cells>", help: "Start every snake with a body of this length (default 1)" };
+const INPUT_DELAY: Flag = Flag { name: "--input-delay", value: "<ticks>", help: "Apply every turn this many ticks after it was made (0 to 10, default 1)" };
+const TIME_ATTACK: Flag = Flag { name: "--time-attack", value: "<seconds>", help: "Grow as much as possible before the time runs out" };
+const ZEN: Flag = Flag { name: "--zen", value: "", help: "Practice without dying, crashes shrink the snake and cost points" };
+const RESTART: Flag = Flag { name: "--restart", value: "", help: "Start the campaign over from the first stage" };
+const NAME: Flag = Flag { name: "--name", value: "<name>", help: "Name shown to the other players (up to 16 characters)" };
+const SUBMIT_SCORE: Flag = Flag { name: "--submit-score", value: "<url>", help: "Send the final score to a leaderboard service over HTTP" };
+const RECORD: Flag = Flag { name: "--record", value: "<path>", help: "Save the game as a replay file" };
+const PLAYERS: Flag = Flag { name: "--players", value: "<count>", help: "Number of players, including the host (2 to 4)" };
+const LOCAL_PLAYERS: Flag = Flag { name: "--players", value: "<count>", help: "Number of players sharing the keyboard (2 to 4)" };
+const AUTHORITATIVE: Flag = Flag { name: "--authoritative", value: "", help: "Run the game on the host only and stream the state to the clients" };
+const SYNC: Flag = Flag { name: "--sync", value: "lockstep|free", help: "Wait for every player each tick, or run ahead and repair drift from the host state (default lockstep)" };
+const ALLOW_PUBLIC: Flag = Flag { name: "--allow-public", value: "", help: "Accept connections on a public address (needs --secret)" };
+const LOBBY: Flag = Flag { name: "--lobby", value: "", help: "Register a game on a relay and get a code back" };
+const CODE: Flag = Flag { name: "--code", value: "<code>", help: "Join the relay game registered under the code" };
+const DIRECT: Flag = Flag { name: "--direct", value: "", help: "Try a direct connection once the relay paired the players (Linux only)" };
+const WEBSOCKET: Flag = Flag { name: "--websocket", value: "", help: "Talk to the server over WebSocket" };
+const SECRET: Flag = Flag { name: "--secret", value: "<token>", help: "Shared token every connection must present" };
+const PASSWORD: Flag = Flag { name: "--password", value: "<password>", help: "Password the host challenges every player and spectator for (not with --lobby or --code)" };
+const QR: Flag = Flag { name: "--qr", value: "", help: "Draw the join token as a QR code in the terminal" };
+const NO_COLOR: Flag = Flag { name: "--no-color", value: "", help: "Draw the board without colors" };
+const EMOJI: Flag = Flag { name: "--emoji", value: "", help: "Draw snakes, targets and crashes as emoji" };
+const SOUND: Flag = Flag { name: "--sound", value: "", help: "Ring the terminal bell when a snake eats or crashes" };
+const EVENT_LOG: Flag = Flag { name: "--event-log", value: "<path>", help: "Write every game event to a file, one per line" };
+const EXPORT_CAST: Flag = Flag { name: "--export-cast", value: "<path>", help: "Record the game as an asciicast v2 file" };
+const EXPORT_STATS: Flag = Flag { name: "--export-stats", value: "<path>", help: "Append a record of every finished game to this file (JSON lines, or CSV for .csv files)" };
+const TIMEOUT: Flag = Flag { name: "--timeout", value: "<seconds>s", help: "Give up connecting or waiting for the other players after this long" };
+const WEB_SPECTATE: Flag = Flag { name: "--web-spectate", value: "<port>", help: "Stream the game to browsers on the local network at this port" };
+const STATE_API: Flag = Flag { name: "--state-api", value: "<port>", help: "Serve the live game state as JSON at http://127.0.0.1:<port>/state" };
+const GAMES: Flag = Flag { name: "--games", value: "<count>", help: "Number of games to play (1 to 1000, default 1)" };
+const SIM_PLAYERS: Flag = Flag { name: "--players", value: "<count>", help: "Number of computer players (2 to 4)" };
+const SIM_BOT: Flag = Flag { name: "--bot", value: "greedy|astar|hamilton", help: "Strength of every computer player (default astar)" };
+const PORT: Flag = Flag { name: "--port", value: "<port>", help: "Port to relay games at" };
+const METRICS_PORT: Flag = Flag { name: "--metrics-port", value: "<port>", help: "Serve Prometheus metrics at /metrics on this port" };
+
+const SUBCOMMANDS: &[Subcommand] = &[
+    Subcommand {
+        name: "play",
+        address: "",
+        about: "Play a singleplayer game",
+        flags: &[DIFFICULTY, PACE, SEED, BOARD, WALLS, LEVEL, OBSTACLES, START_LENGTH, TIME_ATTACK, ZEN, BOT, BOT_CMD, SUBMIT_SCORE, RECORD, WEB_SPECTATE, STATE_API, NO_COLOR, EMOJI, SOUND, EVENT_LOG, EXPORT_CAST, EXPORT_STATS]
+    },
+    Subcommand {
+        name: "campaign",
+        address: "",
+        about: "Clear stage after stage of growing boards",
+        flags: &[SEED, BOT_CMD, NO_COLOR, EMOJI, SOUND, EVENT_LOG, EXPORT_CAST, EXPORT_STATS, RESTART]
+    },
+    Subcommand {
+        name: "local",
+        address: "",
+        about: "Play against each other on one keyboard",
+        flags: &[DIFFICULTY, PACE, SEED, BOARD, WALLS, LEVEL, START_LENGTH, LOCAL_PLAYERS, BOT, WEB_SPECTATE, STATE_API, NO_COLOR, EMOJI, SOUND, EVENT_LOG, EXPORT_CAST, EXPORT_STATS]
+    },
+    Subcommand {
+        name: "host",
+        address: "<interface>:<port>",
+        about: "Host a multiplayer game",
+        flags: &[DIFFICULTY, PACE, SEED, BOARD, WALLS, LEVEL, START_LENGTH, INPUT_DELAY, BOT_CMD, NAME, PLAYERS, AUTHORITATIVE, SYNC, ALLOW_PUBLIC, SECRET, PASSWORD, QR, TIMEOUT, WEB_SPECTATE, STATE_API, NO_COLOR, EMOJI, SOUND, EVENT_LOG, EXPORT_CAST, EXPORT_STATS]
+    },
+    Subcommand {
+        name: "join",
+        address: "<host>:<port>|<token>",
+        about: "Join a multiplayer game or a relay",
+        flags: &[DIFFICULTY, PACE, SEED, BOARD, WALLS, LEVEL, START_LENGTH, INPUT_DELAY, BOT_CMD, NAME, SYNC, LOBBY, CODE, DIRECT, WEBSOCKET, SECRET, PASSWORD, TIMEOUT, WEB_SPECTATE, STATE_API, NO_COLOR, EMOJI, SOUND, EVENT_LOG, EXPORT_CAST, EXPORT_STATS]
+    },
+    Subcommand {
+        name: "spectate",
+        address: "<host>:<port>|<token>",
+        about: "Watch a multiplayer game",
+        flags: &[WEBSOCKET, SECRET, PASSWORD, TIMEOUT, NO_COLOR, EMOJI]
+    },
+    Subcommand {
+        name: "discover",
+        address: "",
+        about: "List the games waiting for players on the local network",
+        flags: &[]
+    },
+    Subcommand {
+        name: "list",
+        address: "<host>:<port>",
+        about: "List the open rooms on a relay and pick one to join",
+        flags: &[DIFFICULTY, SEED, START_LENGTH, INPUT_DELAY, BOT_CMD, NAME, SYNC, DIRECT, WEBSOCKET, SECRET, TIMEOUT, WEB_SPECTATE, STATE_API, NO_COLOR, EMOJI, SOUND, EVENT_LOG, EXPORT_CAST, EXPORT_STATS]
+    },
+    Subcommand {
+        name: "replay",
+        address: "<path>",
+        about: "Watch a recorded game",
+        flags: &[NO_COLOR, EMOJI, EXPORT_CAST]
+    },
+    Subcommand {
+        name: "simulate",
+        address: "",
+        about: "Let computer players fight it out without drawing and print who won",
+        flags: &[DIFFICULTY, SEED, BOARD, WALLS, LEVEL, START_LENGTH, SIM_PLAYERS, SIM_BOT, GAMES]
+    },
+    Subcommand {
+        name: "stats",
+        address: "",
+        about: "Show the games played, apples eaten and the record against every opponent",
+        flags: &[]
+    },
+    Subcommand {
+        name: "leaderboard",
+        address: "<url>",
+        about: "Show the best scores submitted to a leaderboard service",
+        flags: &[]
+    },
+    Subcommand {
+        name: "serve",
+        address: "",
+        about: "Relay games between players that can't reach each other",
+        flags: &[PORT, SECRET, METRICS_PORT]
+    }
+];
+
+#[derive(Default)]
+struct Options {
+    address: Option<SocketAddr>,
+    url: Option<String>,
+    path: Option<String>,
+    config: Option<GameConfig>,
+    pace: Option<Duration>,
+    seed: Option<u64>,
+    size: Option<(usize, usize)>,
+    walls: bool,
+    level: Option<String>,
+    obstacles: Option<usize>,
+    start_length: Option<usize>,
+    input_delay: Option<u64>,
+    time_limit: Option<Duration>,
+    zen: bool,
+    restart: bool,
+    bot_cmd: Option<String>,
+    opponent: Option<Tier>,
+    name: Option<String>,
+    submit_score: Option<String>,
+    record: Option<String>,
+    players: Option<usize>,
+    authoritative: bool,
+    sync: Option<SyncMode>,
+    allow_public: bool,
+    lobby: Option<Lobby>,
+    direct: bool,
+    websocket: bool,
+    secret: Option<String>,
+    password: Option<String>,
+    qr: bool,
+    timeout: Option<Duration>,
+    web_spectate: Option<u16>,
+    state_api: Option<u16>,
+    no_color: bool,
+    emoji: bool,
+    sound: bool,
+    event_log: Option<String>,
+    export_cast: Option<String>,
+    export_stats: Option<String>,
+    games: Option<usize>,
+    port: Option<u16>,
+    metrics_port: Option<u16>
+}
+
+impl Options {
+    fn set(&mut self, flag: &str, value: String) -> Option<()> {
+        match flag {
+            "--difficulty" => {
+                self.config = Some(GameConfig::difficulty(&value)?);
+            },
+            "--pace" => {
+                match value.parse::<u64>() {
+                    Ok(pace) if (MIN_PACE..=MAX_PACE).contains(&pace) => {
+                        self.pace = Some(Duration::from_millis(pace));
+                    },
+                    _ => {
+                        return None;
+                    }
+                }
+            },
+            "--seed" => {
+                self.seed = Some(value.parse::<u64>().ok()?);
+            },
+            "--board" => {
+                let (width, height) = value.split_once('x')?;
+                let size = (width.parse::<usize>().ok()?, height.parse::<usize>().ok()?);
+                if !is_valid_size(size.0, size.1) {
+                    return None;
+                }
+
+                self.size = Some(size);
+            },
+            "--walls" => {
+                self.walls = true;
+            },
+            "--level" => {
+                self.level = Some(value);
+            },
+            "--obstacles" => {
+                match value.trim_end_matches('%').parse::<usize>() {
+                    Ok(percent) if percent <= MAX_OBSTACLES => {
+                        self.obstacles = Some(percent);
+                    },
+                    _ => {
+                        return None;
+                    }
+                }
+            },
+            "--start-length" => {
+                match value.parse::<usize>() {
+                    Ok(length) if (1..=MAX_START_LENGTH).contains(&length) => {
+                        self.start_length = Some(length);
+                    },
+                    _ => {
+                        return None;
+                    }
+                }
+            },
+            "--input-delay" => {
+                match value.parse::<u64>() {
+                    Ok(delay) if delay <= MAX_INPUT_DELAY => {
+                        self.input_delay = Some(delay);
+                    },
+                    _ => {
+                        return None;
+                    }
+                }
+            },
+            "--time-attack" => {
+                match value.parse::<u64>() {
+                    Ok(seconds) if (1..=MAX_TIME_LIMIT).contains(&seconds) => {
+                        self.time_limit = Some(Duration::from_secs(seconds));
+                    },
+                    _ => {
+                        return None;
+                    }
+                }
+            },
+            "--zen" => {
+                self.zen = true;
+            },
+            "--restart" => {
+                self.restart = true;
+            },
+            "--bot" => {
+                self.opponent = Some(Tier::parse(&value)?);
+            },
+            "--bot-cmd" => {
+                self.bot_cmd = Some(value);
+            },
+            "--name" => {
+                if value.is_empty() || value.chars().count() > MAX_NAME_LENGTH || value.chars().any(|c| c.is_control()) {
+                    return None;
+                }
+
+                self.name = Some(value);
+            },
+            "--submit-score" if is_valid_url(&value) => {
+                self.submit_score = Some(value);
+            },
+            "--record" => {
+                self.record = Some(value);
+            },
+            "--players" => {
+                match value.parse::<usize>() {
+                    Ok(count) if (2..=MAX_PLAYERS).contains(&count) => {
+                        self.players = Some(count);
+                    },
+                    _ => {
+                        return None;
+                    }
+                }
+            },
+            "--authoritative" => {
+                self.authoritative = true;
+            },
+            "--sync" => {
+                self.sync = Some(SyncMode::parse(&value)?);
+            },
+            "--allow-public" => {
+                self.allow_public = true;
+            },
+            "--lobby" => {
+                self.lobby = Some(Lobby::Register);
+            },
+            "--code" => {
+                if value.is_empty() || value.len() > MAX_CODE_LENGTH || !value.chars().all(|c| c.is_ascii_alphanumeric()) {
+                    return None;
+                }
+
+                self.lobby = Some(Lobby::Join(value.to_uppercase()));
+            },
+            "--direct" => {
+                self.direct = true;
+            },
+            "--websocket" => {
+                self.websocket = true;
+            },
+            "--secret" if !value.is_empty() => {
+                self.secret = Some(value);
+            },
+            "--password" if !value.is_empty() => {
+                self.password = Some(value);
+            },
+            "--qr" => {
+                self.qr = true;
+            },
+            "--timeout" => {
+                match value.strip_suffix('s').unwrap_or(&value).parse::<u64>() {
+                    Ok(seconds) if (1..=MAX_TIMEOUT).contains(&seconds) => {
+                        self.timeout = Some(Duration::from_secs(seconds));
+                    },
+                    _ => {
+                        return None;
+                    }
+                }
+            },
+            "--web-spectate" => {
+                self.web_spectate = Some(value.parse::<u16>().ok()?);
+            },
+            "--state-api" => {
+                self.state_api = Some(value.parse::<u16>().ok()?);
+            },
+            "--no-color" => {
+                self.no_color = true;
+            },
+            "--emoji" => {
+                self.emoji = true;
+            },
+            "--sound" => {
+                self.sound = true;
+            },
+            "--event-log" => {
+                self.event_log = Some(value);
+            },
+            "--export-cast" => {
+                self.export_cast = Some(value);
+            },
+            "--export-stats" => {
+                self.export_stats = Some(value);
+            },
+            "--games" => {
+                match value.parse::<usize>() {
+                    Ok(games) if (1..=MAX_GAMES).contains(&games) => {
+                        self.games = Some(games);
+                    },
+                    _ => {
+                        return None;
+                    }
+                }
+            },
+            "--port" => {
+                self.port = Some(value.parse::<u16>().ok()?);
+            },
+            "--metrics-port" => {
+                self.metrics_port = Some(value.parse::<u16>().ok()?);
+            },
+            _ => {
+                return None;
+            }
+        }
+
+        Some(())
+    }
+
+    #[allow(clippy::single_match)]
+    fn game_config(self) -> Option<GameConfig> {
+        let mut builder = GameConfigBuilder::new(self.config.unwrap_or_else(GameConfig::normal));
+        match self.pace {
+            Some(pace) => {
+                builder = builder.pace(pace);
+            },
+            None => {}
+        }
+
+        match self.size {
+            Some((width, height)) => {
+                builder = builder.board(width, height);
+            },
+            None => {}
+        }
+
+        match self.obstacles {
+            Some(obstacles) => {
+                builder = builder.obstacles(obstacles);
+            },
+            None => {}
+        }
+
+        match self.start_length {
+            Some(length) => {
+                builder = builder.start_length(length);
+            },
+            None => {}
+        }
+
+        match self.input_delay {
+            Some(delay) => {
+                builder = builder.input_delay(delay);
+            },
+            None => {}
+        }
+
+        match self.players {
+            Some(players) => {
+                builder = builder.players(players);
+            },
+            None => {}
+        }
+
+        match self.time_limit {
+            Some(limit) => {
+                builder = builder.time_limit(limit);
+            },
+            None => {}
+        }
+
+        match &self.level {
+            Some(path) => {
+                builder = builder.level(path);
+            },
+            None => {}
+        }
+
+        match &self.bot_cmd {
+            Some(program) => {
+                builder = builder.bot_cmd(program);
+            },
+            None => {}
+        }
+
+        match &self.name {
+            Some(name) => {
+                builder = builder.name(name);
+            },
+            None => {}
+        }
+
+        match self.opponent {
+            Some(tier) => {
+                builder = builder.opponent(tier);
+            },
+            None => {}
+        }
+
+        if self.walls {
+            builder = builder.walls(true);
+        }
+
+        if self.zen {
+            builder = builder.zen(true);
+        }
+
+        let mut config = builder.color(!self.no_color).emoji(self.emoji).sound(self.sound).build().ok()?;
+        config.websocket = self.websocket;
+        config.authoritative = self.authoritative;
+        config.sync = self.sync.unwrap_or(SyncMode::Lockstep);
+        config.lobby = self.lobby;
+        config.direct = self.direct;
+        config.allow_public = self.allow_public;
+        config.secret = self.secret;
+        config.password = self.password;
+        config.qr = self.qr;
+        config.timeout = self.timeout;
+        config.web_spectate = self.web_spectate;
+        config.state_api = self.state_api;
+        config.submit_score = self.submit_score;
+        config.record = self.record;
+        config.event_log = self.event_log;
+        config.export_cast = self.export_cast;
+        config.export_stats = self.export_stats;
+        Some(config)
+    }
+
+    fn command(self, name: &str) -> Option<Command> {
+        if self.allow_public && self.secret.is_none() {
+            return None;
+        }
+
+        if self.password.is_some() && self.lobby.is_some() {
+            return None;
+        }
+
+        let command = match name {
+            "play" => {
+                let seed = self.seed;
+                let mode = if self.opponent.is_some() { GameMode::Local } else { GameMode::Singleplayer };
+                Command::Game(mode, self.game_config()?, seed)
+            },
+            "local" => {
+                let seed = self.seed;
+                Command::Game(GameMode::Local, self.game_config()?, seed)
+            },
+            "campaign" => {
+                let stage = if self.restart { 0 } else { progress() };
+                let seed = self.seed;
+                let mut config = self.game_config()?;
+                config.campaign = Some(stage);
+                Command::Game(GameMode::Singleplayer, config, seed)
+            },
+            "host" => {
+                let (local, seed) = (self.address?, self.seed);
+                Command::Game(GameMode::Multiplayer(SocketMode::Server(local)), self.game_config()?, seed)
+            },
+            "join" => {
+                let (remote, seed) = (self.address?, self.seed);
+                Command::Game(GameMode::Multiplayer(SocketMode::Client(remote)), self.game_config()?, seed)
+            },
+            "spectate" => {
+                let remote = self.address?;
+                Command::Spectate(remote, self.game_config()?)
+            },
+            "discover" => Command::Discover,
+            "list" => {
+                let (remote, seed) = (self.address?, self.seed);
+                Command::List(remote, self.game_config()?, seed)
+            },
+            "stats" => Command::Stats,
+            "leaderboard" => Command::Leaderboard(self.url?),
+            "replay" => {
+                let path = self.path.clone()?;
+                Command::Replay(path, self.game_config()?)
+            },
+            "simulate" => {
+                let (seed, games) = (self.seed, self.games.unwrap_or(1));
+                let mut config = self.game_config()?;
+                config.opponent = Some(config.opponent.unwrap_or(Tier::AStar));
+                Command::Simulate(config, seed, games)
+            },
+            "serve" => Command::Serve(self.port?, self.secret, self.metrics_port),
+            _ => {
+                return None;
+            }
+        };
+
+        Some(command)
+    }
+}
+
+pub fn parse(args: Vec<String>) -> Result<Command, Option<&'static str>> {
+    let mut args = args.into_iter();
+    let name = args.next().unwrap_or_else(|| "play".to_string());
+
+    if name == "help" || name == "--help" || name == "-h" {
+        return match (args.next(), args.next()) {
+            (None, _) => Ok(Command::Help(None)),
+            (Some(topic), None) => {
+                match subcommand(&topic) {
+                    Some(subcommand) => Ok(Command::Help(Some(subcommand.name))),
+                    None => Err(None)
+                }
+            },
+            _ => Err(None)
+        };
+    }
+
+    let subcommand = match subcommand(&name) {
+        Some(subcommand) => subcommand,
+        None => {
+            return Err(None);
+        }
+    };
+
+    let mut options = Options::default();
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            return Ok(Command::Help(Some(subcommand.name)));
+        }
+
+        if !arg.starts_with("--") && subcommand.address == "<path>" && options.path.is_none() {
+            options.path = Some(arg);
+            continue;
+        }
+
+        if !arg.starts_with("--") && subcommand.address == "<url>" && options.url.is_none() {
+            if !is_valid_url(&arg) {
+                return Err(Some(subcommand.name));
+            }
+
+            options.url = Some(arg);
+            continue;
+        }
+
+        if !arg.starts_with("--") && !subcommand.address.is_empty() && options.address.is_none() {
+            options.address = match (arg.parse::<SocketAddr>(), JoinToken::decode(&arg)) {
+                (Ok(address), _) => Some(address),
+                (Err(_), Some(token)) if (subcommand.name == "join" || subcommand.name == "spectate") && is_valid_size(token.width, token.height) => {
+                    options.size = Some((token.width, token.height));
+                    options.walls = token.walls;
+                    options.password = token.password;
+                    options.secret = token.secret;
+                    Some(token.address)
+                },
+                _ => {
+                    return Err(Some(subcommand.name));
+                }
+            };
+            continue;
+        }
+
+        let flag = match subcommand.flags.iter().find(|flag| flag.name == arg) {
+            Some(flag) => flag,
+            None => {
+                return Err(Some(subcommand.name));
+            }
+        };
+
+        let value = if flag.value.is_empty() {
+            String::new()
+        } else {
+            match args.next() {
+                Some(value) => value,
+                None => {
+                    return Err(Some(subcommand.name));
+                }
+            }
+        };
+
+        if options.set(flag.name, value).is_none() {
+            return Err(Some(subcommand.name));
+        }
+    }
+
+    match options.command(subcommand.name) {
+        Some(command) => Ok(command),
+        None => Err(Some(subcommand.name))
+    }
+}
+
+pub fn usage(topic: Option<&str>) -> String {
+    let subcommand = match topic.and_then(subcommand) {
+        Some(subcommand) => subcommand,
+        None => {
+            let mut usage = String::from("Usage: snake-game <command> [options]\n\nCommands:\n");
+            for subcommand in SUBCOMMANDS {
+                usage.push_str(&format!("  {:<10}{}\n", subcommand.name, subcommand.about));
+            }
+
+            usage.push_str("\nRun `snake-game <command> --help` for the options of a command.");
+            return usage;
+        }
+    };
+
+    let mut usage = format!("Usage: snake-game {}", subcommand.name);
+    if !subcommand.address.is_empty() {
+        usage.push_str(&format!(" {}", subcommand.address));
+    }
+
+    if !subcommand.flags.is_empty() {
+        usage.push_str(" [options]");
+    }
+
+    usage.push_str(&format!("\n\n{}\n", subcommand.about));
+    if !subcommand.flags.is_empty() {
+        usage.push_str("\nOptions:\n");
+        for flag in subcommand.flags {
+            let syntax = format!("{} {}", flag.name, flag.value);
+            usage.push_str(&format!("  {:<32}{}\n", syntax.trim_end(), flag.help));
+        }
+    }
+
+    usage.trim_end().to_string()
+}
+
+fn subcommand(name: &str) -> Option<&'static Subcommand> {
+    SUBCOMMANDS.iter().find(|subcommand| subcommand.name == name)
+}
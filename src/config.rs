@@ -0,0 +1,300 @@
+use std::time::Duration;
+
+use crate::{
+    ai::Tier,
+    board::{
+        BOARD_HEIGHT, BOARD_WIDTH
+    },
+    error::SnakeError,
+    level::{
+        is_valid_size, MAX_OBSTACLES
+    },
+    net::{
+        Lobby, MAX_NAME_LENGTH, MAX_PLAYERS
+    },
+    sync::SyncMode
+};
+
+pub const GAME_PACE: Duration = Duration::from_millis(350);
+pub const MIN_PACE: u64 = 20;
+pub const MAX_PACE: u64 = 5000;
+pub const MAX_TIME_LIMIT: u64 = 3600;
+pub const MAX_START_LENGTH: usize = 100;
+pub const INPUT_DELAY: u64 = 1;
+pub const MAX_INPUT_DELAY: u64 = 10;
+pub const MAX_TIMEOUT: u64 = 3600;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameConfig {
+    pub pace: Duration,
+    pub width: usize,
+    pub height: usize,
+    pub walls: bool,
+    pub level: Option<String>,
+    pub obstacles: usize,
+    pub growth: usize,
+    pub players: usize,
+    pub bot_cmd: Option<String>,
+    pub opponent: Option<Tier>,
+    pub websocket: bool,
+    pub authoritative: bool,
+    pub sync: SyncMode,
+    pub lobby: Option<Lobby>,
+    pub direct: bool,
+    pub allow_public: bool,
+    pub secret: Option<String>,
+    pub password: Option<String>,
+    pub qr: bool,
+    pub color: bool,
+    pub emoji: bool,
+    pub sound: bool,
+    pub event_log: Option<String>,
+    pub export_cast: Option<String>,
+    pub export_stats: Option<String>,
+    pub time_limit: Option<Duration>,
+    pub zen: bool,
+    pub campaign: Option<usize>,
+    pub goal: Option<usize>,
+    pub start_length: usize,
+    pub input_delay: u64,
+    pub name: Option<String>,
+    pub seed: u64,
+    pub submit_score: Option<String>,
+    pub record: Option<String>,
+    pub timeout: Option<Duration>,
+    pub web_spectate: Option<u16>,
+    pub state_api: Option<u16>
+}
+
+impl GameConfig {
+    pub fn easy() -> Self {
+        GameConfig {
+            pace: Duration::from_millis(450),
+            width: 12,
+            height: 10,
+            obstacles: 0,
+            growth: 1,
+            ..GameConfig::normal()
+        }
+    }
+
+    pub fn normal() -> Self {
+        GameConfig {
+            pace: GAME_PACE,
+            width: BOARD_WIDTH,
+            height: BOARD_HEIGHT,
+            walls: false,
+            level: None,
+            obstacles: 0,
+            growth: 1,
+            players: 2,
+            bot_cmd: None,
+            opponent: None,
+            websocket: false,
+            authoritative: false,
+            sync: SyncMode::Lockstep,
+            lobby: None,
+            direct: false,
+            allow_public: false,
+            secret: None,
+            password: None,
+            qr: false,
+            color: true,
+            emoji: false,
+            sound: false,
+            event_log: None,
+            export_cast: None,
+            export_stats: None,
+            time_limit: None,
+            zen: false,
+            campaign: None,
+            goal: None,
+            start_length: 1,
+            input_delay: INPUT_DELAY,
+            name: None,
+            seed: 0,
+            submit_score: None,
+            record: None,
+            timeout: None,
+            web_spectate: None,
+            state_api: None
+        }
+    }
+
+    pub fn hard() -> Self {
+        GameConfig {
+            pace: Duration::from_millis(200),
+            width: 16,
+            height: 12,
+            obstacles: 10,
+            growth: 2,
+            ..GameConfig::normal()
+        }
+    }
+
+    pub fn difficulty(name: &str) -> Option<Self> {
+        match name {
+            "easy" => Some(GameConfig::easy()),
+            "normal" => Some(GameConfig::normal()),
+            "hard" => Some(GameConfig::hard()),
+            _ => None
+        }
+    }
+
+    pub fn builder() -> GameConfigBuilder {
+        GameConfigBuilder::new(GameConfig::normal())
+    }
+}
+
+pub struct GameConfigBuilder {
+    config: GameConfig
+}
+
+impl GameConfigBuilder {
+    pub fn new(config: GameConfig) -> Self {
+        GameConfigBuilder { config }
+    }
+
+    pub fn board(mut self, width: usize, height: usize) -> Self {
+        self.config.width = width;
+        self.config.height = height;
+        self
+    }
+
+    pub fn pace(mut self, pace: Duration) -> Self {
+        self.config.pace = pace;
+        self
+    }
+
+    pub fn pace_ms(self, pace: u64) -> Self {
+        self.pace(Duration::from_millis(pace))
+    }
+
+    pub fn walls(mut self, walls: bool) -> Self {
+        self.config.walls = walls;
+        self
+    }
+
+    pub fn level(mut self, path: &str) -> Self {
+        self.config.level = Some(path.into());
+        self
+    }
+
+    pub fn obstacles(mut self, percent: usize) -> Self {
+        self.config.obstacles = percent;
+        self
+    }
+
+    pub fn growth(mut self, growth: usize) -> Self {
+        self.config.growth = growth;
+        self
+    }
+
+    pub fn players(mut self, players: usize) -> Self {
+        self.config.players = players;
+        self
+    }
+
+    pub fn start_length(mut self, length: usize) -> Self {
+        self.config.start_length = length;
+        self
+    }
+
+    pub fn input_delay(mut self, ticks: u64) -> Self {
+        self.config.input_delay = ticks;
+        self
+    }
+
+    pub fn time_limit(mut self, limit: Duration) -> Self {
+        self.config.time_limit = Some(limit);
+        self
+    }
+
+    pub fn zen(mut self, zen: bool) -> Self {
+        self.config.zen = zen;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = seed;
+        self
+    }
+
+    pub fn opponent(mut self, tier: Tier) -> Self {
+        self.config.opponent = Some(tier);
+        self
+    }
+
+    pub fn bot_cmd(mut self, program: &str) -> Self {
+        self.config.bot_cmd = Some(program.into());
+        self
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.config.name = Some(name.into());
+        self
+    }
+
+    pub fn color(mut self, color: bool) -> Self {
+        self.config.color = color;
+        self
+    }
+
+    pub fn emoji(mut self, emoji: bool) -> Self {
+        self.config.emoji = emoji;
+        self
+    }
+
+    pub fn sound(mut self, sound: bool) -> Self {
+        self.config.sound = sound;
+        self
+    }
+
+    pub fn build(self) -> Result<GameConfig, SnakeError> {
+        let config = self.config;
+        let pace = config.pace.as_millis() as u64;
+        if !(MIN_PACE..=MAX_PACE).contains(&pace) {
+            return Err(SnakeError::BadConfig(format!("pace must be {} to {}ms", MIN_PACE, MAX_PACE)));
+        }
+
+        if !is_valid_size(config.width, config.height) {
+            return Err(SnakeError::BadConfig("bad board size".into()));
+        }
+
+        if config.obstacles > MAX_OBSTACLES {
+            return Err(SnakeError::BadConfig(format!("obstacles must be at most {}%", MAX_OBSTACLES)));
+        }
+
+        if config.growth == 0 {
+            return Err(SnakeError::BadConfig("growth must be at least 1".into()));
+        }
+
+        if !(2..=MAX_PLAYERS).contains(&config.players) {
+            return Err(SnakeError::BadConfig(format!("players must be 2 to {}", MAX_PLAYERS)));
+        }
+
+        if !(1..=MAX_START_LENGTH).contains(&config.start_length) {
+            return Err(SnakeError::BadConfig(format!("start length must be 1 to {}", MAX_START_LENGTH)));
+        }
+
+        if config.input_delay > MAX_INPUT_DELAY {
+            return Err(SnakeError::BadConfig(format!("input delay must be at most {} ticks", MAX_INPUT_DELAY)));
+        }
+
+        match config.time_limit {
+            Some(limit) if !(1..=MAX_TIME_LIMIT).contains(&limit.as_secs()) => {
+                return Err(SnakeError::BadConfig(format!("time limit must be 1 to {}s", MAX_TIME_LIMIT)));
+            },
+            _ => {}
+        }
+
+        match &config.name {
+            Some(name) if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH || name.chars().any(|c| c.is_control()) => {
+                return Err(SnakeError::BadConfig(format!("name must be 1 to {} printable characters", MAX_NAME_LENGTH)));
+            },
+            _ => {}
+        }
+
+        Ok(config)
+    }
+}
@@ -9,23 +9,48 @@ pub enum Direction {
 }
 
 impl Direction {
-    pub fn from(value: u8) -> Direction {
+    pub fn from(value: u8) -> Option<Direction> {
         match value {
-            0x00 => {
-                Direction::Right
-            },
-            0x01 => {
-                Direction::Down
-            },
-            0x02 => {
-                Direction::Left
-            },
-            0x03 => {
-                Direction::Up
-            },
-            _ => {
-                panic!("bad direction [Direction::from()]");
-            }
+            0x00 => Some(Direction::Right),
+            0x01 => Some(Direction::Down),
+            0x02 => Some(Direction::Left),
+            0x03 => Some(Direction::Up),
+            _ => None
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Direction> {
+        match name {
+            "right" => Some(Direction::Right),
+            "down" => Some(Direction::Down),
+            "left" => Some(Direction::Left),
+            "up" => Some(Direction::Up),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::Right => "right",
+            Direction::Down => "down",
+            Direction::Left => "left",
+            Direction::Up => "up"
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Right => Direction::Left,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Up => Direction::Down
+        }
+    }
+
+    pub fn perpendicular(&self) -> [Direction; 2] {
+        match self {
+            Direction::Right | Direction::Left => [Direction::Down, Direction::Up],
+            Direction::Down | Direction::Up => [Direction::Right, Direction::Left]
         }
     }
 
@@ -0,0 +1,204 @@
+use std::{
+    io::stdin,
+    net::{
+        Ipv4Addr, SocketAddr, UdpSocket
+    },
+    sync::{
+        atomic::{
+            AtomicUsize, Ordering
+        },
+        Arc
+    },
+    thread::spawn,
+    time::{
+        Duration, Instant
+    }
+};
+
+use crate::{
+    config::GameConfig,
+    error::{
+        ProtocolError, SnakeError
+    },
+    net::Network,
+    packet::{
+        Opcode, Packet, PacketReader
+    },
+    relay::MAX_CODE_LENGTH
+};
+
+pub const DISCOVERY_PORT: u16 = 7475;
+
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(1);
+const ADVERTISE_TIMEOUT: Duration = Duration::from_millis(500);
+const MAX_DATAGRAM_SIZE: usize = 512;
+const MAX_NAME_LENGTH: usize = 64;
+
+#[derive(Clone)]
+pub struct Listing {
+    pub port: u16,
+    pub width: usize,
+    pub height: usize,
+    pub walls: bool,
+    pub pace: u64,
+    pub players: usize,
+    pub joined: usize,
+    pub code: String,
+    pub name: String
+}
+
+impl Listing {
+    #[allow(clippy::identity_op)]
+    pub fn encode(&self) -> Result<Packet, SnakeError> {
+        let name: String = self.name.chars().take(MAX_NAME_LENGTH).collect();
+        let code: String = self.code.chars().take(MAX_CODE_LENGTH).collect();
+        let mut packet = Packet::new(Opcode::Announce, 12 + code.len() + name.len());
+        packet.push_data(&[(self.port >> 8) as u8, (self.port >> 0) as u8])?;
+        packet.push_data(&[(self.width >> 8) as u8, (self.width >> 0) as u8])?;
+        packet.push_data(&[(self.height >> 8) as u8, (self.height >> 0) as u8])?;
+        packet.push_data(&[self.walls as u8, (self.pace >> 8) as u8, (self.pace >> 0) as u8, self.players as u8, self.joined as u8])?;
+        packet.push_data(&[code.len() as u8])?;
+        packet.push_data(code.as_bytes())?;
+        packet.push_data(name.as_bytes())?;
+        Ok(packet)
+    }
+
+    pub fn decode(packet: &Packet) -> Result<Listing, SnakeError> {
+        if packet.opcode() != Opcode::Announce {
+            return Err(ProtocolError::UnexpectedOpcode(packet.opcode()).into());
+        }
+
+        let mut reader = PacketReader::new(packet.data());
+        let port = reader.read_u16()?;
+        let width = reader.read_u16()? as usize;
+        let height = reader.read_u16()? as usize;
+        let walls = reader.read_u8()? != 0;
+        let pace = reader.read_u16()? as u64;
+        let players = reader.read_u8()? as usize;
+        let joined = reader.read_u8()? as usize;
+        let size = reader.read_u8()? as usize;
+        let code = String::from_utf8_lossy(reader.read_bytes(size)?).into_owned();
+        let name = String::from_utf8_lossy(reader.read_bytes(packet.data().len() - 12 - size)?).into_owned();
+        Ok(Listing { port, width, height, walls, pace, players, joined, code, name })
+    }
+
+    pub fn describe(&self) -> String {
+        let walls = if self.walls { ", walls" } else { "" };
+        format!("{}  {}x{}{}  {} ms  {} of {} players", self.name, self.width, self.height, walls, self.pace, self.joined, self.players)
+    }
+}
+
+#[allow(clippy::single_match)]
+pub fn advertise(mut listing: Listing, joined: Arc<AtomicUsize>) {
+    let socket = match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, DISCOVERY_PORT)) {
+        Ok(socket) => socket,
+        Err(error) => {
+            eprintln!("LAN discovery disabled ({})", error);
+            return;
+        }
+    };
+
+    spawn(move || {
+        if socket.set_read_timeout(Some(ADVERTISE_TIMEOUT)).is_err() {
+            return;
+        }
+
+        while joined.load(Ordering::SeqCst) < listing.players {
+
+            let mut buffer = [0; MAX_DATAGRAM_SIZE];
+            let (n, remote) = match socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(_) => {
+                    continue;
+                }
+            };
+
+            match Packet::decode(&buffer[..n]) {
+                Ok(packet) if packet.opcode() == Opcode::Discover => {},
+                _ => {
+                    continue;
+                }
+            }
+
+            listing.joined = joined.load(Ordering::SeqCst);
+            match listing.encode() {
+                Ok(packet) => {
+                    let _ = socket.send_to(&packet.encode(0), remote);
+                },
+                Err(_) => {}
+            }
+        }
+    });
+}
+
+pub fn discover() -> Result<(), SnakeError> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.set_broadcast(true)?;
+    socket.set_read_timeout(Some(ADVERTISE_TIMEOUT))?;
+
+    let query = Packet::new(Opcode::Discover, 0).encode(0);
+    for address in [Ipv4Addr::BROADCAST, Ipv4Addr::LOCALHOST] {
+        match socket.send_to(&query, (address, DISCOVERY_PORT)) {
+            Ok(_) => {},
+            Err(error) => {
+                eprintln!("Could not query {} ({})", address, error);
+            }
+        }
+    }
+
+    println!("Looking for games on the local network");
+    let mut found: Vec<SocketAddr> = Vec::new();
+    let started = Instant::now();
+    while started.elapsed() < DISCOVERY_TIMEOUT {
+        let mut buffer = [0; MAX_DATAGRAM_SIZE];
+        let (n, remote) = match socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(_) => {
+                continue;
+            }
+        };
+
+        let listing = match Packet::decode(&buffer[..n]).map_err(SnakeError::from).and_then(|packet| Listing::decode(&packet)) {
+            Ok(listing) => listing,
+            Err(_) => {
+                continue;
+            }
+        };
+
+        let address = SocketAddr::new(remote.ip(), listing.port);
+        if found.contains(&address) {
+            continue;
+        }
+
+        found.push(address);
+        println!("  {}  {}", address, listing.describe());
+    }
+
+    if found.is_empty() {
+        println!("No games found");
+    }
+
+    Ok(())
+}
+
+pub fn pick(remote: SocketAddr, config: &GameConfig) -> Result<Option<Listing>, SnakeError> {
+    let mut rooms = Network::rooms(remote, config)?;
+    if rooms.is_empty() {
+        println!("No open rooms on {}", remote);
+        return Ok(None);
+    }
+
+    rooms.sort_by(|a, b| a.code.cmp(&b.code));
+    println!("Open rooms on {}", remote);
+    for (i, room) in rooms.iter().enumerate() {
+        println!("  {}. {}  {}", i + 1, room.code, room.describe());
+    }
+
+    println!("Type a room number and press Enter to join it (just Enter to cancel)");
+    let mut line = String::new();
+    stdin().read_line(&mut line)?;
+    match line.trim().parse::<usize>() {
+        Ok(n) if (1..=rooms.len()).contains(&n) => Ok(Some(rooms.swap_remove(n - 1))),
+        _ => Ok(None)
+    }
+}
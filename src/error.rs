@@ -0,0 +1,116 @@
+use std::{error::Error, fmt, io};
+
+use crate::packet::Opcode;
+
+#[derive(Debug)]
+pub enum ProtocolError {
+    Truncated,
+    BadProtocolId(u64),
+    UnknownOpcode(u16),
+    UnexpectedOpcode(Opcode),
+    BadSize(usize, usize),
+    NestedBatch,
+    UnknownPlayer(u8),
+    BadDirection(u8),
+    BadTargetKind(u8),
+    BadCrash(u8),
+    BadPosition(usize, usize),
+    Malformed(&'static str)
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProtocolError::Truncated => write!(f, "ends too early"),
+            ProtocolError::BadProtocolId(id) => write!(f, "unknown protocol id {:016x}", id),
+            ProtocolError::UnknownOpcode(opcode) => write!(f, "unknown opcode {:#06x}", opcode),
+            ProtocolError::UnexpectedOpcode(opcode) => write!(f, "unexpected {:?}", opcode),
+            ProtocolError::BadSize(expected, received) => write!(f, "{} bytes of data instead of {}", received, expected),
+            ProtocolError::NestedBatch => write!(f, "batch inside a batch"),
+            ProtocolError::UnknownPlayer(id) => write!(f, "unknown player {}", id),
+            ProtocolError::BadDirection(direction) => write!(f, "bad direction {}", direction),
+            ProtocolError::BadTargetKind(kind) => write!(f, "bad target kind {}", kind),
+            ProtocolError::BadCrash(crash) => write!(f, "bad crash {}", crash),
+            ProtocolError::BadPosition(row, column) => write!(f, "position ({}, {}) off the board", row, column),
+            ProtocolError::Malformed(what) => write!(f, "malformed {}", what)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SnakeError {
+    Io(io::Error),
+    Disconnected,
+    Protocol(ProtocolError),
+    BadDataSize,
+    BadLevel(String),
+    BadBot(String),
+    BadLeaderboard(String),
+    BadReplay(String),
+    BadConfig(String),
+    BadPlayer(usize),
+    NoDataDir,
+    NotPrivateAddress,
+    BadHandshake,
+    ConnectionLost,
+    SequenceGap(u32, u32),
+    BadCode,
+    BadSecret,
+    BadPassword,
+    BadSettings,
+    Kicked,
+    Banned,
+    TimedOut(String),
+    Cancelled
+}
+
+impl fmt::Display for SnakeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnakeError::Io(error) => write!(f, "{}", error),
+            SnakeError::Disconnected => write!(f, "disconnected"),
+            SnakeError::Protocol(error) => write!(f, "bad packet ({})", error),
+            SnakeError::BadDataSize => write!(f, "bad data size"),
+            SnakeError::BadLevel(msg) => write!(f, "bad level ({})", msg),
+            SnakeError::BadBot(msg) => write!(f, "bad bot ({})", msg),
+            SnakeError::BadReplay(msg) => write!(f, "bad replay ({})", msg),
+            SnakeError::BadConfig(msg) => write!(f, "bad config ({})", msg),
+            SnakeError::BadPlayer(id) => write!(f, "no player {}", id),
+            SnakeError::BadLeaderboard(msg) => write!(f, "leaderboard request failed ({})", msg),
+            SnakeError::NoDataDir => write!(f, "no data directory"),
+            SnakeError::NotPrivateAddress => write!(f, "not a local/private IP address"),
+            SnakeError::BadHandshake => write!(f, "bad websocket handshake"),
+            SnakeError::ConnectionLost => write!(f, "connection lost"),
+            SnakeError::SequenceGap(expected, received) => write!(f, "sequence gap (expected {}, got {})", expected, received),
+            SnakeError::BadCode => write!(f, "unknown lobby code"),
+            SnakeError::BadSecret => write!(f, "wrong secret"),
+            SnakeError::BadPassword => write!(f, "wrong or missing password"),
+            SnakeError::BadSettings => write!(f, "the game is played on a different board, level, --walls, --start-length or growth"),
+            SnakeError::Kicked => write!(f, "kicked by the host"),
+            SnakeError::Banned => write!(f, "banned by the host"),
+            SnakeError::TimedOut(msg) => write!(f, "timed out ({})", msg),
+            SnakeError::Cancelled => write!(f, "cancelled")
+        }
+    }
+}
+
+impl Error for SnakeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SnakeError::Io(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for SnakeError {
+    fn from(error: io::Error) -> Self {
+        SnakeError::Io(error)
+    }
+}
+
+impl From<ProtocolError> for SnakeError {
+    fn from(error: ProtocolError) -> Self {
+        SnakeError::Protocol(error)
+    }
+}
@@ -0,0 +1,49 @@
+use std::{
+    fs::File,
+    io::Write
+};
+
+use crate::{
+    error::SnakeError,
+    game::Crash,
+    packet::Packet,
+    target::Target
+};
+
+pub enum Event<'a> {
+    TargetEaten(usize, Target),
+    SnakeGrew(usize, usize),
+    Crash(usize, Crash),
+    TickCompleted(u64),
+    Desync(u64),
+    PacketReceived(&'a Packet)
+}
+
+pub trait Subscriber {
+    fn notify(&mut self, event: &Event);
+}
+
+pub struct EventLog {
+    file: File
+}
+
+impl EventLog {
+    pub fn create(path: &str) -> Result<Self, SnakeError> {
+        Ok(EventLog { file: File::create(path)? })
+    }
+}
+
+impl Subscriber for EventLog {
+    fn notify(&mut self, event: &Event) {
+        let line = match event {
+            Event::TargetEaten(id, target) => format!("target_eaten P{} {:?} {:?}", id + 1, target.kind, target.pos),
+            Event::SnakeGrew(id, size) => format!("snake_grew P{} {}", id + 1, size),
+            Event::Crash(id, crash) => format!("crash P{} {:?}", id + 1, crash),
+            Event::TickCompleted(tick_id) => format!("tick_completed {}", tick_id),
+            Event::Desync(tick_id) => format!("desync {}", tick_id),
+            Event::PacketReceived(packet) => format!("packet_received {:?} {}", packet.opcode(), packet.sequence())
+        };
+
+        let _ = writeln!(self.file, "{}", line);
+    }
+}
@@ -0,0 +1,68 @@
+use std::{
+    fs::OpenOptions,
+    io::Write
+};
+
+use crate::{error::SnakeError, util::escape};
+
+const CSV_HEADER: &str = "date,mode,board,walls,pace,seed,result,reason,score,length,apples,ticks,duration_ms,peers";
+
+pub struct GameRecord {
+    pub date: String,
+    pub mode: &'static str,
+    pub width: usize,
+    pub height: usize,
+    pub walls: bool,
+    pub pace: u64,
+    pub seed: u64,
+    pub result: &'static str,
+    pub reason: String,
+    pub score: u64,
+    pub length: usize,
+    pub apples: u64,
+    pub ticks: u64,
+    pub duration: u64,
+    pub peers: Vec<String>
+}
+
+impl GameRecord {
+    fn json(&self) -> String {
+        let peers: Vec<String> = self.peers.iter().map(|peer| format!("\"{}\"", escape(peer))).collect();
+        format!(
+            "{{\"date\":\"{}\",\"mode\":\"{}\",\"board\":\"{}x{}\",\"walls\":{},\"pace\":{},\"seed\":{},\"result\":\"{}\",\"reason\":\"{}\",\"score\":{},\"length\":{},\"apples\":{},\"ticks\":{},\"duration_ms\":{},\"peers\":[{}]}}",
+            self.date, self.mode, self.width, self.height, self.walls, self.pace, self.seed, self.result, escape(&self.reason),
+            self.score, self.length, self.apples, self.ticks, self.duration, peers.join(",")
+        )
+    }
+
+    fn csv(&self) -> String {
+        format!(
+            "{},{},{}x{},{},{},{},{},{},{},{},{},{},{},{}",
+            self.date, self.mode, self.width, self.height, self.walls, self.pace, self.seed, self.result, csv_field(&self.reason),
+            self.score, self.length, self.apples, self.ticks, self.duration, csv_field(&self.peers.join(";"))
+        )
+    }
+
+    pub fn append(&self, path: &str) -> Result<(), SnakeError> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if !path.to_lowercase().ends_with(".csv") {
+            writeln!(file, "{}", self.json())?;
+            return Ok(());
+        }
+
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "{}", CSV_HEADER)?;
+        }
+
+        writeln!(file, "{}", self.csv())?;
+        Ok(())
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.into()
+    }
+}
@@ -1,536 +1,2408 @@
 use std::{
     collections::VecDeque,
-    io::{
-        stdin, ErrorKind, Read, Write
-    },
+    io::stdin,
+    mem::take,
     net::{
-        SocketAddr, SocketAddrV4, TcpListener, TcpStream
+        IpAddr, Ipv4Addr, SocketAddr
+    },
+    sync::mpsc::{
+        channel, Receiver, RecvTimeoutError
     },
-    sync::mpsc::channel,
     thread::{
         sleep, spawn
     },
-    time::Duration
+    time::{
+        Duration, Instant
+    }
 };
 
 use crate::{
+    ai::Ai,
     board::{
-        Board, BOARD_SIZE, CRASH_CHAR, OPPONENT_CHAR, PLAYER_CHAR, TARGET_CHAR
+        is_portal, is_snake, Board, Slot, Stroke, CRASH_CHAR, OPPONENT_CHAR, PLAYER_CHAR, SNAKE_CHARS, WALL_CHAR
+    },
+    bot::Bot,
+    campaign::{
+        save_progress, STAGES
     },
+    cast::CastTerminal,
+    config::GameConfig,
     direction::Direction,
+    discovery::Listing,
+    error::{
+        ProtocolError, SnakeError
+    },
+    events::{
+        Event, EventLog, Subscriber
+    },
+    export::GameRecord,
+    glyphs::Glyphs,
+    highscore::{
+        HighScore, HighScores
+    },
+    input::{
+        InputParser, Key
+    },
+    keymap::Keymap,
+    leaderboard::{
+        submit, Entry
+    },
+    level::Level,
+    lifetime::{
+        Lifetime, Outcome
+    },
+    net::{
+        goodbye_packet, Member, Network, FEATURE_CHAT, FEATURE_GAME_OVER, FEATURE_PAUSE_REQUEST, FEATURE_SNAPSHOTS, HOST_ID, MAX_PLAYERS, PEER_TIMEOUT
+    },
     packet::{
-        Opcode, Packet, HEADER_SIZE
+        Opcode, Packet, PacketReader
+    },
+    pause::{
+        PauseAction, PauseMenu, Setting
+    },
+    replay::Replay,
+    score::Score,
+    signal::interrupted,
+    snake::{
+        Snake, MAX_QUEUED_TURNS
+    },
+    spectator::snapshot,
+    stats::Stats,
+    target::{
+        Target, TargetKind
+    },
+    terminal::{
+        terminal, NullTerminal, Terminal
     },
-    snake::Snake
+    util::{
+        self, escape, fnv1a, host_name, random_number, time_seed, today, Rng
+    },
+    web::WebServer
 };
 
-const GAME_PACE: Duration = Duration::from_millis(350);
+const MAX_PREDICTION: usize = 3;
+const STATE_INTERVAL: u64 = 50;
+const PUMP_INTERVAL: Duration = Duration::from_millis(10);
+const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+const RISING_GLYPHS: [char; 2] = ['\u{2581}', '\u{2584}'];
+const FALLING_GLYPHS: [char; 2] = ['\u{2594}', '\u{2580}'];
+const WIDE_STROKE_GLYPH: char = '\u{2588}';
+const STAGE_DELAY: Duration = Duration::from_secs(3);
+const SPAWN_ATTEMPTS: usize = 100;
+const CHAT_LINES: usize = 3;
+const CHAT_MAX_LENGTH: usize = 80;
+
+const SPAWN_DIRECTIONS: [Direction; MAX_PLAYERS] = [Direction::Right, Direction::Left, Direction::Down, Direction::Up];
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum GameMode {
     Singleplayer,
+    Local,
     Multiplayer(SocketMode),
+    Replay(Replay)
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum SocketMode {
-    Client(SocketAddrV4),
-    Server(SocketAddrV4),
+    Client(SocketAddr),
+    Server(SocketAddr),
 }
 
 #[derive(Clone, Debug, PartialEq)]
-enum GameResult {
+pub enum GameResult {
     Win(String),
     Lose(String),
-    Draw(String)
+    Draw(String),
+    Winner(usize, String)
 }
 
-pub struct SnakeGame {
-    board: Board,
-    player: Snake,
-    target: VecDeque<(usize, usize)>,
-    socket: Option<TcpStream>,
-    opponent: Option<Snake>,
-    queue: VecDeque<Packet>,
-    tick_id: u64
+impl GameResult {
+    pub fn reason(&self) -> &str {
+        match self {
+            GameResult::Win(reason) | GameResult::Lose(reason) | GameResult::Draw(reason) | GameResult::Winner(_, reason) => reason
+        }
+    }
 }
 
-impl SnakeGame {
-    pub fn new(mode: GameMode) -> Self {
-        let mut board = Board::new();
-
-        let player;
-        let target;
-        let socket;
-        let opponent;
-
-        match mode {
-            GameMode::Singleplayer => {
-                let head = board.random_position().unwrap();
-                player = Snake::new(head, Direction::random());
-                board.mark(head, PLAYER_CHAR);
-
-                target = board.random_position().unwrap();
-                board.mark(target, TARGET_CHAR);
-
-                socket = None;
-                opponent = None;
-            },
-            GameMode::Multiplayer(mode) => {
-                match mode {
-                    SocketMode::Client(remote) => {
-                        if !remote.ip().is_loopback() && !remote.ip().is_private() {
-                            panic!("not a local/private IP address [SnakeGame::new()]");
-                        }
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlayerInput {
+    Turn(usize, Direction),
+    Pause,
+    Resume,
+    Quit,
+    Chat(String)
+}
 
-                        let head = (1, 1);
-                        player = Snake::new(head, Direction::Right);
-                        board.mark(head, PLAYER_CHAR);
+#[derive(Clone, Debug, PartialEq)]
+pub enum TickOutcome {
+    Running,
+    Over(GameResult)
+}
 
-                        let head = (BOARD_SIZE - 2, BOARD_SIZE - 2);
-                        opponent = Some(Snake::new(head, Direction::Left));
-                        board.mark(head, OPPONENT_CHAR);
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Crash {
+    Wall = 0x01,
+    Obstacle,
+    Snake,
+    Heads,
+    Left
+}
 
-                        target = (BOARD_SIZE / 2, BOARD_SIZE / 2);
-                        board.mark(target, TARGET_CHAR);
+impl Crash {
+    fn from(value: u8) -> Option<Crash> {
+        match value {
+            0x01 => Some(Crash::Wall),
+            0x02 => Some(Crash::Obstacle),
+            0x03 => Some(Crash::Snake),
+            0x04 => Some(Crash::Heads),
+            0x05 => Some(Crash::Left),
+            _ => None
+        }
+    }
 
-                        println!("Connecting to {}", remote);
-                        socket = match TcpStream::connect(&SocketAddr::V4(remote)) {
-                            Ok(stream) => Some(stream),
-                            Err(error) => {
-                                panic!("{} [SnakeGame::new()]", error.kind());
-                            }
-                        }
-                    },
-                    SocketMode::Server(local) => {
-                        if !local.ip().is_loopback() && !local.ip().is_private() {
-                            panic!("not a local/private IP address [SnakeGame::new()]");
-                        }
+    fn reason(&self, own: bool) -> &'static str {
+        match self {
+            Crash::Wall => if own { "player hit a wall" } else { "opponent hit a wall" },
+            Crash::Obstacle => if own { "player hit an obstacle" } else { "opponent hit an obstacle" },
+            Crash::Snake => if own { "player crash" } else { "opponent crash" },
+            Crash::Heads => "heads crash",
+            Crash::Left => if own { "player quit" } else { "opponent left" }
+        }
+    }
+}
 
-                        let head = (BOARD_SIZE - 2, BOARD_SIZE - 2);
-                        player = Snake::new(head, Direction::Left);
-                        board.mark(head, PLAYER_CHAR);
+pub struct SnakeGame {
+    level: Level,
+    board: Board,
+    snakes: Vec<Snake>,
+    scores: Vec<Score>,
+    stats: Vec<Stats>,
+    crashes: Vec<Option<Crash>>,
+    local: usize,
+    locals: Vec<usize>,
+    target: VecDeque<Target>,
+    network: Option<Network>,
+    bot: Option<Bot>,
+    ai: Option<Ai>,
+    input: Receiver<Key>,
+    chat: VecDeque<String>,
+    tick_id: u64,
+    config: GameConfig,
+    paused: bool,
+    holding: Vec<bool>,
+    pause: PauseMenu,
+    ring: bool,
+    kicked: Option<bool>,
+    quitting: bool,
+    glyphs: Glyphs,
+    terminal: Box<dyn Terminal>,
+    web: Vec<WebServer>,
+    subscribers: Vec<Box<dyn Subscriber>>,
+    inbox: Vec<Packet>,
+    scheduled: Vec<(u64, usize, Direction)>,
+    inputs: Vec<(u64, usize, Direction)>,
+    replay: Option<Replay>,
+    playback: VecDeque<(u64, usize, Direction)>,
+    rng: Option<Rng>,
+    resync: bool,
+    desynced: bool,
+    verdict: Option<GameResult>,
+    played: u64
+}
 
-                        let head = (1, 1);
-                        opponent = Some(Snake::new(head, Direction::Right));
-                        board.mark(head, OPPONENT_CHAR);
+impl SnakeGame {
+    #[allow(clippy::single_match)]
+    pub fn new(mode: GameMode, mut config: GameConfig) -> Result<Self, SnakeError> {
+        match config.campaign {
+            Some(stage) => {
+                STAGES[stage].apply(&mut config);
+            },
+            None => {}
+        }
 
-                        target = (BOARD_SIZE / 2, BOARD_SIZE / 2);
-                        board.mark(target, TARGET_CHAR);
+        let level = load_level(&mode, &config)?;
+        let keymap = Keymap::load()?;
+        let (ctrl_tx, ctrl_rx) = channel::<Key>();
 
-                        let server = match TcpListener::bind(local) {
-                            Ok(server) => server,
-                            Err(error) => {
-                                panic!("{} [SnakeGame::new()]", error.kind());
-                            }
-                        };
+        let keys = keymap.clone();
+        spawn(move || {
+            let mut parser = InputParser::new(keys);
+            loop {
+                let mut line = String::new();
+                if stdin().read_line(&mut line).unwrap() == 0 {
+                    ctrl_tx.send(Key::No).unwrap();
+                    break;
+                }
 
-                        let local = server.local_addr().unwrap();
-                        println!("Accepting connection at {}", local);
-                        socket = match server.accept() {
-                            Ok((stream, _)) => Some(stream),
-                            Err(error) => {
-                                panic!("{} [SnakeGame::new()]", error.kind());
-                            }
-                        }
-                    }
+                for key in parser.parse(&line) {
+                    ctrl_tx.send(key).unwrap();
                 }
             }
-        }
-
-        let mut deque = VecDeque::new();
-        deque.push_back(target);
+        });
 
-        SnakeGame { board, player, target: deque, socket, opponent, queue: VecDeque::new(), tick_id: 0 }
-    }
+        let cancelled = || interrupted() || ctrl_rx.try_iter().any(|key| key == Key::Quit);
+        let ready = || {
+            if config.bot_cmd.is_some() {
+                return Some(true);
+            }
 
-    pub fn play(&mut self) {
-        println!("\x1b[?25l");
-        let (ctrl_tx, ctrl_rx) = channel::<Direction>();
+            if interrupted() {
+                return Some(false);
+            }
 
-        spawn(move || {
-            loop {
-                let mut line = String::new();
-                stdin().read_line(&mut line).unwrap();
-                match line.trim() {
-                    "d" => {
-                        ctrl_tx.send(Direction::Right).unwrap();
-                    },
-                    "s" => {
-                        ctrl_tx.send(Direction::Down).unwrap();
+            for key in ctrl_rx.try_iter() {
+                match key {
+                    Key::Yes => {
+                        return Some(true);
                     },
-                    "a" => {
-                        ctrl_tx.send(Direction::Left).unwrap();
+                    Key::Quit | Key::No => {
+                        return Some(false);
                     },
-                    "w" => {
-                        ctrl_tx.send(Direction::Up).unwrap();
-                    }
                     _ => {}
                 }
             }
-        });
 
-        let mut result = None;
-        while result == None {
-            self.tick_id += 1;
-
-            match ctrl_rx.try_recv() {
-                Ok(direction) => {
-                    self.control(true, direction);
-                    if self.is_multiplayer() {
-                        self.send_control(direction);
-                    }
-                },
-                Err(_) => {}
-            }
+            None
+        };
 
-            if self.is_multiplayer() {
-                self.synchronize();
+        let mut network = match &mode {
+            GameMode::Singleplayer | GameMode::Local | GameMode::Replay(_) => None,
+            GameMode::Multiplayer(socket) => {
+                match *socket {
+                    SocketMode::Client(remote) => {
+                        Some(Network::connect(remote, &config, settings_hash(&level, &config), &cancelled)?)
+                    },
+                    SocketMode::Server(local) => {
+                        level.validate(config.players, config.walls)?;
+                        let listing = Listing {
+                            port: local.port(),
+                            width: level.width(),
+                            height: level.height(),
+                            walls: config.walls,
+                            pace: config.pace.as_millis() as u64,
+                            players: config.players,
+                            joined: 1,
+                            code: String::new(),
+                            name: host_name()
+                        };
 
-                loop {
-                    match self.queue.pop_front() {
-                        Some(packet) => {
-                            self.process(&packet);
-                        },
-                        None => {
-                            match self.recv_packet() {
-                                Some(packet) => {
-                                    self.process(&packet);
-                                },
-                                None => {
-                                    break;
-                                }
-                            }
-                        }
+                        Some(Network::host(local, &config, listing, settings_hash(&level, &config), &cancelled)?)
                     }
                 }
             }
+        };
 
-            result = self.update();
-            println!("\x1b[2J\x1b[1;1H{}", self.board.draw());
-            sleep(GAME_PACE);
+        match &mut network {
+            Some(network) => {
+                network.ready_check(&ready)?;
+            },
+            None => {}
         }
 
-        match result.unwrap() {
-            GameResult::Win(msg) => {
-                println!("You won :D ({})", msg);
+        SnakeGame::assemble(mode, config, level, network, ctrl_rx, keymap, false)
+    }
+
+    #[allow(clippy::single_match)]
+    pub fn headless(mode: GameMode, mut config: GameConfig) -> Result<Self, SnakeError> {
+        match config.campaign {
+            Some(stage) => {
+                STAGES[stage].apply(&mut config);
             },
-            GameResult::Lose(msg) => {
-                println!("You lost :/ ({})", msg);
+            None => {}
+        }
+
+        match mode {
+            GameMode::Multiplayer(_) => {
+                return Err(SnakeError::BadConfig("headless games can't be played over the network".into()));
             },
-            GameResult::Draw(msg) => {
-                println!("It's a draw ._. ({})", msg);
-            }
+            _ => {}
         }
 
-        println!("\x1b[?25h");
+        let level = load_level(&mode, &config)?;
+        let (_, input) = channel::<Key>();
+        SnakeGame::assemble(mode, config, level, None, input, Keymap::default(), true)
     }
 
-    fn is_multiplayer(&self) -> bool {
-        self.socket.is_some()
-    }
+    #[allow(clippy::single_match)]
+    fn assemble(mode: GameMode, mut config: GameConfig, level: Level, network: Option<Network>, input: Receiver<Key>, keymap: Keymap, headless: bool) -> Result<Self, SnakeError> {
+        let local_multiplayer = mode == GameMode::Local;
+        let replay = match mode {
+            GameMode::Replay(replay) => Some(replay),
+            _ => None
+        };
+
+        let (local, players) = match &network {
+            Some(network) => {
+                config.pace = network.pace();
+                (network.id(), network.count())
+            },
+            None if local_multiplayer => (0, config.players),
+            None => (0, 1)
+        };
 
-    fn control(&mut self, own: bool, direction: Direction) {
-        if own {
-            self.player.control(direction);
-        } else {
-            match &mut self.opponent {
-                Some(opponent) => {
-                    opponent.control(direction);
-                },
-                None => {
-                    panic!("unreachable [SnakeGame::control()]");
-                }
-            }
-        }
-    }
+        let locals = if local_multiplayer { (0..players).collect() } else { vec![local] };
 
-    fn update(&mut self) -> Option<GameResult> {
-        let tail = self.player.tail();
-        self.board.unmark(tail);
-        self.player.update();
+        level.validate(players, config.walls)?;
 
-        let target = *self.target.front().unwrap();
-        self.board.mark(target, TARGET_CHAR);
+        let (board, snakes, target) = setup(&level, players, local, locals.len() > 1, &config)?;
 
-        let mut opponent_tail = None;
-        match &mut self.opponent {
-            Some(opponent) => {
-                let tail = opponent.tail();
-                opponent_tail = Some(tail);
-                self.board.unmark(tail);
-                opponent.update();
+        let bot = match &config.bot_cmd {
+            Some(cmd) => Some(Bot::spawn(cmd)?),
+            None => None
+        };
 
-                if self.player.head() == opponent.head() {
-                    self.board.mark(self.player.head(), CRASH_CHAR);
-                    return Some(GameResult::Draw("heads crash".into()));
-                }
+        let ai = match config.opponent {
+            Some(tier) if local_multiplayer => Some(Ai::new(tier, board.width(), board.height())),
+            _ => None
+        };
+
+        let mut deque = VecDeque::new();
+        deque.push_back(target);
+
+        let scores = (0..players).map(|_| Score::new()).collect();
+        let stats = snakes.iter().map(Stats::new).collect();
+        let glyphs = if config.emoji { Glyphs::emoji() } else if headless { Glyphs::default() } else { Glyphs::load()? };
+        let terminal: Box<dyn Terminal> = match &config.export_cast {
+            _ if headless => Box::new(NullTerminal),
+            Some(path) => {
+                let width = (board.width() * glyphs.cell_width() + 2).max(CHAT_MAX_LENGTH);
+                let height = 1 + board.height() + 2 + 3 + CHAT_LINES;
+                Box::new(CastTerminal::create(terminal(), path, width, height)?)
+            },
+            None => terminal()
+        };
+
+        let playback = match &replay {
+            Some(replay) => replay.inputs().iter().copied().collect(),
+            None => VecDeque::new()
+        };
+
+        let mut web = Vec::new();
+        match config.web_spectate {
+            Some(port) => {
+                let server = WebServer::serve(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port)?;
+                println!("Streaming the game at http://{}:{}/", host_name(), server.port());
+                web.push(server);
             },
             None => {}
         }
 
-        let pixel = self.board.value(self.player.head());
-        if pixel == PLAYER_CHAR || pixel == OPPONENT_CHAR {
-            match &mut self.opponent {
-                Some(opponent) => {
-                    self.board.mark(opponent.head(), OPPONENT_CHAR);
-                },
-                None => {}
-            }
+        match config.state_api {
+            Some(port) => {
+                let server = WebServer::serve(IpAddr::V4(Ipv4Addr::LOCALHOST), port)?;
+                println!("Serving the game state at http://127.0.0.1:{}/state", server.port());
+                web.push(server);
+            },
+            None => {}
+        }
 
-            self.board.mark(self.player.head(), CRASH_CHAR);
-            return Some(GameResult::Lose("player crash".into()));
+        let pause = PauseMenu::new(network.is_none() && replay.is_none() && config.time_limit.is_none(), keymap);
+        let rng = match &network {
+            Some(network) if !network.is_authoritative() => Some(Rng::new(network.seed())),
+            _ => None
+        };
+
+        let mut game = SnakeGame { level, board, snakes, scores, stats, crashes: vec![None; players], local, locals, target: deque, network, bot, ai, input, chat: VecDeque::new(), tick_id: 0, config, paused: false, holding: vec![false; players], pause, ring: false, kicked: None, quitting: false, glyphs, terminal, web, subscribers: Vec::new(), inbox: Vec::new(), scheduled: Vec::new(), inputs: Vec::new(), replay, playback, rng, resync: false, desynced: false, verdict: None, played: 0 };
+        match &game.config.event_log {
+            Some(path) => {
+                let log = EventLog::create(path)?;
+                game.subscribe(Box::new(log));
+            },
+            None => {}
         }
 
-        let mut opponent_grow = false;
-        self.board.mark(self.player.head(), PLAYER_CHAR);
+        Ok(game)
+    }
 
-        match &mut self.opponent {
-            Some(opponent) => {
-                let pixel = self.board.value(opponent.head());
-                if pixel == OPPONENT_CHAR || pixel == PLAYER_CHAR {
-                    self.board.mark(opponent.head(), CRASH_CHAR);
-                    return Some(GameResult::Win("opponent crash".into()));
-                }
+    pub fn ai_inputs(&self) -> Vec<PlayerInput> {
+        (0..self.snakes.len())
+            .filter_map(|id| self.ai_turn(id).map(|direction| PlayerInput::Turn(id, direction)))
+            .collect()
+    }
 
-                self.board.mark(opponent.head(), OPPONENT_CHAR);
-                if opponent.head() == target {
-                    let tail = opponent_tail.unwrap();
-                    opponent.grow(tail);
+    fn ai_turn(&self, id: usize) -> Option<Direction> {
+        match &self.ai {
+            Some(ai) if !self.paused && self.crashes[id].is_none() => {
+                let rivals: Vec<(usize, usize)> = self.heads().into_iter().map(|(head, _)| head).filter(|head| *head != self.snakes[id].head()).collect();
+                Some(ai.choose(&self.board, &self.snakes[id], self.target.front().unwrap().pos, &rivals, self.config.walls))
+            },
+            _ => None
+        }
+    }
 
-                    self.board.mark(tail, OPPONENT_CHAR);
-                    if self.board.is_full() {
-                        if self.player.size() > opponent.size() {
-                            return Some(GameResult::Win("board full, player size wins".into()));
-                        } else if self.player.size() < opponent.size() {
-                            return Some(GameResult::Lose("board full, opponent size wins".into()));
-                        } else {
-                            return Some(GameResult::Draw("board full, same size".into()));
-                        }
-                    }
+    pub fn subscribe(&mut self, subscriber: Box<dyn Subscriber>) {
+        self.subscribers.push(subscriber);
+    }
 
-                    self.target.pop_front();
-                    opponent_grow = true;
+    #[allow(clippy::single_match)]
+    pub fn play(&mut self) -> Result<(), SnakeError> {
+        loop {
+            self.terminal.hide_cursor();
+            let result = self.run();
+            self.terminal.show_cursor();
+
+            let result = result?;
+            match &result {
+                GameResult::Win(msg) => {
+                    println!("You won :D ({})", msg);
+                },
+                GameResult::Lose(msg) => {
+                    println!("You lost :/ ({})", msg);
+                },
+                GameResult::Draw(msg) => {
+                    println!("It's a draw ._. ({})", msg);
+                },
+                GameResult::Winner(id, msg) => {
+                    println!("P{} won :D ({})", id + 1, msg);
                 }
-            },
-            None => {}
-        }
+            }
 
-        if !opponent_grow && self.player.head() == target {
-            self.player.grow(tail);
-            self.board.mark(tail, PLAYER_CHAR);
-
-            let target = self.board.random_position();
-            if target.is_none() {
-                match &mut self.opponent {
-                    Some(opponent) => {
-                        if self.player.size() > opponent.size() {
-                            return Some(GameResult::Win("board full, player size wins".into()));
-                        } else if self.player.size() < opponent.size() {
-                            return Some(GameResult::Lose("board full, opponent size wins".into()));
-                        } else {
-                            return Some(GameResult::Draw("board full, same size".into()));
+            if self.replay.is_some() {
+                println!("Score: {}", self.scores[self.local].points());
+                println!("{}", self.summary());
+                return Ok(());
+            }
+
+            self.record_lifetime(&result);
+            match &self.config.export_stats {
+                Some(path) => {
+                    match self.record(&result).append(path) {
+                        Ok(_) => {},
+                        Err(error) => {
+                            eprintln!("Could not export the game stats ({})", error);
                         }
-                    },
-                    None => {
-                        return Some(GameResult::Win("board full".into()));
                     }
-                }
+                },
+                None => {}
             }
 
-            let target = target.unwrap();
-            self.board.mark(target, TARGET_CHAR);
-            if self.is_multiplayer() {
-                self.send_target(target);
+            match &self.config.record {
+                Some(path) if !self.is_multiplayer() && !self.is_local_multiplayer() => {
+                    match Replay::new(&self.config, self.played, self.inputs.clone()).and_then(|replay| replay.save(path)) {
+                        Ok(_) => {
+                            println!("Replay saved to {}", path);
+                        },
+                        Err(error) => {
+                            eprintln!("Could not save the replay ({})", error);
+                        }
+                    }
+                },
+                _ => {}
             }
 
-            self.target.push_back(target);
-            self.target.pop_front();
-        }
-
-        None
-    }
+            if self.is_local_multiplayer() {
+                let scores: Vec<String> = self.scores.iter().enumerate().map(|(id, score)| format!("P{} {}", id + 1, score.points())).collect();
+                println!("Scores: {}", scores.join("  "));
+                println!("{}", self.summary());
+                println!("Seed: {}", self.config.seed);
+                if !self.restart()? {
+                    return Ok(());
+                }
 
-    fn synchronize(&mut self) {
-        let mut packet = Packet::new(Opcode::Sync, 8);
+                self.reset()?;
+                continue;
+            }
 
-        let mut data = [0; 8];
-        data[0] = (self.tick_id >> 56) as u8;
-        data[1] = (self.tick_id >> 48) as u8;
-        data[2] = (self.tick_id >> 40) as u8;
-        data[3] = (self.tick_id >> 32) as u8;
-        data[4] = (self.tick_id >> 24) as u8;
-        data[5] = (self.tick_id >> 16) as u8;
-        data[6] = (self.tick_id >> 8) as u8;
-        data[7] = (self.tick_id >> 0) as u8;
+            println!("Score: {}", self.scores[self.local].points());
+            println!("{}", self.summary());
+            println!("Seed: {}", self.config.seed);
 
-        packet.push_data(&data);
-        self.send_packet(&packet);
+            let mode = self.mode();
+            let entry = HighScore::new(self.scores[self.local].points(), today(), self.board.width(), self.board.height(), mode);
 
-        match &mut self.socket {
-            Some(socket) => {
-                match socket.set_nonblocking(false) {
+            let mut high_scores = HighScores::load();
+            let rank = high_scores.insert(entry);
+            if rank.is_some() {
+                match high_scores.save() {
                     Ok(_) => {},
                     Err(error) => {
-                        panic!("{} [SnakeGame::synchronize()]", error.kind());
+                        eprintln!("Could not save high scores ({})", error);
                     }
                 }
-            },
-            None => {}
-        }
+            }
 
-        loop {
-            match self.recv_packet() {
-                Some(packet) => {
-                    match packet.opcode() {
-                        Opcode::Sync => {
-                            let data = packet.data();
-                            let mut tick_id: u64 = 0;
-                            tick_id |= (data[0] as u64) << 56;
-                            tick_id |= (data[1] as u64) << 48;
-                            tick_id |= (data[2] as u64) << 40;
-                            tick_id |= (data[3] as u64) << 32;
-                            tick_id |= (data[4] as u64) << 24;
-                            tick_id |= (data[5] as u64) << 16;
-                            tick_id |= (data[6] as u64) << 8;
-                            tick_id |= (data[7] as u64) << 0;
-
-                            if tick_id == self.tick_id {
-                                break;
-                            }
+            println!("\n{}", high_scores.draw(rank));
+
+            match &self.config.submit_score {
+                Some(url) if !self.is_multiplayer() => {
+                    let entry = Entry::new(self.scores[self.local].points(), self.board.width(), self.board.height(), mode, self.config.seed, self.replay_hash());
+                    match submit(url, &entry) {
+                        Ok(_) => {
+                            println!("Score submitted to {}", url);
                         },
-                        _ => {
-                            self.queue.push_back(packet);
+                        Err(error) => {
+                            eprintln!("Could not submit the score ({})", error);
                         }
                     }
                 },
-                None => {
-                    panic!("unreachable [SnakeGame::synchronize()]");
-                }
+                _ => {}
             }
-        }
 
-        match &mut self.socket {
-            Some(socket) => {
-                match socket.set_nonblocking(true) {
-                    Ok(_) => {},
-                    Err(error) => {
-                        panic!("{} [SnakeGame::synchronize()]", error.kind());
+            match self.config.campaign {
+                Some(stage) if self.goal_reached() => {
+                    if !self.next_stage(stage + 1)? {
+                        return Ok(());
                     }
-                }
-            },
-            None => {}
+
+                    continue;
+                },
+                _ => {}
+            }
+
+            let again = if self.is_multiplayer() { self.rematch()? } else { self.restart()? };
+            if !again {
+                return Ok(());
+            }
+
+            self.reset()?;
         }
     }
 
-    fn process(&mut self, packet: &Packet) {
-        match packet.opcode() {
-            Opcode::Sync => {
-                panic!("unreachable [SnakeGame::process()]");
-            },
-            Opcode::NewDirection => {
-                let data = packet.data();
-                let direction = Direction::from(data[0]);
-                self.control(false, direction);
-            },
-            Opcode::NewTarget => {
-                let data = packet.data();
-                let target = (data[0] as usize, data[1] as usize);
-                self.target.push_back(target);
+    fn record_lifetime(&self, result: &GameResult) {
+        let mut lifetime = Lifetime::load();
+        let stats = &self.stats[self.local];
+        lifetime.game_played(stats.eaten(), stats.ticks());
+
+        if self.is_multiplayer() || self.ai.is_some() {
+            let outcome = self.outcome(result);
+            for name in self.opponents() {
+                lifetime.opponent(&name, outcome);
+            }
+        }
+
+        match lifetime.save() {
+            Ok(_) => {},
+            Err(error) => {
+                eprintln!("Could not save lifetime stats ({})", error);
             }
         }
     }
 
-    fn send_control(&mut self, direction: Direction) {
-        let mut packet = Packet::new(Opcode::NewDirection, 1);
-        packet.push_data(&[direction as u8]);
-        self.send_packet(&packet);
+    fn record(&self, result: &GameResult) -> GameRecord {
+        let stats = &self.stats[self.local];
+        GameRecord {
+            date: today(),
+            mode: self.mode(),
+            width: self.board.width(),
+            height: self.board.height(),
+            walls: self.config.walls,
+            pace: self.config.pace.as_millis() as u64,
+            seed: self.config.seed,
+            result: self.outcome(result).name(),
+            reason: result.reason().into(),
+            score: self.scores[self.local].points(),
+            length: self.snakes[self.local].size(),
+            apples: stats.eaten(),
+            ticks: stats.ticks(),
+            duration: (self.config.pace * self.played as u32).as_millis() as u64,
+            peers: self.opponents()
+        }
+    }
+
+    fn mode(&self) -> &'static str {
+        if self.is_multiplayer() {
+            "multiplayer"
+        } else if self.ai.is_some() {
+            "computer"
+        } else if self.is_local_multiplayer() {
+            "local"
+        } else if self.config.campaign.is_some() {
+            "campaign"
+        } else if self.config.time_limit.is_some() {
+            "time attack"
+        } else if self.config.zen {
+            "zen"
+        } else {
+            "singleplayer"
+        }
+    }
+
+    fn outcome(&self, result: &GameResult) -> Outcome {
+        match result {
+            GameResult::Win(_) => Outcome::Win,
+            GameResult::Lose(_) => Outcome::Loss,
+            GameResult::Draw(_) => Outcome::Draw,
+            GameResult::Winner(id, _) if self.ai.is_some() && *id == self.snakes.len() - 1 => Outcome::Loss,
+            GameResult::Winner(id, _) if self.ai.is_none() && *id != self.local => Outcome::Loss,
+            GameResult::Winner(_, _) => Outcome::Win
+        }
+    }
+
+    fn opponents(&self) -> Vec<String> {
+        match self.config.opponent {
+            Some(tier) if self.ai.is_some() => {
+                return vec![format!("computer ({})", tier.name())];
+            },
+            _ => {}
+        }
+
+        (0..self.snakes.len()).filter(|id| *id != self.local).map(|id| self.name(id)).collect()
     }
 
-    fn send_target(&mut self, target: (usize, usize)) {
-        if !(target.0 < BOARD_SIZE) || !(target.1 < BOARD_SIZE) {
-            panic!("bad position [SnakeGame::send_target()]");
+    fn next_stage(&mut self, stage: usize) -> Result<bool, SnakeError> {
+        match save_progress(stage.min(STAGES.len() - 1)) {
+            Ok(_) => {},
+            Err(error) => {
+                eprintln!("Could not save campaign progress ({})", error);
+            }
+        }
+
+        if stage == STAGES.len() {
+            println!("Campaign complete, every stage cleared!");
+            return Ok(false);
         }
 
-        let mut packet = Packet::new(Opcode::NewTarget, 2);
-        packet.push_data(&[target.0 as u8, target.1 as u8]);
-        self.send_packet(&packet);
+        println!("Stage {} cleared, stage {} of {} starts in a moment", stage, stage + 1, STAGES.len());
+        sleep(STAGE_DELAY);
+
+        STAGES[stage].apply(&mut self.config);
+        self.config.campaign = Some(stage);
+        self.level = Level::empty(self.config.width, self.config.height);
+        self.level.scatter_obstacles(self.config.obstacles, self.config.walls);
+        self.reset()?;
+        Ok(true)
     }
 
-    fn send_packet(&mut self, packet: &Packet) {
-        match &mut self.socket {
-            Some(socket) => {
-                let buffer = packet.encode();
-                match socket.write(&buffer) {
-                    Ok(n) => {
-                        if n != buffer.len() {
-                            panic!("write() error [SnakeGame::send_packet()]");
+    fn rematch(&mut self) -> Result<bool, SnakeError> {
+        let complete = match &self.network {
+            Some(network) => network.is_complete(),
+            None => false
+        };
+
+        if !complete || self.kicked.is_some() || self.crashes.contains(&Some(Crash::Left)) {
+            return Ok(false);
+        }
+
+        while self.input.try_recv().is_ok() {}
+        println!("Play again? (y/n)");
+
+        let mut votes = vec![false; self.snakes.len()];
+        while votes.contains(&false) {
+            match self.input.try_recv() {
+                Ok(Key::Yes) if !votes[self.local] => {
+                    let opcode = if votes.contains(&true) { Opcode::RematchAccept } else { Opcode::RematchRequest };
+                    let mut packet = Packet::new(opcode, 1);
+                    packet.push_data(&[self.local as u8])?;
+                    self.send_packet(&packet)?;
+
+                    votes[self.local] = true;
+                    println!("Waiting for the others");
+                },
+                Ok(Key::No) | Ok(Key::Quit) => {
+                    self.send_packet(&goodbye_packet(self.local)?)?;
+                    return Ok(false);
+                },
+                _ => {}
+            }
+
+            if interrupted() {
+                self.send_packet(&goodbye_packet(self.local)?)?;
+                return Ok(false);
+            }
+
+            let packets = match &mut self.network {
+                Some(network) => network.poll()?,
+                None => Vec::new()
+            };
+
+            for packet in packets {
+                match packet.opcode() {
+                    Opcode::RematchRequest | Opcode::RematchAccept => {
+                        let id = self.peer_id(PacketReader::new(packet.data()).read_u8()?)?;
+                        if !votes[id] {
+                            votes[id] = true;
+                            println!("Player {} wants a rematch", id + 1);
                         }
                     },
-                    Err(error) => {
-                        panic!("{} [SnakeGame::send_packet()]", error.kind());
-                    }
+                    Opcode::Goodbye => {
+                        println!("Player {} left", PacketReader::new(packet.data()).read_u8()? as usize + 1);
+                        return Ok(false);
+                    },
+                    _ => {}
                 }
-            },
-            None => {
-                panic!("unreachable [SnakeGame::send_packet()]");
             }
+
+            sleep(PUMP_INTERVAL);
         }
+
+        Ok(true)
     }
 
-    fn recv_packet(&mut self) -> Option<Packet> {
-        match &mut self.socket {
-            Some(socket) => {
-                let mut buffer = vec![0; HEADER_SIZE];
-                match socket.read(&mut buffer) {
-                    Ok(n) => {
-                        if n == 0 {
-                            panic!("disconnected [SnakeGame::recv_packet()]");
-                        }
+    fn restart(&mut self) -> Result<bool, SnakeError> {
+        if self.quitting {
+            return Ok(false);
+        }
 
-                        if n != HEADER_SIZE {
-                            panic!("read() error [SnakeGame::recv_packet()]");
-                        }
+        while self.input.try_recv().is_ok() {}
 
-                        let mut size: u16 = 0;
-                        size |= (buffer[10] as u16) << 8;
-                        size |= (buffer[11] as u16) << 0;
-
-                        if size > 0 {
-                            buffer.resize(HEADER_SIZE + size as usize, 0);
-                            match socket.read(&mut buffer[HEADER_SIZE..]) {
-                                Ok(n) => {
-                                    if n != size as usize {
-                                        panic!("read() error [SnakeGame::recv_packet()]");
-                                    }
-                                },
-                                Err(error) => {
-                                    panic!("{} [SnakeGame::recv_packet()]", error.kind());
-                                }
-                            }
-                        }
+        let seconds = (self.config.pace * self.played as u32).as_secs();
+        println!("\nGame over");
+        if self.is_local_multiplayer() {
+            println!("Time {}:{:02}", seconds / 60, seconds % 60);
+        } else {
+            println!("Score {}  Length {}  Time {}:{:02}", self.scores[self.local].points(), self.snakes[self.local].size(), seconds / 60, seconds % 60);
+        }
 
-                        match Packet::decode(&buffer) {
-                            Some(packet) => {
-                                Some(packet)
-                            },
-                            None => {
-                                panic!("bad packet [SnakeGame::recv_packet()]");
-                            }
-                        }
-                    },
-                    Err(error) => {
-                        if error.kind() != ErrorKind::WouldBlock && error.kind() != ErrorKind::TimedOut {
-                            panic!("{} [SnakeGame::recv_packet()]", error.kind());
-                        }
+        println!("Press r to play again or q to quit");
+        loop {
+            match self.input.recv_timeout(PUMP_INTERVAL) {
+                Ok(Key::Restart) => {
+                    break;
+                },
+                Ok(Key::Quit) | Ok(Key::No) | Err(RecvTimeoutError::Disconnected) => {
+                    return Ok(false);
+                },
+                _ => {}
+            }
 
-                        None
-                    }
-                }
-            },
-            None => {
-                panic!("unreachable [SnakeGame::recv_packet()]");
+            if interrupted() {
+                return Ok(false);
             }
         }
+
+        let seed = time_seed();
+        util::seed(seed);
+        self.config.seed = seed;
+        Ok(true)
+    }
+
+    fn reset(&mut self) -> Result<(), SnakeError> {
+        let players = self.snakes.len();
+        let (board, snakes, target) = setup(&self.level, players, self.local, self.is_local_multiplayer(), &self.config)?;
+        self.board = board;
+        self.snakes = snakes;
+        self.target.clear();
+        self.target.push_back(target);
+        self.scores = (0..players).map(|_| Score::new()).collect();
+        self.stats = self.snakes.iter().map(Stats::new).collect();
+        self.crashes = vec![None; players];
+        self.inbox.clear();
+        self.scheduled.clear();
+        self.inputs.clear();
+        self.resync = false;
+        self.desynced = false;
+        self.verdict = None;
+        self.played = 0;
+        self.tick_id = 0;
+        self.paused = false;
+        self.holding = vec![false; self.snakes.len()];
+        self.quitting = false;
+        Ok(())
+    }
+
+    fn run(&mut self) -> Result<GameResult, SnakeError> {
+        let mut result = None;
+        let mut next_tick = Instant::now();
+        let mut next_frame = next_tick;
+        while result.is_none() {
+            let now = Instant::now();
+            if now < next_tick {
+                self.pump()?;
+                if now >= next_frame {
+                    let progress = 1.0 - (next_tick - now).as_secs_f64() / self.config.pace.as_secs_f64();
+                    self.terminal.draw(&self.frame(&self.board, &self.strokes(progress)));
+                    next_frame = now + FRAME_INTERVAL;
+                }
+
+                sleep((next_tick - now).min(next_frame - now).min(PUMP_INTERVAL));
+                continue;
+            }
+
+            next_tick = if now - next_tick > self.config.pace { now } else { next_tick };
+            while result.is_none() && Instant::now() >= next_tick {
+                match self.tick()? {
+                    TickOutcome::Running => {},
+                    TickOutcome::Over(over) => {
+                        result = Some(over);
+                    }
+                }
+
+                next_tick += self.config.pace;
+                if self.is_authoritative() && !self.is_host() {
+                    next_tick = Instant::now();
+                    break;
+                }
+            }
+
+            self.terminal.draw(&self.draw());
+            if take(&mut self.ring) && self.config.sound {
+                self.terminal.bell();
+            }
+
+            next_frame = Instant::now() + FRAME_INTERVAL;
+            if !self.web.is_empty() {
+                let (frame, state) = (self.render(&self.board, &[], false), self.live_json());
+                for server in &self.web {
+                    server.publish(&frame, &state);
+                }
+            }
+        }
+
+        Ok(result.unwrap())
+    }
+
+    #[allow(clippy::single_match)]
+    fn tick(&mut self) -> Result<TickOutcome, SnakeError> {
+        let mut inputs = Vec::new();
+        while let Ok(key) = self.input.try_recv() {
+            match key {
+                Key::Direction(_, _) | Key::Select if self.paused => {
+                    match self.pause.key(&key, &self.config) {
+                        Some(PauseAction::Apply(setting)) => {
+                            self.apply(setting);
+                        },
+                        Some(PauseAction::Forfeit) => {
+                            inputs.push(PlayerInput::Quit);
+                        },
+                        Some(PauseAction::Resume) => {
+                            inputs.push(PlayerInput::Resume);
+                        },
+                        None => {}
+                    }
+                },
+                Key::Direction(cluster, direction) if self.replay.is_none() => {
+                    let humans = self.locals.len() - self.ai.is_some() as usize;
+                    let id = if humans > 1 {
+                        match self.locals[..humans].get(cluster) {
+                            Some(id) => *id,
+                            None => {
+                                continue;
+                            }
+                        }
+                    } else {
+                        self.local
+                    };
+
+                    inputs.push(PlayerInput::Turn(id, direction));
+                },
+                Key::Pause => {
+                    inputs.push(PlayerInput::Pause);
+                },
+                Key::Quit => {
+                    inputs.push(PlayerInput::Quit);
+                },
+                Key::Chat(message) => {
+                    inputs.push(PlayerInput::Chat(message));
+                },
+                Key::Kick(who, ban) if self.is_host() => {
+                    self.kick(&who, ban)?;
+                },
+                _ => {}
+            }
+        }
+
+        if interrupted() {
+            inputs.push(PlayerInput::Quit);
+        }
+
+        let id = self.snakes.len() - 1;
+        match self.ai_turn(id) {
+            Some(direction) => {
+                inputs.push(PlayerInput::Turn(id, direction));
+            },
+            None => {}
+        }
+
+        match &mut self.network {
+            Some(network) => network.hold(),
+            None => {}
+        }
+
+        let outcome = self.step(&inputs);
+        match &mut self.network {
+            Some(network) => network.flush()?,
+            None => {}
+        }
+
+        outcome
+    }
+
+    pub fn apply(&mut self, setting: Setting) {
+        match setting {
+            Setting::Pace(pace) => {
+                self.config.pace = pace;
+            },
+            Setting::Color(color) => {
+                self.config.color = color;
+            },
+            Setting::Sound(sound) => {
+                self.config.sound = sound;
+            }
+        }
+    }
+
+    #[allow(clippy::single_match)]
+    pub fn step(&mut self, inputs: &[PlayerInput]) -> Result<TickOutcome, SnakeError> {
+        for input in inputs {
+            match input {
+                PlayerInput::Turn(id, _) if *id >= self.snakes.len() => {
+                    return Err(SnakeError::BadPlayer(*id));
+                },
+                _ => {}
+            }
+        }
+
+        self.tick_id += 1;
+        let hash = self.state_hash();
+
+        for input in inputs {
+            match input {
+                PlayerInput::Turn(id, direction) => {
+                    self.steer(*id, *direction)?;
+                },
+                PlayerInput::Pause | PlayerInput::Resume if self.is_multiplayer() && self.supports(FEATURE_PAUSE_REQUEST) => {
+                    let holding = *input == PlayerInput::Pause && !self.holding[self.local];
+                    if holding != self.holding[self.local] {
+                        self.hold_pause(self.local, holding);
+                        self.send_pause(holding)?;
+                    }
+                },
+                PlayerInput::Pause | PlayerInput::Resume => {
+                    self.paused = *input == PlayerInput::Pause && !self.paused;
+                    if self.is_multiplayer() {
+                        self.send_pause(self.paused)?;
+                    }
+                },
+                PlayerInput::Quit => {
+                    return self.quit().map(TickOutcome::Over);
+                },
+                PlayerInput::Chat(message) if self.is_multiplayer() => {
+                    self.send_chat(message)?;
+                },
+                _ => {}
+            }
+        }
+
+        if !self.paused && self.crashes[self.local].is_none() {
+            let state = self.state_json();
+            let pace = self.config.pace;
+            match &mut self.bot {
+                Some(bot) => {
+                    bot.send_state(&state)?;
+                    match bot.recv_direction(pace) {
+                        Some(direction) => {
+                            self.steer(self.local, direction)?;
+                        },
+                        None => {}
+                    }
+                },
+                None => {}
+            }
+        }
+
+        let packets = match self.network.take() {
+            Some(mut network) => {
+                let pace = self.config.pace;
+                let mut predicted = 0;
+                let packets = network.synchronize(self.tick_id, hash, &mut |waited| {
+                    let steps = ((waited.as_millis() / pace.as_millis().max(1)) as usize).min(MAX_PREDICTION);
+                    if !self.paused && steps > predicted {
+                        predicted = steps;
+                        let frame = self.frame(&self.predict(steps), &[]);
+                        self.terminal.draw(&frame);
+                    }
+                });
+
+                let desync = network.take_desync();
+                let malformed = network.take_malformed();
+                self.network = Some(network);
+                match malformed {
+                    Some((id, error)) => {
+                        self.show_chat("Game", &format!("{} sent a bad packet ({}), disconnecting", self.name(id), error));
+                    },
+                    None => {}
+                }
+
+                match desync {
+                    Some(tick_id) => {
+                        self.emit(Event::Desync(tick_id));
+                        if !self.desynced {
+                            self.show_chat("Game", &format!("out of sync at tick {}, resyncing", tick_id));
+                        }
+                        if self.is_host() {
+                            self.resync = true;
+                        }
+                    },
+                    None => {}
+                }
+
+                self.desynced = desync.is_some();
+
+                match packets {
+                    Ok(packets) => packets,
+                    Err(SnakeError::ConnectionLost) => {
+                        return Ok(TickOutcome::Over(GameResult::Draw("connection lost".into())));
+                    },
+                    Err(SnakeError::Protocol(error)) => {
+                        return Ok(TickOutcome::Over(GameResult::Draw(format!("bad packet ({})", error))));
+                    },
+                    Err(error) => {
+                        return Err(error);
+                    }
+                }
+            },
+            None => Vec::new()
+        };
+
+        let mut inbox = take(&mut self.inbox);
+        inbox.extend(packets);
+
+        let crashes = self.crashes.clone();
+        self.receive(&inbox)?;
+
+        match self.kicked {
+            Some(ban) => {
+                let reason = if ban { SnakeError::Banned } else { SnakeError::Kicked };
+                return Ok(TickOutcome::Over(GameResult::Lose(reason.to_string())));
+            },
+            None => {}
+        }
+
+        let mut result = None;
+        if self.is_authoritative() {
+            self.apply_inputs();
+            result = self.authoritative_tick(&crashes)?;
+        } else {
+            if self.is_host() && self.supports(FEATURE_SNAPSHOTS) && (self.tick_id.is_multiple_of(STATE_INTERVAL) || take(&mut self.resync)) {
+                let packet = self.state_snapshot()?;
+                self.send_packet(&packet)?;
+            }
+
+            self.apply_inputs();
+            match &mut self.network {
+                Some(network) => network.release(self.tick_id, hash)?,
+                None => {}
+            }
+
+            if !self.paused {
+                self.play_back();
+                result = self.update()?;
+            }
+        }
+
+        if result.is_none() && self.time_left() == Some(Duration::ZERO) {
+            result = Some(GameResult::Win("time's up".into()));
+        }
+
+        if result.is_none() && self.goal_reached() {
+            result = Some(GameResult::Win("goal reached".into()));
+        }
+
+        let replayed = match &self.replay {
+            Some(replay) => self.played >= replay.ticks(),
+            None => false
+        };
+
+        if result.is_none() && replayed {
+            result = Some(GameResult::Lose("end of replay".into()));
+        }
+
+        if self.is_multiplayer() && self.supports(FEATURE_GAME_OVER) {
+            result = self.verdict(result, &crashes)?;
+        }
+
+        self.emit(Event::TickCompleted(self.tick_id));
+        self.broadcast_snapshot()?;
+        match result {
+            Some(result) => Ok(TickOutcome::Over(result)),
+            None => Ok(TickOutcome::Running)
+        }
+    }
+
+    fn receive(&mut self, packets: &[Packet]) -> Result<(), SnakeError> {
+        for packet in packets {
+            self.emit(Event::PacketReceived(packet));
+            match self.process(packet) {
+                Ok(()) => {},
+                Err(SnakeError::Protocol(error)) => {
+                    self.show_chat("Game", &format!("ignored a bad {:?} packet ({})", packet.opcode(), error));
+                },
+                Err(error) => {
+                    return Err(error);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[allow(clippy::single_match)]
+    fn verdict(&mut self, result: Option<GameResult>, crashes: &[Option<Crash>]) -> Result<Option<GameResult>, SnakeError> {
+        if self.is_host() {
+            match &result {
+                Some(result) => self.send_verdicts(result, crashes)?,
+                None => {}
+            }
+
+            return Ok(result);
+        }
+
+        if result.is_some() {
+            self.await_verdict()?;
+        }
+
+        match take(&mut self.verdict) {
+            Some(verdict) => Ok(Some(verdict)),
+            None => Ok(result)
+        }
+    }
+
+    #[allow(clippy::single_match)]
+    fn send_verdicts(&mut self, result: &GameResult, crashes: &[Option<Crash>]) -> Result<(), SnakeError> {
+        let crashes = self.tick_crashes(crashes);
+        for id in 0..self.snakes.len() {
+            if id == self.local {
+                continue;
+            }
+
+            let packet = verdict_packet(&self.settle(id, &crashes).unwrap_or_else(|| result.clone()))?;
+            match &mut self.network {
+                Some(network) => network.send_to(id, &packet)?,
+                None => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn await_verdict(&mut self) -> Result<(), SnakeError> {
+        let started = Instant::now();
+        while self.verdict.is_none() && started.elapsed() < PEER_TIMEOUT {
+            let packets = match &mut self.network {
+                Some(network) if network.peer_count() > 0 => network.poll()?,
+                _ => {
+                    break;
+                }
+            };
+
+            self.receive(&packets)?;
+            sleep(PUMP_INTERVAL);
+        }
+
+        Ok(())
+    }
+
+    #[allow(clippy::single_match)]
+    fn pump(&mut self) -> Result<(), SnakeError> {
+        if self.is_authoritative() && !self.is_host() {
+            return Ok(());
+        }
+
+        match &mut self.network {
+            Some(network) => {
+                let packets = network.poll()?;
+                self.inbox.extend(packets);
+            },
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    fn authoritative_tick(&mut self, crashes: &[Option<Crash>]) -> Result<Option<GameResult>, SnakeError> {
+        if !self.is_host() {
+            let crashes = self.tick_crashes(crashes);
+            return Ok(self.settle(self.local, &crashes));
+        }
+
+        let result = if self.paused { None } else { self.update()? };
+        let packet = self.state_snapshot()?;
+        self.send_packet(&packet)?;
+        Ok(result)
+    }
+
+    fn tick_crashes(&self, before: &[Option<Crash>]) -> Vec<Option<Crash>> {
+        before.iter().zip(&self.crashes).map(|(before, after)| {
+            match before {
+                Some(_) => None,
+                None => *after
+            }
+        }).collect()
+    }
+
+    fn settle(&self, id: usize, crashes: &[Option<Crash>]) -> Option<GameResult> {
+        let survivors = self.crashes.iter().filter(|crash| crash.is_none()).count();
+        if survivors <= 1 {
+            return Some(self.crash_result(id, crashes));
+        }
+
+        if self.board.is_full() {
+            return Some(self.board_full_result(id));
+        }
+
+        None
+    }
+
+    fn quit(&mut self) -> Result<GameResult, SnakeError> {
+        self.quitting = true;
+        if self.is_local_multiplayer() {
+            return Ok(GameResult::Draw("game quit".into()));
+        }
+
+        self.crashes[self.local] = Some(Crash::Left);
+        self.emit(Event::Crash(self.local, Crash::Left));
+        if self.is_multiplayer() {
+            self.send_packet(&goodbye_packet(self.local)?)?;
+        }
+
+        Ok(GameResult::Lose(Crash::Left.reason(true).into()))
+    }
+
+    fn draw(&self) -> String {
+        self.frame(&self.board, &[])
+    }
+
+    fn heads(&self) -> Vec<((usize, usize), Direction)> {
+        self.snakes.iter().enumerate()
+            .filter(|(id, _)| self.crashes[*id].is_none())
+            .map(|(_, snake)| (snake.head(), snake.direction()))
+            .collect()
+    }
+
+    fn strokes(&self, progress: f64) -> Vec<Stroke> {
+        let mut strokes = Vec::new();
+        let amount = ((progress * 3.0) as usize).min(2);
+        if amount == 0 || self.paused || (self.is_authoritative() && !self.is_host()) {
+            return strokes;
+        }
+
+        for (id, snake) in self.snakes.iter().enumerate() {
+            if self.crashes[id].is_some() {
+                continue;
+            }
+
+            let direction = match snake.turns().front() {
+                Some(direction) if *direction != snake.direction().opposite() => *direction,
+                _ => snake.direction()
+            };
+
+            let head = snake.head();
+            let pixel = self.pixel(id);
+            let glyph = if self.glyphs.is_wide() { WIDE_STROKE_GLYPH } else { self.glyphs.glyph(pixel) };
+            match direction {
+                Direction::Right => {
+                    strokes.push(Stroke { pos: head, slot: Slot::After, pixel, glyph });
+                    if amount >= 2 && head.1 + 1 < self.board.width() {
+                        strokes.push(Stroke { pos: (head.0, head.1 + 1), slot: Slot::Before, pixel, glyph });
+                    }
+                },
+                Direction::Left => {
+                    strokes.push(Stroke { pos: head, slot: Slot::Before, pixel, glyph });
+                    if amount >= 2 && head.1 > 0 {
+                        strokes.push(Stroke { pos: (head.0, head.1 - 1), slot: Slot::After, pixel, glyph });
+                    }
+                },
+                Direction::Down => {
+                    if head.0 + 1 < self.board.height() {
+                        strokes.push(Stroke { pos: (head.0 + 1, head.1), slot: Slot::Inside, pixel, glyph: FALLING_GLYPHS[amount - 1] });
+                    }
+                },
+                Direction::Up => {
+                    if head.0 > 0 {
+                        strokes.push(Stroke { pos: (head.0 - 1, head.1), slot: Slot::Inside, pixel, glyph: RISING_GLYPHS[amount - 1] });
+                    }
+                }
+            }
+        }
+
+        strokes
+    }
+
+    fn frame(&self, board: &Board, strokes: &[Stroke]) -> String {
+        self.render(board, strokes, self.config.color && self.terminal.has_color())
+    }
+
+    #[allow(clippy::single_match)]
+    fn render(&self, board: &Board, strokes: &[Stroke], color: bool) -> String {
+        let mut frame = String::new();
+        if self.is_multiplayer() {
+            let scores: Vec<String> = self.scores.iter().enumerate()
+                .map(|(id, score)| format!("{} {}", self.name(id), score.points()))
+                .collect();
+            frame.push_str(&scores.join(" \u{2014} "));
+            match self.network.as_ref().and_then(|network| network.rtt()) {
+                Some(rtt) => {
+                    frame.push_str(&format!("  RTT: {} ms", rtt.as_millis()));
+                },
+                None => {}
+            }
+
+            frame.push('\n');
+        }
+
+        frame.push_str(&board.draw_between(color, &self.glyphs, strokes, &self.heads()));
+        if self.is_local_multiplayer() {
+            let scores: Vec<String> = self.scores.iter().enumerate()
+                .map(|(id, score)| format!("P{} {}: {}", id + 1, self.glyphs.glyph(self.pixel(id)), score.points()))
+                .collect();
+            frame.push_str(&scores.join("  "));
+            frame.push('\n');
+        } else if !self.is_multiplayer() {
+            frame.push_str(&format!("Score: {}  Length: {}", self.scores[self.local].points(), self.snakes[self.local].size()));
+            match (self.config.campaign, self.config.goal) {
+                (Some(stage), Some(goal)) => {
+                    frame.push_str(&format!("  Stage: {}/{}  Goal: {}", stage + 1, STAGES.len(), goal));
+                },
+                _ => {}
+            }
+
+            match self.time_left() {
+                Some(left) => {
+                    let seconds = left.as_millis().div_ceil(1000);
+                    frame.push_str(&format!("  Time: {}:{:02}", seconds / 60, seconds % 60));
+                },
+                None => {}
+            }
+
+            frame.push('\n');
+        }
+
+        let survivors = self.crashes.iter().filter(|crash| crash.is_none()).count();
+        if self.is_multiplayer() && self.crashes[self.local].is_some() && survivors > 1 {
+            frame.push_str("You crashed, watching the others\n");
+        }
+
+        if self.paused {
+            frame.push_str(&self.pause.draw(&self.config));
+        }
+
+        for line in &self.chat {
+            frame.push_str(line);
+            frame.push('\n');
+        }
+
+        frame
+    }
+
+    fn state_json(&self) -> String {
+        let snake_json = |snake: &Snake| {
+            let body: Vec<String> = snake.body().iter().map(|pos| format!("[{},{}]", pos.0, pos.1)).collect();
+            format!("{{\"direction\":\"{}\",\"body\":[{}]}}", snake.direction().name(), body.join(","))
+        };
+
+        let mut obstacles = Vec::new();
+        for i in 0..self.board.height() {
+            for j in 0..self.board.width() {
+                if self.board.value((i, j)) == WALL_CHAR {
+                    obstacles.push(format!("[{},{}]", i, j));
+                }
+            }
+        }
+
+        let target = match self.target.front() {
+            Some(target) => format!("{{\"pos\":[{},{}],\"kind\":\"{}\"}}", target.pos.0, target.pos.1, target.kind.name()),
+            None => "null".into()
+        };
+
+        let mut opponents = Vec::new();
+        for (id, snake) in self.snakes.iter().enumerate() {
+            if id != self.local && self.crashes[id].is_none() {
+                opponents.push(snake_json(snake));
+            }
+        }
+
+        let portals: Vec<String> = self.level.portals().iter()
+            .map(|portal| format!("[[{},{}],[{},{}]]", portal.ends.0.0, portal.ends.0.1, portal.ends.1.0, portal.ends.1.1))
+            .collect();
+
+        format!(
+            "{{\"tick\":{},\"width\":{},\"height\":{},\"walls\":{},\"player\":{},\"opponents\":[{}],\"target\":{},\"obstacles\":[{}],\"portals\":[{}],\"score\":{}}}",
+            self.tick_id, self.board.width(), self.board.height(), self.config.walls, snake_json(&self.snakes[self.local]), opponents.join(","),
+            target, obstacles.join(","), portals.join(","), self.scores[self.local].points()
+        )
+    }
+
+    fn live_json(&self) -> String {
+        let mut players = Vec::new();
+        for (id, snake) in self.snakes.iter().enumerate() {
+            let body: Vec<String> = snake.body().iter().map(|pos| format!("[{},{}]", pos.0, pos.1)).collect();
+            players.push(format!(
+                "{{\"id\":{},\"name\":\"{}\",\"score\":{},\"alive\":{},\"length\":{},\"direction\":\"{}\",\"body\":[{}]}}",
+                id, escape(&self.name(id)), self.scores[id].points(), self.crashes[id].is_none(), snake.size(), snake.direction().name(), body.join(",")
+            ));
+        }
+
+        let targets: Vec<String> = self.target.iter()
+            .map(|target| format!("{{\"pos\":[{},{}],\"kind\":\"{}\"}}", target.pos.0, target.pos.1, target.kind.name()))
+            .collect();
+
+        format!(
+            "{{\"tick\":{},\"width\":{},\"height\":{},\"walls\":{},\"paused\":{},\"players\":[{}],\"targets\":[{}]}}",
+            self.tick_id, self.board.width(), self.board.height(), self.config.walls, self.paused, players.join(","), targets.join(",")
+        )
+    }
+
+    #[allow(clippy::single_match)]
+    fn summary(&self) -> String {
+        let mut lines = Vec::new();
+        for (id, stats) in self.stats.iter().enumerate() {
+            let name = if self.is_local_multiplayer() {
+                format!("P{}", id + 1)
+            } else if !self.is_multiplayer() {
+                "Stats".to_string()
+            } else if id == self.local {
+                format!("{} (you)", self.name(id))
+            } else {
+                self.name(id)
+            };
+
+            lines.push(format!(
+                "{}: length {} (max {}), {} apples, {} ticks, {} turns",
+                name, self.snakes[id].size(), stats.max_length(), stats.eaten(), stats.ticks(), stats.turns()
+            ));
+        }
+
+        match self.network.as_ref().and_then(|network| network.average_rtt()) {
+            Some(rtt) => {
+                lines.push(format!("Average RTT: {:.1} ms", rtt.as_secs_f64() * 1000.0));
+            },
+            None => {}
+        }
+
+        lines.join("\n")
+    }
+
+    fn goal_reached(&self) -> bool {
+        match self.config.goal {
+            Some(goal) => !self.is_multiplayer() && self.crashes[self.local].is_none() && self.snakes[self.local].size() >= goal,
+            None => false
+        }
+    }
+
+    fn time_left(&self) -> Option<Duration> {
+        let limit = self.config.time_limit?;
+        Some(limit.saturating_sub(self.config.pace * self.played as u32))
+    }
+
+    fn emit(&mut self, event: Event) {
+        match event {
+            Event::TargetEaten(_, _) | Event::Crash(_, _) => {
+                self.ring = true;
+            },
+            _ => {}
+        }
+
+        for subscriber in &mut self.subscribers {
+            subscriber.notify(&event);
+        }
+    }
+
+    fn is_multiplayer(&self) -> bool {
+        self.network.is_some()
+    }
+
+    fn is_local_multiplayer(&self) -> bool {
+        self.locals.len() > 1
+    }
+
+    fn is_host(&self) -> bool {
+        match &self.network {
+            Some(network) => network.is_host(),
+            None => false
+        }
+    }
+
+    fn is_authoritative(&self) -> bool {
+        match &self.network {
+            Some(network) => network.is_authoritative(),
+            None => false
+        }
+    }
+
+    fn supports(&self, feature: u32) -> bool {
+        match &self.network {
+            Some(network) => network.has(feature),
+            None => true
+        }
+    }
+
+    fn name(&self, id: usize) -> String {
+        match &self.network {
+            Some(network) => network.names()[id].clone(),
+            None => format!("P{}", id + 1)
+        }
+    }
+
+    fn pixel(&self, id: usize) -> char {
+        snake_pixel(id, self.local, self.is_local_multiplayer())
+    }
+
+    fn predict(&self, steps: usize) -> Board {
+        let mut board = self.board.clone();
+        for (id, snake) in self.snakes.iter().enumerate() {
+            if id == self.local || self.crashes[id].is_some() {
+                continue;
+            }
+
+            let mut snake = snake.clone();
+            for _ in 0..steps {
+                let tail = snake.tail();
+                if !snake.update(board.width(), board.height(), self.config.walls, &self.level) || board.value(snake.head()) != ' ' {
+                    break;
+                }
+
+                board.unmark(tail);
+                board.mark(snake.head(), self.pixel(id));
+            }
+        }
+
+        board
+    }
+
+    fn steer(&mut self, id: usize, direction: Direction) -> Result<(), SnakeError> {
+        let survivors = self.crashes.iter().filter(|crash| crash.is_none()).count();
+        if self.crashes[id].is_some() && survivors > 1 {
+            return Ok(());
+        }
+
+        if self.is_multiplayer() {
+            let tick_id = self.tick_id + self.config.input_delay;
+            self.send_control(direction, tick_id)?;
+            self.scheduled.push((tick_id, id, direction));
+        } else {
+            self.inputs.push((self.played, id, direction));
+            self.snakes[id].control(direction);
+        }
+
+        Ok(())
+    }
+
+    fn play_back(&mut self) {
+        while self.playback.front().is_some_and(|input| input.0 <= self.played) {
+            let (_, id, direction) = self.playback.pop_front().unwrap();
+            self.snakes[id].control(direction);
+        }
+    }
+
+    fn apply_inputs(&mut self) {
+        let tick_id = self.tick_id;
+        let (due, later) = take(&mut self.scheduled).into_iter().partition(|input| input.0 <= tick_id);
+        self.scheduled = later;
+
+        for (_, id, direction) in due {
+            self.snakes[id].control(direction);
+        }
+    }
+
+    fn update(&mut self) -> Result<Option<GameResult>, SnakeError> {
+        self.played += 1;
+        let width = self.board.width();
+        let height = self.board.height();
+        let alive: Vec<usize> = (0..self.snakes.len()).filter(|id| self.crashes[*id].is_none()).collect();
+
+        let tails: Vec<(usize, usize)> = self.snakes.iter().map(|snake| snake.tail()).collect();
+        for id in &alive {
+            self.board.unmark(tails[*id]);
+        }
+
+        let mut crashes = vec![None; self.snakes.len()];
+        for id in &alive {
+            let id = *id;
+            let pixel = self.pixel(id);
+            let snake = &mut self.snakes[id];
+            if !snake.update(width, height, self.config.walls, &self.level) {
+                crashes[id] = Some(Crash::Wall);
+                self.board.mark(tails[id], pixel);
+            } else if snake.grow_pending(tails[id]) {
+                self.board.mark(tails[id], pixel);
+                self.emit(Event::SnakeGrew(id, self.snakes[id].size()));
+            }
+        }
+
+        let target = *self.target.front().unwrap();
+        self.board.mark(target.pos, target.kind.pixel());
+
+        for id in &alive {
+            let id = *id;
+            if crashes[id].is_some() {
+                continue;
+            }
+
+            let head = self.snakes[id].head();
+            let heads = alive.iter().any(|other| {
+                *other != id && crashes[*other] != Some(Crash::Wall) && self.snakes[*other].head() == head
+            });
+
+            let pixel = self.board.value(head);
+            if heads {
+                crashes[id] = Some(Crash::Heads);
+            } else if pixel == WALL_CHAR || is_portal(pixel) {
+                crashes[id] = Some(Crash::Obstacle);
+            } else if is_snake(pixel) || pixel == CRASH_CHAR {
+                crashes[id] = Some(Crash::Snake);
+            }
+        }
+
+        for id in &alive {
+            if crashes[*id].is_none() {
+                self.board.mark(self.snakes[*id].head(), self.pixel(*id));
+            }
+        }
+
+        for id in &alive {
+            if crashes[*id].is_some() {
+                self.board.mark(self.snakes[*id].head(), CRASH_CHAR);
+                self.crashes[*id] = crashes[*id];
+                self.emit(Event::Crash(*id, crashes[*id].unwrap()));
+            }
+        }
+
+        for id in &alive {
+            if crashes[*id].is_none() {
+                self.stats[*id].tick(&self.snakes[*id]);
+            }
+        }
+
+        let survivors = alive.iter().filter(|id| crashes[**id].is_none()).count();
+        if self.snakes.len() > 1 {
+            if survivors <= 1 {
+                return Ok(Some(self.crash_result(self.local, &crashes)));
+            }
+        } else {
+            match crashes[self.local] {
+                Some(_) if self.config.zen => {
+                    return Ok(self.respawn(self.local));
+                },
+                Some(crash) => {
+                    return Ok(Some(GameResult::Lose(crash.reason(true).into())));
+                },
+                None => {}
+            }
+        }
+
+        for id in &alive {
+            let id = *id;
+            if crashes[id].is_some() || self.snakes[id].head() != target.pos {
+                continue;
+            }
+
+            let tail = tails[id];
+            let snake = &mut self.snakes[id];
+            snake.grow(tail);
+            snake.feed(target.kind.growth() * self.config.growth - 1);
+            self.scores[id].target_eaten(target.kind, snake.size());
+            self.stats[id].target_eaten(snake.size());
+            self.board.mark(tail, self.pixel(id));
+            self.emit(Event::TargetEaten(id, target));
+            self.emit(Event::SnakeGrew(id, self.snakes[id].size()));
+
+            self.target.pop_front();
+            if self.board.is_full() {
+                return Ok(Some(self.board_full_result(self.local)));
+            }
+
+            let target = match self.spawn_target() {
+                Some(target) => target,
+                None => {
+                    return Ok(Some(self.board_full_result(self.local)));
+                }
+            };
+
+            self.board.mark(target.pos, target.kind.pixel());
+            self.target.push_back(target);
+        }
+
+        Ok(None)
+    }
+
+    fn spawn_target(&mut self) -> Option<Target> {
+        let roll = self.roll();
+        let pos = self.board.free_position(roll)?;
+        Some(Target::new(pos, TargetKind::roll(self.roll())))
+    }
+
+    fn roll(&mut self) -> u64 {
+        match &mut self.rng {
+            Some(rng) => rng.next(),
+            None => random_number()
+        }
+    }
+
+    fn respawn(&mut self, id: usize) -> Option<GameResult> {
+        self.crashes[id] = Some(Crash::Left);
+        self.rebuild_board();
+
+        let head = match self.board.random_position() {
+            Some(head) => head,
+            None => {
+                return Some(self.board_full_result(self.local));
+            }
+        };
+
+        let direction = self.snakes[id].direction();
+        self.snakes[id] = Snake::new(head, direction);
+        self.crashes[id] = None;
+        self.scores[id].crashed();
+        self.board.mark(head, self.pixel(id));
+        None
+    }
+
+    fn crash_result(&self, local: usize, crashes: &[Option<Crash>]) -> GameResult {
+        if self.is_local_multiplayer() {
+            return match self.crashes.iter().position(|crash| crash.is_none()) {
+                Some(id) => GameResult::Winner(id, "last snake standing".into()),
+                None if crashes.contains(&Some(Crash::Heads)) => GameResult::Draw("heads crash".into()),
+                None => GameResult::Draw("everyone crashed".into())
+            };
+        }
+
+        match self.crashes[local] {
+            None => {
+                let crash = crashes.iter().flatten().next().unwrap_or(&Crash::Left);
+                GameResult::Win(crash.reason(false).into())
+            },
+            Some(crash) => {
+                let survivors = self.crashes.iter().filter(|crash| crash.is_none()).count();
+                if survivors > 0 || crashes[local].is_none() {
+                    return GameResult::Lose(crash.reason(true).into());
+                }
+
+                if crash == Crash::Heads {
+                    GameResult::Draw("heads crash".into())
+                } else if crashes.iter().flatten().all(|crash| *crash == Crash::Wall) {
+                    GameResult::Draw("walls crash".into())
+                } else {
+                    GameResult::Draw("everyone crashed".into())
+                }
+            }
+        }
+    }
+
+    fn board_full_result(&self, local: usize) -> GameResult {
+        if self.is_local_multiplayer() {
+            let alive: Vec<usize> = (0..self.snakes.len()).filter(|id| self.crashes[*id].is_none()).collect();
+            let largest = alive.iter().map(|id| self.snakes[*id].size()).max().unwrap_or(0);
+            let winners: Vec<usize> = alive.into_iter().filter(|id| self.snakes[*id].size() == largest).collect();
+            return match winners[..] {
+                [id] => GameResult::Winner(id, "board full, largest snake".into()),
+                _ => GameResult::Draw("board full, same size".into())
+            };
+        }
+
+        if !self.is_multiplayer() {
+            return GameResult::Win("board full".into());
+        }
+
+        let survivors = self.crashes.iter().filter(|crash| crash.is_none()).count();
+        if self.crashes[local].is_some() && survivors > 1 {
+            return GameResult::Lose("board full, opponent size wins".into());
+        }
+
+        let size = self.snakes[local].size();
+        let mut largest = 0;
+        for (id, snake) in self.snakes.iter().enumerate() {
+            if id != local && self.crashes[id].is_none() {
+                largest = largest.max(snake.size());
+            }
+        }
+
+        if size > largest {
+            GameResult::Win("board full, player size wins".into())
+        } else if size < largest {
+            GameResult::Lose("board full, opponent size wins".into())
+        } else {
+            GameResult::Draw("board full, same size".into())
+        }
+    }
+
+    fn process(&mut self, packet: &Packet) -> Result<(), SnakeError> {
+        let data = packet.data();
+        let mut reader = PacketReader::new(data);
+        match packet.opcode() {
+            Opcode::Sync | Opcode::Batch => {
+                panic!("unreachable [SnakeGame::process()]");
+            },
+            Opcode::NewTarget | Opcode::Snapshot | Opcode::Spectate | Opcode::Join | Opcode::Welcome | Opcode::Ping | Opcode::RematchRequest | Opcode::RematchAccept | Opcode::Register | Opcode::Code | Opcode::Discover | Opcode::Announce | Opcode::Rendezvous | Opcode::AuthChallenge | Opcode::AuthResponse | Opcode::SecretChallenge | Opcode::SecretResponse | Opcode::Pong | Opcode::Names | Opcode::Ready => {},
+            Opcode::Kick => {
+                if !self.is_host() {
+                    self.kicked = Some(reader.read_u8()? == 1);
+                }
+            },
+            Opcode::NewDirection => {
+                let id = self.peer_id(reader.read_u8()?)?;
+                let direction = read_direction(&mut reader)?;
+                let tick_id = reader.read_u64()?;
+                self.scheduled.push((tick_id, id, direction));
+            },
+            Opcode::Pause => {
+                self.peer_id(reader.read_u8()?)?;
+                self.paused = reader.read_u8()? != 0;
+            },
+            Opcode::PauseRequest => {
+                let id = self.peer_id(reader.read_u8()?)?;
+                let holding = reader.read_u8()? != 0;
+                self.hold_pause(id, holding);
+            },
+            Opcode::Goodbye => {
+                let id = self.peer_id(reader.read_u8()?)?;
+                self.leave(id);
+                if id == HOST_ID {
+                    for id in 0..self.snakes.len() {
+                        if id != self.local {
+                            self.leave(id);
+                        }
+                    }
+                }
+
+                for id in 0..self.snakes.len() {
+                    if self.crashes[id] == Some(Crash::Left) {
+                        self.holding[id] = false;
+                    }
+                }
+
+                self.paused = self.holding.contains(&true);
+            },
+            Opcode::StateSnapshot => {
+                if !self.is_host() {
+                    self.apply_state(data)?;
+                }
+            },
+            Opcode::GameOver => {
+                if !self.is_host() {
+                    self.verdict = Some(read_verdict(&mut reader, data.len())?);
+                }
+            },
+            Opcode::Chat => {
+                let id = self.peer_id(reader.read_u8()?)?;
+                let message = String::from_utf8_lossy(reader.read_bytes(data.len() - 1)?).into_owned();
+                let name = self.name(id);
+                self.show_chat(&name, &message);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[allow(clippy::identity_op, clippy::single_match)]
+    fn state_snapshot(&self) -> Result<Packet, SnakeError> {
+        let mut packet = Packet::new(Opcode::StateSnapshot, 64);
+        packet.push_data(&self.tick_id.to_be_bytes())?;
+        packet.push_data(&[self.paused as u8, self.snakes.len() as u8])?;
+
+        for (id, snake) in self.snakes.iter().enumerate() {
+            let crash = match self.crashes[id] {
+                Some(crash) => crash as u8,
+                None => 0
+            };
+
+            packet.push_data(&[crash, snake.direction() as u8, snake.turns().len() as u8])?;
+            for turn in snake.turns() {
+                packet.push_data(&[*turn as u8])?;
+            }
+
+            packet.push_data(&[(snake.pending() >> 8) as u8, (snake.pending() >> 0) as u8])?;
+            packet.push_data(&self.scores[id].points().to_be_bytes())?;
+            packet.push_data(&[(snake.size() >> 8) as u8, (snake.size() >> 0) as u8])?;
+            for pos in snake.body() {
+                packet.push_data(&[(pos.0 >> 8) as u8, (pos.0 >> 0) as u8, (pos.1 >> 8) as u8, (pos.1 >> 0) as u8])?;
+            }
+        }
+
+        packet.push_data(&[self.target.len() as u8])?;
+        for target in &self.target {
+            let pos = target.pos;
+            packet.push_data(&[(pos.0 >> 8) as u8, (pos.0 >> 0) as u8, (pos.1 >> 8) as u8, (pos.1 >> 0) as u8, target.kind as u8])?;
+        }
+
+        match &self.rng {
+            Some(rng) => {
+                packet.push_data(&rng.state().to_be_bytes())?;
+            },
+            None => {}
+        }
+
+        Ok(packet)
+    }
+
+    #[allow(clippy::identity_op, clippy::single_match)]
+    fn state_hash(&self) -> u64 {
+        if !self.is_multiplayer() || self.is_authoritative() {
+            return 0;
+        }
+
+        let mut data = vec![self.paused as u8];
+        for (id, snake) in self.snakes.iter().enumerate() {
+            let crash = match self.crashes[id] {
+                Some(crash) => crash as u8,
+                None => 0
+            };
+
+            data.extend_from_slice(&[crash, snake.direction() as u8, (snake.pending() >> 8) as u8, (snake.pending() >> 0) as u8]);
+            data.extend(snake.turns().iter().map(|turn| *turn as u8));
+            data.extend_from_slice(&self.scores[id].points().to_be_bytes());
+            for pos in snake.body() {
+                data.extend_from_slice(&[(pos.0 >> 8) as u8, (pos.0 >> 0) as u8, (pos.1 >> 8) as u8, (pos.1 >> 0) as u8]);
+            }
+        }
+
+        for target in &self.target {
+            data.extend_from_slice(&[(target.pos.0 >> 8) as u8, (target.pos.0 >> 0) as u8, (target.pos.1 >> 8) as u8, (target.pos.1 >> 0) as u8, target.kind as u8]);
+        }
+
+        match &self.rng {
+            Some(rng) => {
+                data.extend_from_slice(&rng.state().to_be_bytes());
+            },
+            None => {}
+        }
+
+        fnv1a(&data)
+    }
+
+    fn replay_hash(&self) -> u64 {
+        let mut data = Vec::with_capacity(self.inputs.len() * 10);
+        for (tick_id, id, direction) in &self.inputs {
+            data.extend_from_slice(&tick_id.to_be_bytes());
+            data.push(*id as u8);
+            data.push(*direction as u8);
+        }
+
+        fnv1a(&data)
+    }
+
+    #[allow(clippy::single_match)]
+    fn apply_state(&mut self, data: &[u8]) -> Result<(), SnakeError> {
+        let mut reader = PacketReader::new(data);
+        let tick_id = reader.read_u64()?;
+        let paused = reader.read_u8()? != 0;
+        if reader.read_u8()? as usize != self.snakes.len() {
+            return Err(ProtocolError::Malformed("snake count").into());
+        }
+
+        let read_pos = |reader: &mut PacketReader| -> Result<(usize, usize), ProtocolError> {
+            let pos = (reader.read_u16()? as usize, reader.read_u16()? as usize);
+            if pos.0 >= self.board.height() || pos.1 >= self.board.width() {
+                return Err(ProtocolError::BadPosition(pos.0, pos.1));
+            }
+
+            Ok(pos)
+        };
+
+        let mut snakes = Vec::new();
+        let mut scores = Vec::new();
+        let mut crashes = Vec::new();
+        for _ in 0..self.snakes.len() {
+            let crash = reader.read_u8()?;
+            if crash != 0 && Crash::from(crash).is_none() {
+                return Err(ProtocolError::BadCrash(crash).into());
+            }
+
+            let direction = read_direction(&mut reader)?;
+            let mut turns = VecDeque::new();
+            for _ in 0..reader.read_u8()? {
+                if turns.len() >= MAX_QUEUED_TURNS {
+                    return Err(ProtocolError::Malformed("turn queue").into());
+                }
+
+                turns.push_back(read_direction(&mut reader)?);
+            }
+
+            let pending = reader.read_u16()? as usize;
+            let points = reader.read_u64()?;
+            let size = reader.read_u16()? as usize;
+            if size == 0 {
+                return Err(ProtocolError::Malformed("empty snake").into());
+            }
+
+            let mut body = VecDeque::with_capacity(size);
+            for _ in 0..size {
+                body.push_back(read_pos(&mut reader)?);
+            }
+
+            snakes.push(Snake::restore(body, direction, turns, pending));
+            scores.push(Score::restore(points));
+            crashes.push(Crash::from(crash));
+        }
+
+        let mut targets = VecDeque::new();
+        for _ in 0..reader.read_u8()? {
+            let pos = read_pos(&mut reader)?;
+            let kind = reader.read_u8()?;
+            let kind = match TargetKind::from(kind) {
+                Some(kind) => kind,
+                None => {
+                    return Err(ProtocolError::BadTargetKind(kind).into());
+                }
+            };
+
+            targets.push_back(Target::new(pos, kind));
+        }
+
+        let rng = match &self.rng {
+            Some(_) => Some(Rng::new(reader.read_u64()?)),
+            None => None
+        };
+
+        if !reader.is_empty() {
+            return Err(ProtocolError::Malformed("state snapshot").into());
+        }
+
+        if self.is_authoritative() {
+            for id in 0..snakes.len() {
+                match crashes[id] {
+                    Some(crash) => {
+                        if self.crashes[id].is_none() {
+                            self.emit(Event::Crash(id, crash));
+                        }
+
+                        continue;
+                    },
+                    None => {}
+                }
+
+                if scores[id].points() > self.scores[id].points() {
+                    self.stats[id].target_eaten(snakes[id].size());
+                    match self.target.front() {
+                        Some(target) => {
+                            let target = *target;
+                            self.emit(Event::TargetEaten(id, target));
+                        },
+                        None => {}
+                    }
+                }
+
+                if snakes[id].size() > self.snakes[id].size() {
+                    self.emit(Event::SnakeGrew(id, snakes[id].size()));
+                }
+
+                self.stats[id].tick(&snakes[id]);
+            }
+        }
+
+        self.tick_id = tick_id;
+        self.paused = paused;
+        self.snakes = snakes;
+        self.scores = scores;
+        self.crashes = crashes;
+        self.target = targets;
+        self.rng = rng;
+        self.rebuild_board();
+        Ok(())
+    }
+
+    #[allow(clippy::single_match)]
+    fn rebuild_board(&mut self) {
+        let mut board = Board::new(self.level.width(), self.level.height());
+        self.level.mark(&mut board);
+
+        for (id, snake) in self.snakes.iter().enumerate() {
+            match self.crashes[id] {
+                Some(Crash::Left) => {
+                    continue;
+                },
+                Some(_) => {
+                    for pos in snake.body() {
+                        board.mark(*pos, self.pixel(id));
+                    }
+
+                    board.mark(snake.head(), CRASH_CHAR);
+                },
+                None => {
+                    for pos in snake.body() {
+                        board.mark(*pos, self.pixel(id));
+                    }
+                }
+            }
+        }
+
+        match self.target.front() {
+            Some(target) => {
+                board.mark(target.pos, target.kind.pixel());
+            },
+            None => {}
+        }
+
+        self.board = board;
+    }
+
+    fn leave(&mut self, id: usize) {
+        if self.crashes[id].is_some() {
+            return;
+        }
+
+        self.crashes[id] = Some(Crash::Left);
+        for pos in self.snakes[id].body() {
+            self.board.unmark(*pos);
+        }
+
+        self.emit(Event::Crash(id, Crash::Left));
+    }
+
+    fn kick(&mut self, who: &str, ban: bool) -> Result<(), SnakeError> {
+        let member = match who.strip_prefix('s').and_then(|n| n.parse::<usize>().ok()) {
+            Some(n) if n > 0 => Some(Member::Spectator(n - 1)),
+            _ => {
+                match who.parse::<usize>() {
+                    Ok(n) if n > 1 => Some(Member::Player(n - 1)),
+                    _ => (0..self.snakes.len()).find(|id| *id != self.local && self.name(*id) == who).map(Member::Player)
+                }
+            }
+        };
+
+        let kicked = match (&mut self.network, member) {
+            (Some(network), Some(member)) => network.kick(member, ban)?,
+            _ => false
+        };
+
+        if !kicked {
+            self.show_chat("Game", &format!("no player or spectator {}", who));
+            return Ok(());
+        }
+
+        let name = match member {
+            Some(Member::Player(id)) => {
+                self.inbox.push(goodbye_packet(id)?);
+                self.name(id)
+            },
+            _ => format!("spectator {}", who)
+        };
+
+        self.show_chat("Game", &format!("{} was {}", name, if ban { "banned" } else { "kicked" }));
+        Ok(())
+    }
+
+    fn peer_id(&self, id: u8) -> Result<usize, SnakeError> {
+        let id = id as usize;
+        if id >= self.snakes.len() || id == self.local {
+            return Err(ProtocolError::UnknownPlayer(id as u8).into());
+        }
+
+        Ok(id)
+    }
+
+    fn send_chat(&mut self, message: &str) -> Result<(), SnakeError> {
+        if !self.supports(FEATURE_CHAT) {
+            self.show_chat("Game", "chat is off, a player runs an older version");
+            return Ok(());
+        }
+
+        let message: String = message.chars().take(CHAT_MAX_LENGTH).collect();
+        let mut packet = Packet::new(Opcode::Chat, message.len() + 1);
+        packet.push_data(&[self.local as u8])?;
+        packet.push_data(message.as_bytes())?;
+        self.send_packet(&packet)?;
+
+        self.show_chat("You", &message);
+        Ok(())
+    }
+
+    fn show_chat(&mut self, name: &str, message: &str) {
+        let message: String = message.chars().filter(|c| !c.is_control()).take(CHAT_MAX_LENGTH).collect();
+        self.chat.push_back(format!("{}: {}", name, message));
+        if self.chat.len() > CHAT_LINES {
+            self.chat.pop_front();
+        }
+    }
+
+    fn send_control(&mut self, direction: Direction, tick_id: u64) -> Result<(), SnakeError> {
+        let mut packet = Packet::new(Opcode::NewDirection, 10);
+        packet.push_data(&[self.local as u8, direction as u8])?;
+        packet.push_data(&tick_id.to_be_bytes())?;
+        self.send_packet(&packet)
+    }
+
+    fn hold_pause(&mut self, id: usize, holding: bool) {
+        let paused = self.paused;
+        if holding && !paused {
+            for other in 0..self.snakes.len() {
+                self.holding[other] = self.crashes[other] != Some(Crash::Left);
+            }
+        }
+
+        self.holding[id] = holding;
+        self.paused = self.holding.contains(&true);
+
+        let waiting: Vec<String> = (0..self.snakes.len()).filter(|other| self.holding[*other] && *other != self.local).map(|other| self.name(other)).collect();
+        let message = match (id == self.local, holding) {
+            (true, true) if !paused => "paused, the game goes on once everyone resumes".into(),
+            (true, false) if self.paused => format!("waiting for {} to resume", waiting.join(", ")),
+            (false, true) if !paused => format!("{} requested a pause", self.name(id)),
+            (false, false) if self.paused => format!("{} is ready to resume", self.name(id)),
+            (false, false) => format!("{} resumed", self.name(id)),
+            _ => {
+                return;
+            }
+        };
+
+        self.show_chat("Game", &message);
+    }
+
+    fn send_pause(&mut self, paused: bool) -> Result<(), SnakeError> {
+        let opcode = if self.supports(FEATURE_PAUSE_REQUEST) { Opcode::PauseRequest } else { Opcode::Pause };
+        let mut packet = Packet::new(opcode, 2);
+        packet.push_data(&[self.local as u8, paused as u8])?;
+        self.send_packet(&packet)
+    }
+
+    fn broadcast_snapshot(&mut self) -> Result<(), SnakeError> {
+        let points: Vec<u64> = self.scores.iter().map(|score| score.points()).collect();
+        let board = &self.board;
+        match &mut self.network {
+            Some(network) => {
+                network.broadcast_snapshot(&|| snapshot(board, &points))
+            },
+            None => Ok(())
+        }
+    }
+
+    fn send_packet(&mut self, packet: &Packet) -> Result<(), SnakeError> {
+        match &mut self.network {
+            Some(network) => {
+                network.send(packet)
+            },
+            None => {
+                panic!("unreachable [SnakeGame::send_packet()]");
+            }
+        }
+    }
+}
+
+fn setup(level: &Level, players: usize, local: usize, local_multiplayer: bool, config: &GameConfig) -> Result<(Board, Vec<Snake>, Target), SnakeError> {
+    let mut board = Board::new(level.width(), level.height());
+    level.mark(&mut board);
+
+    let mut snakes = Vec::new();
+    let target = if players == 1 {
+        let snake = match place_snake(&board, level.spawn(0), config.start_length, config.walls) {
+            Some(snake) => snake,
+            None => {
+                return Err(SnakeError::BadLevel("no room for the starting length".into()));
+            }
+        };
+
+        for pos in snake.body() {
+            board.mark(*pos, PLAYER_CHAR);
+        }
+
+        snakes.push(snake);
+
+        let pos = match level.target() {
+            Some(pos) => pos,
+            None => {
+                match board.random_position() {
+                    Some(pos) => pos,
+                    None => {
+                        return Err(SnakeError::BadLevel("no room for a target".into()));
+                    }
+                }
+            }
+        };
+        Target::new(pos, TargetKind::Normal)
+    } else {
+        for (id, head) in level.multiplayer_spawns(players).into_iter().enumerate() {
+            let snake = match Snake::spawn(&board, head, SPAWN_DIRECTIONS[id], config.start_length, config.walls) {
+                Some(snake) => snake,
+                None => {
+                    return Err(SnakeError::BadLevel("no room for the starting length".into()));
+                }
+            };
+
+            for pos in snake.body() {
+                board.mark(*pos, snake_pixel(id, local, local_multiplayer));
+            }
+
+            snakes.push(snake);
+        }
+
+        Target::new(level.multiplayer_target(), TargetKind::Normal)
+    };
+
+    board.mark(target.pos, target.kind.pixel());
+    Ok((board, snakes, target))
+}
+
+#[allow(clippy::single_match)]
+fn place_snake(board: &Board, spawn: Option<(usize, usize)>, length: usize, walls: bool) -> Option<Snake> {
+    for _ in 0..SPAWN_ATTEMPTS {
+        let head = match spawn {
+            Some(head) => head,
+            None => board.random_position()?
+        };
+
+        match Snake::spawn(board, head, Direction::random(), length, walls) {
+            Some(snake) => {
+                return Some(snake);
+            },
+            None => {}
+        }
+    }
+
+    None
+}
+
+fn snake_pixel(id: usize, local: usize, local_multiplayer: bool) -> char {
+    if local_multiplayer {
+        SNAKE_CHARS[id]
+    } else if id == local {
+        PLAYER_CHAR
+    } else {
+        OPPONENT_CHAR
+    }
+}
+
+fn verdict_packet(result: &GameResult) -> Result<Packet, SnakeError> {
+    let (kind, reason) = match result {
+        GameResult::Win(reason) => (0x01, reason),
+        GameResult::Lose(reason) => (0x02, reason),
+        GameResult::Draw(reason) => (0x03, reason),
+        GameResult::Winner(_, _) => {
+            panic!("bad result [verdict_packet()]");
+        }
+    };
+
+    let mut packet = Packet::new(Opcode::GameOver, reason.len() + 1);
+    packet.push_data(&[kind])?;
+    packet.push_data(reason.as_bytes())?;
+    Ok(packet)
+}
+
+fn load_level(mode: &GameMode, config: &GameConfig) -> Result<Level, SnakeError> {
+    let replay = match mode {
+        GameMode::Replay(replay) => replay.level(),
+        _ => None
+    };
+
+    let mut level = match (&config.level, replay) {
+        (_, Some(text)) => Level::parse(text)?,
+        (Some(path), None) => Level::load(path)?,
+        (None, None) => Level::empty(config.width, config.height)
+    };
+
+    match mode {
+        GameMode::Singleplayer | GameMode::Replay(_) => {
+            level.scatter_obstacles(config.obstacles, config.walls);
+        },
+        _ => {}
+    }
+
+    Ok(level)
+}
+
+fn settings_hash(level: &Level, config: &GameConfig) -> u64 {
+    let mut data = Vec::with_capacity(8 + 1 + 8 * 3);
+    data.extend_from_slice(&level.fingerprint().to_be_bytes());
+    data.push(config.walls as u8);
+    data.extend_from_slice(&(config.obstacles as u64).to_be_bytes());
+    data.extend_from_slice(&(config.start_length as u64).to_be_bytes());
+    data.extend_from_slice(&(config.growth as u64).to_be_bytes());
+    fnv1a(&data)
+}
+
+fn read_verdict(reader: &mut PacketReader, size: usize) -> Result<GameResult, ProtocolError> {
+    let kind = reader.read_u8()?;
+    let reason = String::from_utf8_lossy(reader.read_bytes(size - 1)?).chars().filter(|c| !c.is_control()).collect();
+    match kind {
+        0x01 => Ok(GameResult::Win(reason)),
+        0x02 => Ok(GameResult::Lose(reason)),
+        0x03 => Ok(GameResult::Draw(reason)),
+        _ => Err(ProtocolError::Malformed("game result"))
+    }
+}
+
+fn read_direction(reader: &mut PacketReader) -> Result<Direction, ProtocolError> {
+    let direction = reader.read_u8()?;
+    match Direction::from(direction) {
+        Some(direction) => Ok(direction),
+        None => Err(ProtocolError::BadDirection(direction))
     }
 }
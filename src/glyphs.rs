@@ -0,0 +1,131 @@
+use std::fs::read_to_string;
+
+use crate::{
+    board::{
+        is_snake, CRASH_CHAR, GOLDEN_CHAR, SNAKE_CHARS, TARGET_CHAR, WALL_CHAR
+    },
+    direction::Direction,
+    error::SnakeError,
+    util::data_dir
+};
+
+pub const GLYPHS_FILE: &str = "glyphs.txt";
+
+const EMOJI: std::ops::RangeInclusive<char> = '\u{1f300}'..='\u{1faff}';
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Glyphs {
+    snakes: [char; 4],
+    heads: Option<[char; 4]>,
+    target: char,
+    golden: char,
+    crash: char,
+    wall: char,
+    wide: bool
+}
+
+impl Default for Glyphs {
+    fn default() -> Self {
+        Glyphs { snakes: SNAKE_CHARS, heads: None, target: TARGET_CHAR, golden: GOLDEN_CHAR, crash: CRASH_CHAR, wall: WALL_CHAR, wide: false }
+    }
+}
+
+impl Glyphs {
+    pub fn emoji() -> Self {
+        Glyphs {
+            snakes: ['\u{1f7e9}', '\u{1f7e5}', '\u{1f7e6}', '\u{1f7ea}'],
+            heads: None,
+            target: '\u{1f34e}',
+            golden: '\u{1f31f}',
+            crash: '\u{1f4a5}',
+            wall: '\u{1f9f1}',
+            wide: true
+        }
+    }
+
+    pub fn load() -> Result<Self, SnakeError> {
+        match data_dir().and_then(|dir| read_to_string(dir.join(GLYPHS_FILE)).ok()) {
+            Some(text) => Glyphs::parse(&text),
+            None => Ok(Glyphs::default())
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, SnakeError> {
+        let mut glyphs = Glyphs::default();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+
+            let bad = || SnakeError::BadConfig(format!("{} line {}", GLYPHS_FILE, n + 1));
+            let (key, value) = match line.split_once(char::is_whitespace) {
+                Some((key, value)) => (key, value.trim()),
+                None => {
+                    return Err(bad());
+                }
+            };
+
+            let chars: Vec<char> = value.chars().collect();
+            if chars.iter().any(|c| c.is_whitespace() || c.is_control()) {
+                return Err(bad());
+            }
+
+            match (key, chars.len()) {
+                ("snake", 1..=4) => {
+                    glyphs.snakes[..chars.len()].copy_from_slice(&chars);
+                },
+                ("head", 4) => {
+                    glyphs.heads = Some([chars[3], chars[1], chars[2], chars[0]]);
+                },
+                ("target", 1) => {
+                    glyphs.target = chars[0];
+                },
+                ("golden", 1) => {
+                    glyphs.golden = chars[0];
+                },
+                ("crash", 1) => {
+                    glyphs.crash = chars[0];
+                },
+                ("wall", 1) => {
+                    glyphs.wall = chars[0];
+                },
+                _ => {
+                    return Err(bad());
+                }
+            }
+        }
+
+        Ok(glyphs)
+    }
+
+    pub fn glyph(&self, pixel: char) -> char {
+        match pixel {
+            TARGET_CHAR => self.target,
+            GOLDEN_CHAR => self.golden,
+            CRASH_CHAR => self.crash,
+            WALL_CHAR => self.wall,
+            pixel if is_snake(pixel) => {
+                let index = SNAKE_CHARS.iter().position(|snake| *snake == pixel).unwrap();
+                self.snakes[index]
+            },
+            pixel => pixel
+        }
+    }
+
+    pub fn head(&self, direction: Direction) -> Option<char> {
+        self.heads.map(|heads| heads[direction as usize])
+    }
+
+    pub fn is_wide(&self) -> bool {
+        self.wide
+    }
+
+    pub fn cell_width(&self) -> usize {
+        if self.wide { 4 } else { 3 }
+    }
+}
+
+pub fn is_emoji(glyph: char) -> bool {
+    EMOJI.contains(&glyph)
+}
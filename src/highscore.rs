@@ -0,0 +1,112 @@
+use std::{cmp::Reverse, fs::{create_dir_all, read_to_string, write}};
+
+use crate::{error::SnakeError, util::data_dir};
+
+pub const HIGH_SCORES_FILE: &str = "highscores.txt";
+pub const HIGH_SCORES_SIZE: usize = 10;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HighScore {
+    points: u64,
+    date: String,
+    width: usize,
+    height: usize,
+    mode: String
+}
+
+impl HighScore {
+    pub fn new(points: u64, date: String, width: usize, height: usize, mode: &str) -> Self {
+        HighScore { points, date, width, height, mode: mode.into() }
+    }
+
+    fn encode(&self) -> String {
+        format!("{}\t{}\t{}x{}\t{}", self.points, self.date, self.width, self.height, self.mode)
+    }
+
+    fn decode(line: &str) -> Option<HighScore> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 4 {
+            return None;
+        }
+
+        let points = fields[0].parse().ok()?;
+        let (width, height) = fields[2].split_once('x')?;
+        let width = width.parse().ok()?;
+        let height = height.parse().ok()?;
+
+        Some(HighScore::new(points, fields[1].into(), width, height, fields[3]))
+    }
+}
+
+pub struct HighScores {
+    entries: Vec<HighScore>
+}
+
+impl HighScores {
+    #[allow(clippy::single_match)]
+    pub fn load() -> Self {
+        let mut entries = Vec::new();
+        match data_dir().and_then(|dir| read_to_string(dir.join(HIGH_SCORES_FILE)).ok()) {
+            Some(text) => {
+                for line in text.lines() {
+                    match HighScore::decode(line) {
+                        Some(entry) => {
+                            entries.push(entry);
+                        },
+                        None => {}
+                    }
+                }
+            },
+            None => {}
+        }
+
+        entries.sort_by_key(|entry| Reverse(entry.points));
+        entries.truncate(HIGH_SCORES_SIZE);
+        HighScores { entries }
+    }
+
+    pub fn save(&self) -> Result<(), SnakeError> {
+        let dir = match data_dir() {
+            Some(dir) => dir,
+            None => {
+                return Err(SnakeError::NoDataDir);
+            }
+        };
+
+        create_dir_all(&dir)?;
+
+        let mut text = String::new();
+        for entry in &self.entries {
+            text.push_str(&entry.encode());
+            text.push('\n');
+        }
+
+        write(dir.join(HIGH_SCORES_FILE), text)?;
+        Ok(())
+    }
+
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let rank = self.entries.iter().position(|other| entry.points > other.points).unwrap_or(self.entries.len());
+        if rank >= HIGH_SCORES_SIZE {
+            return None;
+        }
+
+        self.entries.insert(rank, entry);
+        self.entries.truncate(HIGH_SCORES_SIZE);
+        Some(rank)
+    }
+
+    pub fn draw(&self, highlight: Option<usize>) -> String {
+        let mut s = String::new();
+        s.push_str("High scores\n");
+        for (i, entry) in self.entries.iter().enumerate() {
+            let marker = if highlight == Some(i) { '*' } else { ' ' };
+            s.push_str(&format!(
+                "{}{:>2}. {:>6}  {}  {:>9}  {}\n",
+                marker, i + 1, entry.points, entry.date, format!("{}x{}", entry.width, entry.height), entry.mode
+            ));
+        }
+
+        s
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{direction::Direction, keymap::Keymap};
+
+pub const CHAT_KEY: char = 't';
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Key {
+    Direction(usize, Direction),
+    Pause,
+    Quit,
+    Yes,
+    No,
+    Restart,
+    Select,
+    Chat(String),
+    Kick(String, bool)
+}
+
+enum State {
+    Ground,
+    Escape,
+    Sequence
+}
+
+pub struct InputParser {
+    state: State,
+    keymap: Keymap
+}
+
+impl InputParser {
+    pub fn new(keymap: Keymap) -> Self {
+        InputParser { state: State::Ground, keymap }
+    }
+
+    #[allow(clippy::single_match)]
+    pub fn parse(&mut self, line: &str) -> Vec<Key> {
+        let mut keys = Vec::new();
+        if line.trim().is_empty() {
+            keys.push(Key::Select);
+            return keys;
+        }
+
+        match line.strip_prefix(CHAT_KEY) {
+            Some(message) if message.starts_with(' ') => {
+                let message = message.trim();
+                if !message.is_empty() {
+                    keys.push(Key::Chat(message.into()));
+                }
+
+                return keys;
+            },
+            _ => {}
+        }
+
+        match line.trim().split_once(' ') {
+            Some((command, who)) if command == "/kick" || command == "/ban" => {
+                keys.push(Key::Kick(who.trim().into(), command == "/ban"));
+                return keys;
+            },
+            _ => {}
+        }
+
+        for byte in line.bytes() {
+            match self.feed(byte) {
+                Some(key) => {
+                    keys.push(key);
+                },
+                None => {}
+            }
+        }
+
+        keys
+    }
+
+    #[allow(clippy::single_match)]
+    pub fn feed(&mut self, byte: u8) -> Option<Key> {
+        match self.state {
+            State::Ground => {
+                match self.keymap.lookup(byte) {
+                    Some(key) => {
+                        return Some(key);
+                    },
+                    None => {}
+                }
+
+                match byte {
+                    0x1b => {
+                        self.state = State::Escape;
+                        None
+                    },
+                    b'l' => Some(Key::Direction(2, Direction::Right)),
+                    b'k' => Some(Key::Direction(2, Direction::Down)),
+                    b'j' => Some(Key::Direction(2, Direction::Left)),
+                    b'i' => Some(Key::Direction(2, Direction::Up)),
+                    b'6' => Some(Key::Direction(3, Direction::Right)),
+                    b'5' => Some(Key::Direction(3, Direction::Down)),
+                    b'4' => Some(Key::Direction(3, Direction::Left)),
+                    b'8' => Some(Key::Direction(3, Direction::Up)),
+                    b'y' => Some(Key::Yes),
+                    b'n' => Some(Key::No),
+                    b'r' => Some(Key::Restart),
+                    _ => None
+                }
+            },
+            State::Escape => {
+                match byte {
+                    b'[' | b'O' => {
+                        self.state = State::Sequence;
+                    },
+                    0x1b => {},
+                    _ => {
+                        self.state = State::Ground;
+                    }
+                }
+
+                None
+            },
+            State::Sequence => {
+                match byte {
+                    0x30..=0x3f => {
+                        return None;
+                    },
+                    _ => {
+                        self.state = State::Ground;
+                    }
+                }
+
+                match byte {
+                    b'C' => Some(Key::Direction(1, Direction::Right)),
+                    b'B' => Some(Key::Direction(1, Direction::Down)),
+                    b'D' => Some(Key::Direction(1, Direction::Left)),
+                    b'A' => Some(Key::Direction(1, Direction::Up)),
+                    _ => None
+                }
+            }
+        }
+    }
+}
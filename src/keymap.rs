@@ -0,0 +1,144 @@
+use std::fs::{create_dir_all, read_to_string, write};
+
+use crate::{
+    direction::Direction,
+    error::SnakeError,
+    input::{
+        Key, CHAT_KEY
+    },
+    util::data_dir
+};
+
+pub const KEYS_FILE: &str = "keys.txt";
+pub const ACTIONS: [Action; 6] = [Action::Up, Action::Down, Action::Left, Action::Right, Action::Pause, Action::Quit];
+
+const RESERVED: [char; 4] = ['y', 'n', 'r', CHAT_KEY];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Pause,
+    Quit
+}
+
+impl Action {
+    pub fn parse(name: &str) -> Option<Action> {
+        ACTIONS.iter().copied().find(|action| action.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Pause => "pause",
+            Action::Quit => "quit"
+        }
+    }
+
+    fn key(&self) -> Key {
+        match self {
+            Action::Up => Key::Direction(0, Direction::Up),
+            Action::Down => Key::Direction(0, Direction::Down),
+            Action::Left => Key::Direction(0, Direction::Left),
+            Action::Right => Key::Direction(0, Direction::Right),
+            Action::Pause => Key::Pause,
+            Action::Quit => Key::Quit
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keymap {
+    keys: [char; 6]
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap { keys: ['w', 's', 'a', 'd', 'p', 'q'] }
+    }
+}
+
+impl Keymap {
+    pub fn load() -> Result<Self, SnakeError> {
+        match data_dir().and_then(|dir| read_to_string(dir.join(KEYS_FILE)).ok()) {
+            Some(text) => Keymap::parse(&text),
+            None => Ok(Keymap::default())
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, SnakeError> {
+        let mut keymap = Keymap::default();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+
+            let bad = || SnakeError::BadConfig(format!("{} line {}", KEYS_FILE, n + 1));
+            let (action, key) = match line.split_once(char::is_whitespace) {
+                Some((action, key)) => (action, key.trim()),
+                None => {
+                    return Err(bad());
+                }
+            };
+
+            let mut chars = key.chars();
+            match (Action::parse(action), chars.next(), chars.next()) {
+                (Some(action), Some(key), None) => {
+                    keymap.bind(action, key).map_err(|_| bad())?;
+                },
+                _ => {
+                    return Err(bad());
+                }
+            }
+        }
+
+        Ok(keymap)
+    }
+
+    pub fn save(&self) -> Result<(), SnakeError> {
+        let dir = match data_dir() {
+            Some(dir) => dir,
+            None => {
+                return Err(SnakeError::NoDataDir);
+            }
+        };
+
+        create_dir_all(&dir)?;
+
+        let mut text = String::new();
+        for action in ACTIONS {
+            text.push_str(&format!("{} {}\n", action.name(), self.key(action)));
+        }
+
+        write(dir.join(KEYS_FILE), text)?;
+        Ok(())
+    }
+
+    pub fn key(&self, action: Action) -> char {
+        self.keys[action as usize]
+    }
+
+    pub fn bind(&mut self, action: Action, key: char) -> Result<(), String> {
+        if !key.is_ascii_graphic() || RESERVED.contains(&key) {
+            return Err(format!("{} can't be bound", key));
+        }
+
+        match ACTIONS.iter().find(|other| **other != action && self.key(**other) == key) {
+            Some(other) => Err(format!("{} is already bound to {}", key, other.name())),
+            None => {
+                self.keys[action as usize] = key;
+                Ok(())
+            }
+        }
+    }
+
+    pub fn lookup(&self, byte: u8) -> Option<Key> {
+        ACTIONS.iter().find(|action| self.key(**action) as u32 == byte as u32).map(Action::key)
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    cmp::Reverse,
+    io::{
+        Read, Write
+    },
+    net::{
+        TcpStream, ToSocketAddrs
+    },
+    time::Duration
+};
+
+use crate::error::SnakeError;
+
+pub const LEADERBOARD_SIZE: usize = 10;
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_RESPONSE_SIZE: u64 = 1 << 20;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    points: u64,
+    width: usize,
+    height: usize,
+    mode: String,
+    seed: u64,
+    replay: u64
+}
+
+impl Entry {
+    pub fn new(points: u64, width: usize, height: usize, mode: &str, seed: u64, replay: u64) -> Self {
+        Entry { points, width, height, mode: mode.into(), seed, replay }
+    }
+
+    fn encode(&self) -> String {
+        format!("{}\t{}x{}\t{}\t{}\t{:016x}", self.points, self.width, self.height, self.mode, self.seed, self.replay)
+    }
+
+    fn decode(line: &str) -> Option<Entry> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 5 {
+            return None;
+        }
+
+        let points = fields[0].parse().ok()?;
+        let (width, height) = fields[1].split_once('x')?;
+        let width = width.parse().ok()?;
+        let height = height.parse().ok()?;
+        let seed = fields[3].parse().ok()?;
+        let replay = u64::from_str_radix(fields[4], 16).ok()?;
+
+        Some(Entry::new(points, width, height, fields[2], seed, replay))
+    }
+}
+
+pub fn submit(url: &str, entry: &Entry) -> Result<(), SnakeError> {
+    request("POST", url, &entry.encode())?;
+    Ok(())
+}
+
+pub fn show(url: &str) -> Result<(), SnakeError> {
+    let body = request("GET", url, "")?;
+    let mut entries: Vec<Entry> = body.lines().filter_map(Entry::decode).collect();
+    entries.sort_by_key(|entry| Reverse(entry.points));
+    entries.truncate(LEADERBOARD_SIZE);
+
+    println!("Leaderboard");
+    for (i, entry) in entries.iter().enumerate() {
+        println!(
+            "{:>2}. {:>6}  {:>9}  {:<12} seed {}",
+            i + 1, entry.points, format!("{}x{}", entry.width, entry.height), entry.mode, entry.seed
+        );
+    }
+
+    if entries.is_empty() {
+        println!("No entries yet");
+    }
+
+    Ok(())
+}
+
+pub fn is_valid_url(url: &str) -> bool {
+    parse_url(url).is_some()
+}
+
+fn request(method: &str, url: &str, body: &str) -> Result<String, SnakeError> {
+    let (authority, host, port, path) = match parse_url(url) {
+        Some(parts) => parts,
+        None => {
+            return Err(SnakeError::BadLeaderboard("unsupported url".into()));
+        }
+    };
+
+    let address = match (host.as_str(), port).to_socket_addrs()?.next() {
+        Some(address) => address,
+        None => {
+            return Err(SnakeError::BadLeaderboard(format!("could not resolve {}", host)));
+        }
+    };
+
+    let mut stream = TcpStream::connect_timeout(&address, HTTP_TIMEOUT)?;
+    stream.set_read_timeout(Some(HTTP_TIMEOUT))?;
+    stream.set_write_timeout(Some(HTTP_TIMEOUT))?;
+
+    let request = format!(
+        "{} {} HTTP/1.0\r\nHost: {}\r\nUser-Agent: snake-game\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\r\n{}",
+        method, path, authority, body.len(), body
+    );
+    stream.write_all(request.as_bytes())?;
+
+    let mut response = Vec::new();
+    stream.take(MAX_RESPONSE_SIZE).read_to_end(&mut response)?;
+    let response = String::from_utf8_lossy(&response);
+
+    let (head, body) = match response.split_once("\r\n\r\n") {
+        Some(parts) => parts,
+        None => {
+            return Err(SnakeError::BadLeaderboard("bad response".into()));
+        }
+    };
+
+    let status = head.lines().next().and_then(|line| line.split_whitespace().nth(1)).unwrap_or("");
+    if !status.starts_with('2') {
+        return Err(SnakeError::BadLeaderboard(format!("HTTP {}", status)));
+    }
+
+    Ok(body.into())
+}
+
+fn parse_url(url: &str) -> Option<(String, String, u16, String)> {
+    let rest = url.strip_prefix("http://")?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/")
+    };
+
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(rest) => {
+            let (host, port) = rest.split_once(']')?;
+            match port.strip_prefix(':') {
+                Some(port) => (host, port.parse().ok()?),
+                None if port.is_empty() => (host, 80),
+                None => {
+                    return None;
+                }
+            }
+        },
+        None => {
+            match authority.split_once(':') {
+                Some((host, port)) => (host, port.parse().ok()?),
+                None => (authority, 80)
+            }
+        }
+    };
+
+    if host.is_empty() || path.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return None;
+    }
+
+    Some((authority.into(), host.into(), port, path.into()))
+}
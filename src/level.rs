@@ -0,0 +1,289 @@
+use std::fs::read_to_string;
+
+use crate::{
+    board::{
+        is_portal, Board, MIN_BOARD_SIZE, WALL_CHAR
+    },
+    error::SnakeError,
+    util::{
+        fnv1a, random_number
+    }
+};
+
+pub const SPAWN_CHARS: [char; 4] = ['1', '2', '3', '4'];
+pub const LEVEL_TARGET_CHAR: char = 'o';
+pub const MAX_OBSTACLES: usize = 50;
+
+const SNAPSHOT_HEADROOM: usize = 256;
+const MAX_BOARD_AREA: usize = (u16::MAX as usize - SNAPSHOT_HEADROOM) / 4;
+
+pub struct Portal {
+    pub pixel: char,
+    pub ends: ((usize, usize), (usize, usize))
+}
+
+pub struct Level {
+    width: usize,
+    height: usize,
+    obstacles: Vec<(usize, usize)>,
+    spawns: [Option<(usize, usize)>; 4],
+    target: Option<(usize, usize)>,
+    portals: Vec<Portal>
+}
+
+impl Level {
+    pub fn empty(width: usize, height: usize) -> Self {
+        Level { width, height, obstacles: Vec::new(), spawns: [None; 4], target: None, portals: Vec::new() }
+    }
+
+    pub fn load(path: &str) -> Result<Self, SnakeError> {
+        let text = read_to_string(path)?;
+        Level::parse(&text)
+    }
+
+    #[allow(clippy::single_match)]
+    pub fn parse(text: &str) -> Result<Self, SnakeError> {
+        let mut rows: Vec<Vec<char>> = text.lines()
+            .map(|line| line.trim_end_matches('\r').chars().collect())
+            .collect();
+
+        while rows.last().is_some_and(|row| row.is_empty()) {
+            rows.pop();
+        }
+
+        let height = rows.len();
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        if !is_valid_size(width, height) {
+            return Err(SnakeError::BadLevel("bad board size".into()));
+        }
+
+        let mut level = Level::empty(width, height);
+        let mut unpaired: Vec<(char, (usize, usize))> = Vec::new();
+        for (i, row) in rows.iter().enumerate() {
+            for (j, pixel) in row.iter().enumerate() {
+                match *pixel {
+                    ' ' | '.' => {},
+                    WALL_CHAR => {
+                        level.obstacles.push((i, j));
+                    },
+                    LEVEL_TARGET_CHAR => {
+                        if level.target.is_some() {
+                            return Err(SnakeError::BadLevel("more than one target".into()));
+                        }
+
+                        level.target = Some((i, j));
+                    },
+                    pixel if is_portal(pixel) => {
+                        if level.portals.iter().any(|portal| portal.pixel == pixel) {
+                            return Err(SnakeError::BadLevel(format!("more than two portals '{}'", pixel)));
+                        }
+
+                        match unpaired.iter().position(|portal| portal.0 == pixel) {
+                            Some(index) => {
+                                let (_, twin) = unpaired.remove(index);
+                                level.portals.push(Portal { pixel, ends: (twin, (i, j)) });
+                            },
+                            None => {
+                                unpaired.push((pixel, (i, j)));
+                            }
+                        }
+                    },
+                    _ => {
+                        match SPAWN_CHARS.iter().position(|c| c == pixel) {
+                            Some(index) => {
+                                if level.spawns[index].is_some() {
+                                    return Err(SnakeError::BadLevel(format!("more than one spawn '{}'", pixel)));
+                                }
+
+                                level.spawns[index] = Some((i, j));
+                            },
+                            None => {
+                                return Err(SnakeError::BadLevel(format!("unknown tile '{}'", pixel)));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        match unpaired.first() {
+            Some((pixel, _)) => {
+                return Err(SnakeError::BadLevel(format!("portal '{}' has no twin", pixel)));
+            },
+            None => {}
+        }
+
+        Ok(level)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn portals(&self) -> &Vec<Portal> {
+        &self.portals
+    }
+
+    pub fn twin(&self, pos: (usize, usize)) -> Option<(usize, usize)> {
+        for portal in &self.portals {
+            if portal.ends.0 == pos {
+                return Some(portal.ends.1);
+            } else if portal.ends.1 == pos {
+                return Some(portal.ends.0);
+            }
+        }
+
+        None
+    }
+
+    pub fn mark(&self, board: &mut Board) {
+        for obstacle in &self.obstacles {
+            board.mark(*obstacle, WALL_CHAR);
+        }
+
+        for portal in &self.portals {
+            board.mark(portal.ends.0, portal.pixel);
+            board.mark(portal.ends.1, portal.pixel);
+        }
+    }
+
+    pub fn spawn(&self, index: usize) -> Option<(usize, usize)> {
+        self.spawns[index]
+    }
+
+    pub fn target(&self) -> Option<(usize, usize)> {
+        self.target
+    }
+
+    pub fn multiplayer_spawns(&self, count: usize) -> Vec<(usize, usize)> {
+        let defaults = [
+            (1, 1),
+            (self.height - 2, self.width - 2),
+            (1, self.width - 2),
+            (self.height - 2, 1)
+        ];
+
+        (0..count).map(|i| self.spawns[i].unwrap_or(defaults[i])).collect()
+    }
+
+    pub fn multiplayer_target(&self) -> (usize, usize) {
+        self.target.unwrap_or((self.height / 2, self.width / 2))
+    }
+
+    pub fn fingerprint(&self) -> u64 {
+        let mut text = format!("{}x{} {:?} {:?} {:?}", self.width, self.height, self.obstacles, self.spawns, self.target);
+        for portal in &self.portals {
+            text.push_str(&format!(" {}{:?}", portal.pixel, portal.ends));
+        }
+
+        fnv1a(text.as_bytes())
+    }
+
+    pub fn validate(&self, players: usize, walls: bool) -> Result<(), SnakeError> {
+        let mut board = Board::new(self.width, self.height);
+        self.mark(&mut board);
+
+        if players > 1 {
+            let spawns = self.multiplayer_spawns(players);
+            let target = self.multiplayer_target();
+            for pos in spawns.iter().chain([&target]) {
+                if board.value(*pos) != ' ' {
+                    return Err(SnakeError::BadLevel("spawn or target inside a wall".into()));
+                }
+            }
+
+            for (i, pos) in spawns.iter().enumerate() {
+                if *pos == target || spawns[i + 1..].contains(pos) {
+                    return Err(SnakeError::BadLevel("overlapping spawns and target".into()));
+                }
+            }
+
+            for pair in spawns.chunks_exact(2) {
+                if self.mirror(pair[0]) != pair[1] {
+                    return Err(SnakeError::BadLevel("spawns are not symmetric".into()));
+                }
+            }
+
+            for obstacle in &self.obstacles {
+                if board.value(self.mirror(*obstacle)) != WALL_CHAR {
+                    return Err(SnakeError::BadLevel("walls are not symmetric".into()));
+                }
+            }
+
+            for portal in &self.portals {
+                if !is_portal(board.value(self.mirror(portal.ends.0))) || !is_portal(board.value(self.mirror(portal.ends.1))) {
+                    return Err(SnakeError::BadLevel("portals are not symmetric".into()));
+                }
+            }
+
+            let reachable = board.reachable(spawns[0], walls);
+            for pos in spawns.iter().chain([&target]) {
+                if !reachable[pos.0][pos.1] {
+                    return Err(SnakeError::BadLevel("spawns or target not reachable".into()));
+                }
+            }
+        } else {
+            match (self.spawns[0], self.target) {
+                (Some(spawn), Some(target)) if !board.reachable(spawn, walls)[target.0][target.1] => {
+                    return Err(SnakeError::BadLevel("target not reachable".into()));
+                },
+                _ => {}
+            }
+
+            if board.random_position().is_none() {
+                return Err(SnakeError::BadLevel("no room to spawn".into()));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn scatter_obstacles(&mut self, percent: usize, walls: bool) {
+        let mut board = Board::new(self.width, self.height);
+        self.mark(&mut board);
+
+        let mut candidates = Vec::new();
+        for i in 0..self.height {
+            for j in 0..self.width {
+                let pos = (i, j);
+                if board.value(pos) == ' ' && !self.spawns.contains(&Some(pos)) && self.target != Some(pos) {
+                    candidates.push(pos);
+                }
+            }
+        }
+
+        for i in (1..candidates.len()).rev() {
+            let j = random_number() as usize % (i + 1);
+            candidates.swap(i, j);
+        }
+
+        if !board.is_connected(walls) {
+            return;
+        }
+
+        let mut count = self.width * self.height * percent / 100;
+        for pos in candidates {
+            if count == 0 {
+                break;
+            }
+
+            if board.stays_connected(pos, walls) {
+                board.mark(pos, WALL_CHAR);
+                self.obstacles.push(pos);
+                count -= 1;
+            }
+        }
+    }
+
+    fn mirror(&self, pos: (usize, usize)) -> (usize, usize) {
+        (self.height - 1 - pos.0, self.width - 1 - pos.1)
+    }
+}
+
+pub fn is_valid_size(width: usize, height: usize) -> bool {
+    width >= MIN_BOARD_SIZE && height >= MIN_BOARD_SIZE && width.checked_mul(height).is_some_and(|area| area <= MAX_BOARD_AREA)
+}
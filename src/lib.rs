@@ -0,0 +1,43 @@
+mod ai;
+mod board;
+mod bot;
+mod campaign;
+mod cast;
+pub mod cli;
+pub mod config;
+pub mod direction;
+pub mod discovery;
+pub mod error;
+mod events;
+mod export;
+pub mod game;
+mod glyphs;
+mod highscore;
+mod input;
+mod keymap;
+pub mod leaderboard;
+mod level;
+pub mod lifetime;
+mod mdns;
+pub mod menu;
+mod metrics;
+pub mod net;
+mod packet;
+mod pause;
+mod punch;
+mod qr;
+pub mod relay;
+pub mod replay;
+mod score;
+pub mod signal;
+pub mod simulate;
+mod snake;
+pub mod spectator;
+mod stats;
+mod sync;
+mod target;
+mod terminal;
+mod token;
+pub mod util;
+mod web;
+mod websocket;
@@ -0,0 +1,142 @@
+use std::fs::{create_dir_all, read_to_string, write};
+
+use crate::{error::SnakeError, util::data_dir};
+
+pub const LIFETIME_FILE: &str = "lifetime.txt";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Draw
+}
+
+impl Outcome {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Outcome::Win => "win",
+            Outcome::Loss => "loss",
+            Outcome::Draw => "draw"
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Record {
+    name: String,
+    wins: u64,
+    losses: u64,
+    draws: u64
+}
+
+#[derive(Default)]
+pub struct Lifetime {
+    games: u64,
+    apples: u64,
+    ticks: u64,
+    records: Vec<Record>
+}
+
+impl Lifetime {
+    #[allow(clippy::single_match)]
+    pub fn load() -> Self {
+        let mut lifetime = Lifetime::default();
+        match data_dir().and_then(|dir| read_to_string(dir.join(LIFETIME_FILE)).ok()) {
+            Some(text) => {
+                for line in text.lines() {
+                    lifetime.decode(line);
+                }
+            },
+            None => {}
+        }
+
+        lifetime
+    }
+
+    fn decode(&mut self, line: &str) -> Option<()> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        match fields[..] {
+            ["games", games] => {
+                self.games = games.parse().ok()?;
+            },
+            ["apples", apples] => {
+                self.apples = apples.parse().ok()?;
+            },
+            ["ticks", ticks] => {
+                self.ticks = ticks.parse().ok()?;
+            },
+            ["opponent", name, wins, losses, draws] => {
+                let record = Record { name: name.into(), wins: wins.parse().ok()?, losses: losses.parse().ok()?, draws: draws.parse().ok()? };
+                self.records.push(record);
+            },
+            _ => {}
+        }
+
+        Some(())
+    }
+
+    pub fn save(&self) -> Result<(), SnakeError> {
+        let dir = match data_dir() {
+            Some(dir) => dir,
+            None => {
+                return Err(SnakeError::NoDataDir);
+            }
+        };
+
+        create_dir_all(&dir)?;
+
+        let mut text = format!("games\t{}\napples\t{}\nticks\t{}\n", self.games, self.apples, self.ticks);
+        for record in &self.records {
+            text.push_str(&format!("opponent\t{}\t{}\t{}\t{}\n", record.name, record.wins, record.losses, record.draws));
+        }
+
+        write(dir.join(LIFETIME_FILE), text)?;
+        Ok(())
+    }
+
+    pub fn game_played(&mut self, apples: u64, ticks: u64) {
+        self.games += 1;
+        self.apples += apples;
+        self.ticks += ticks;
+    }
+
+    pub fn opponent(&mut self, name: &str, outcome: Outcome) {
+        let index = match self.records.iter().position(|record| record.name == name) {
+            Some(index) => index,
+            None => {
+                self.records.push(Record { name: name.into(), wins: 0, losses: 0, draws: 0 });
+                self.records.len() - 1
+            }
+        };
+
+        let record = &mut self.records[index];
+        match outcome {
+            Outcome::Win => record.wins += 1,
+            Outcome::Loss => record.losses += 1,
+            Outcome::Draw => record.draws += 1
+        }
+    }
+
+    pub fn draw(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Lifetime stats\n");
+        s.push_str(&format!("Games played  {}\n", self.games));
+        s.push_str(&format!("Apples eaten  {}\n", self.apples));
+        s.push_str(&format!("Ticks played  {}\n", self.ticks));
+        if self.records.is_empty() {
+            return s;
+        }
+
+        s.push_str(&format!("\n{:<20} {:>5} {:>7} {:>6}\n", "Opponent", "Wins", "Losses", "Draws"));
+        for record in &self.records {
+            s.push_str(&format!("{:<20} {:>5} {:>7} {:>6}\n", record.name, record.wins, record.losses, record.draws));
+        }
+
+        s
+    }
+}
+
+pub fn show() -> Result<(), SnakeError> {
+    print!("{}", Lifetime::load().draw());
+    Ok(())
+}
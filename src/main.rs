@@ -1,56 +1,101 @@
-use std::{env::args, net::SocketAddrV4};
+use std::{env::args, process::exit};
 
-mod board;
-mod direction;
-mod game;
-mod packet;
-mod snake;
-mod util;
-
-use game::{GameMode, SnakeGame, SocketMode};
+use snake_game::{
+    cli::{
+        self, Command
+    },
+    config::GameConfig,
+    discovery,
+    error::SnakeError,
+    game::{
+        GameMode, SnakeGame, SocketMode
+    },
+    leaderboard, lifetime, menu,
+    net::Lobby,
+    relay, replay, signal, simulate, spectator, util
+};
 
 fn main() {
-    let usage = || {
-        println!("Usage: {{--accept <interface>:<port> | --connect <host>:<port>}}");
+    let mut args: Vec<String> = args().skip(1).collect();
+    if args.is_empty() {
+        args = match menu::choose() {
+            Some(args) => args,
+            None => {
+                return;
+            }
+        };
+    }
+
+    let command = match cli::parse(args) {
+        Ok(command) => command,
+        Err(topic) => {
+            eprintln!("{}", cli::usage(topic));
+            exit(2);
+        }
     };
 
-    let args: Vec<String> = args().collect();
-    let mode = match args.len() {
-        1 => GameMode::Singleplayer,
-        3 => {
-            match &args[1] as &str {
-                "--connect" => {
-                    let remote: SocketAddrV4 = match args[2].parse() {
-                        Ok(addr) => addr,
-                        Err(_) => {
-                            usage();
-                            return;
-                        }
-                    };
-                    GameMode::Multiplayer(SocketMode::Client(remote))
-                },
-                "--accept" => {
-                    let local: SocketAddrV4 = match args[2].parse() {
-                        Ok(addr) => addr,
-                        Err(_) => {
-                            usage();
-                            return;
-                        }
-                    };
-                    GameMode::Multiplayer(SocketMode::Server(local))
+    match command {
+        Command::Help(topic) => {
+            println!("{}", cli::usage(topic));
+        },
+        Command::Serve(port, secret, metrics_port) => {
+            check(relay::serve(port, secret, metrics_port));
+        },
+        Command::Discover => {
+            check(discovery::discover());
+        },
+        Command::List(remote, mut config, seed) => {
+            match discovery::pick(remote, &config) {
+                Ok(Some(room)) => {
+                    config.width = room.width;
+                    config.height = room.height;
+                    config.walls = room.walls;
+                    config.lobby = Some(Lobby::Join(room.code));
+                    check(play(GameMode::Multiplayer(SocketMode::Client(remote)), config, seed));
                 },
-                _ => {
-                    usage();
-                    return;
+                Ok(None) => {},
+                Err(error) => {
+                    check(Err(error));
                 }
             }
         },
-        _ => {
-            usage();
-            return;
+        Command::Replay(path, config) => {
+            signal::catch_interrupt();
+            check(replay::watch(&path, config));
+        },
+        Command::Simulate(config, seed, games) => {
+            check(simulate::simulate(config, seed.unwrap_or_else(util::time_seed), games));
+        },
+        Command::Stats => {
+            check(lifetime::show());
+        },
+        Command::Leaderboard(url) => {
+            check(leaderboard::show(&url));
+        },
+        Command::Spectate(remote, config) => {
+            check(spectator::spectate(remote, &config));
+        },
+        Command::Game(mode, config, seed) => {
+            check(play(mode, config, seed));
         }
-    };
+    }
+}
 
-    let mut game = SnakeGame::new(mode);
-    game.play();
+fn play(mode: GameMode, mut config: GameConfig, seed: Option<u64>) -> Result<(), SnakeError> {
+    let seed = seed.unwrap_or_else(util::time_seed);
+    util::seed(seed);
+    config.seed = seed;
+
+    signal::catch_interrupt();
+    SnakeGame::new(mode, config).and_then(|mut game| game.play())
+}
+
+fn check(result: Result<(), SnakeError>) {
+    match result {
+        Ok(_) => {},
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            exit(1);
+        }
+    }
 }
@@ -0,0 +1,265 @@
+use std::{
+    net::{
+        IpAddr, Ipv4Addr, SocketAddr, UdpSocket
+    },
+    sync::{
+        atomic::{
+            AtomicUsize, Ordering
+        },
+        Arc
+    },
+    thread::spawn,
+    time::Duration
+};
+
+use crate::{
+    discovery::Listing,
+    error::{
+        ProtocolError, SnakeError
+    }
+};
+
+pub const SERVICE_NAME: &str = "_snake._tcp.local";
+
+const MDNS_GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+const MDNS_PORT: u16 = 5353;
+const MDNS_TIMEOUT: Duration = Duration::from_millis(500);
+const RECORD_TTL: u32 = 120;
+const MAX_MESSAGE_SIZE: usize = 9000;
+const MAX_LABEL_LENGTH: usize = 63;
+const MAX_POINTERS: usize = 16;
+
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const TYPE_SRV: u16 = 33;
+const TYPE_ANY: u16 = 255;
+const CLASS_IN: u16 = 1;
+const CACHE_FLUSH: u16 = 0x8000;
+
+struct Service {
+    instance: String,
+    host: String,
+    address: Ipv4Addr,
+    listing: Listing
+}
+
+impl Service {
+    #[allow(clippy::identity_op)]
+    fn response(&self, ttl: u32) -> Vec<u8> {
+        let mut message = vec![0, 0, 0x84, 0x00, 0, 0, 0, 4, 0, 0, 0, 0];
+
+        record(&mut message, SERVICE_NAME, TYPE_PTR, CLASS_IN, ttl, &name(&self.instance));
+
+        let mut srv = vec![0, 0, 0, 0, (self.listing.port >> 8) as u8, (self.listing.port >> 0) as u8];
+        srv.extend_from_slice(&name(&self.host));
+        record(&mut message, &self.instance, TYPE_SRV, CLASS_IN | CACHE_FLUSH, ttl, &srv);
+
+        let mut txt = Vec::new();
+        for entry in [
+            format!("board={}x{}", self.listing.width, self.listing.height),
+            format!("walls={}", self.listing.walls as u8),
+            format!("players={}", self.listing.players)
+        ] {
+            txt.push(entry.len() as u8);
+            txt.extend_from_slice(entry.as_bytes());
+        }
+        record(&mut message, &self.instance, TYPE_TXT, CLASS_IN | CACHE_FLUSH, ttl, &txt);
+
+        record(&mut message, &self.host, TYPE_A, CLASS_IN | CACHE_FLUSH, ttl, &self.address.octets());
+        message
+    }
+
+    #[allow(clippy::identity_op)]
+    fn is_asked(&self, query: &[u8]) -> Result<bool, SnakeError> {
+        if query.len() < 12 || query[2] & 0x80 != 0 {
+            return Ok(false);
+        }
+
+        let questions = ((query[4] as usize) << 8) | ((query[5] as usize) << 0);
+        let mut offset = 12;
+        for _ in 0..questions {
+            let (asked, next) = read_name(query, offset)?;
+            if next + 4 > query.len() {
+                return Err(ProtocolError::Malformed("address").into());
+            }
+
+            let kind = ((query[next] as u16) << 8) | ((query[next + 1] as u16) << 0);
+            offset = next + 4;
+
+            let service = asked.eq_ignore_ascii_case(SERVICE_NAME) && (kind == TYPE_PTR || kind == TYPE_ANY);
+            let instance = asked.eq_ignore_ascii_case(&self.instance) && (kind == TYPE_SRV || kind == TYPE_TXT || kind == TYPE_ANY);
+            let host = asked.eq_ignore_ascii_case(&self.host) && (kind == TYPE_A || kind == TYPE_ANY);
+            if service || instance || host {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+#[allow(clippy::single_match)]
+pub fn advertise(listing: Listing, local: IpAddr, joined: Arc<AtomicUsize>) {
+    let address = match local {
+        IpAddr::V4(address) if !address.is_unspecified() => address,
+        _ => {
+            match outbound_address() {
+                Some(address) => address,
+                None => {
+                    eprintln!("mDNS advertisement disabled (no IPv4 address)");
+                    return;
+                }
+            }
+        }
+    };
+
+    let socket = match bind() {
+        Ok(socket) => socket,
+        Err(error) => {
+            eprintln!("mDNS advertisement disabled ({})", error);
+            return;
+        }
+    };
+
+    let label = label(&listing.name);
+    let service = Service {
+        instance: format!("{}.{}", label, SERVICE_NAME),
+        host: format!("{}.local", label),
+        address,
+        listing
+    };
+
+    spawn(move || {
+        let group = SocketAddr::from((MDNS_GROUP, MDNS_PORT));
+        let _ = socket.send_to(&service.response(RECORD_TTL), group);
+
+        while joined.load(Ordering::SeqCst) < service.listing.players {
+            let mut buffer = [0; MAX_MESSAGE_SIZE];
+            let (n, remote) = match socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(_) => {
+                    continue;
+                }
+            };
+
+            match service.is_asked(&buffer[..n]) {
+                Ok(true) => {
+                    let mut response = service.response(RECORD_TTL);
+                    if remote.port() == MDNS_PORT {
+                        let _ = socket.send_to(&response, group);
+                    } else {
+                        response[..2].copy_from_slice(&buffer[..2]);
+                        let _ = socket.send_to(&response, remote);
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        let _ = socket.send_to(&service.response(0), group);
+    });
+}
+
+fn bind() -> Result<UdpSocket, SnakeError> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, MDNS_PORT))?;
+    socket.join_multicast_v4(&MDNS_GROUP, &Ipv4Addr::UNSPECIFIED)?;
+    socket.set_read_timeout(Some(MDNS_TIMEOUT))?;
+    Ok(socket)
+}
+
+pub fn outbound_address() -> Option<Ipv4Addr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((MDNS_GROUP, MDNS_PORT)).ok()?;
+    match socket.local_addr().ok()?.ip() {
+        IpAddr::V4(address) if !address.is_unspecified() => Some(address),
+        _ => None
+    }
+}
+
+fn label(host_name: &str) -> String {
+    let label: String = host_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '-' })
+        .take(MAX_LABEL_LENGTH)
+        .collect();
+
+    if label.is_empty() {
+        "snake".into()
+    } else {
+        label
+    }
+}
+
+fn name(name: &str) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(name.len() + 2);
+    for label in name.split('.') {
+        encoded.push(label.len() as u8);
+        encoded.extend_from_slice(label.as_bytes());
+    }
+
+    encoded.push(0);
+    encoded
+}
+
+#[allow(clippy::identity_op)]
+fn record(message: &mut Vec<u8>, owner: &str, kind: u16, class: u16, ttl: u32, data: &[u8]) {
+    message.extend_from_slice(&name(owner));
+    message.push((kind >> 8) as u8);
+    message.push((kind >> 0) as u8);
+    message.push((class >> 8) as u8);
+    message.push((class >> 0) as u8);
+    message.extend_from_slice(&ttl.to_be_bytes());
+    message.push((data.len() >> 8) as u8);
+    message.push((data.len() >> 0) as u8);
+    message.extend_from_slice(data);
+}
+
+fn read_name(message: &[u8], offset: usize) -> Result<(String, usize), SnakeError> {
+    let mut labels = Vec::new();
+    let mut offset = offset;
+    let mut end = None;
+    let mut pointers = 0;
+    loop {
+        let length = match message.get(offset) {
+            Some(length) => *length as usize,
+            None => {
+                return Err(ProtocolError::Truncated.into());
+            }
+        };
+
+        if length == 0 {
+            break;
+        }
+
+        if length & 0xc0 == 0xc0 {
+            let low = match message.get(offset + 1) {
+                Some(low) => *low as usize,
+                None => {
+                    return Err(ProtocolError::Truncated.into());
+                }
+            };
+
+            pointers += 1;
+            if pointers > MAX_POINTERS {
+                return Err(ProtocolError::Malformed("name pointers").into());
+            }
+
+            end.get_or_insert(offset + 2);
+            offset = ((length & 0x3f) << 8) | low;
+            continue;
+        }
+
+        let label = match message.get(offset + 1..offset + 1 + length) {
+            Some(label) => label,
+            None => {
+                return Err(ProtocolError::Truncated.into());
+            }
+        };
+
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        offset += 1 + length;
+    }
+
+    Ok((labels.join("."), end.unwrap_or(offset + 1)))
+}
@@ -0,0 +1,247 @@
+use std::{
+    io::stdin,
+    net::SocketAddr
+};
+
+use crate::{
+    direction::Direction,
+    input::{
+        InputParser, Key
+    },
+    keymap::{
+        Action, Keymap, ACTIONS, KEYS_FILE
+    },
+    terminal::{
+        terminal, Terminal
+    }
+};
+
+const MAIN_ITEMS: [&str; 7] = ["Singleplayer", "Host a game", "Join a game", "Options", "Controls", "Watch a replay", "Quit"];
+const DIFFICULTIES: [&str; 3] = ["easy", "normal", "hard"];
+
+enum Choice {
+    Select(usize),
+    Quit
+}
+
+struct Settings {
+    difficulty: usize,
+    walls: bool,
+    color: bool,
+    emoji: bool
+}
+
+impl Settings {
+    fn items(&self) -> Vec<String> {
+        vec![
+            format!("Difficulty: {}", DIFFICULTIES[self.difficulty]),
+            format!("Walls: {}", if self.walls { "on" } else { "off" }),
+            format!("Colors: {}", if self.color { "on" } else { "off" }),
+            format!("Emoji: {}", if self.emoji { "on" } else { "off" }),
+            "Back".into()
+        ]
+    }
+
+    fn display_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if !self.color {
+            args.push("--no-color".into());
+        }
+
+        if self.emoji {
+            args.push("--emoji".into());
+        }
+
+        args
+    }
+
+    fn game_args(&self) -> Vec<String> {
+        let mut args = vec!["--difficulty".into(), DIFFICULTIES[self.difficulty].into()];
+        if self.walls {
+            args.push("--walls".into());
+        }
+
+        [args, self.display_args()].concat()
+    }
+}
+
+pub fn choose() -> Option<Vec<String>> {
+    let mut terminal = terminal();
+    let mut settings = Settings { difficulty: 1, walls: false, color: true, emoji: false };
+    let mut selected = 0;
+    let mut message = String::new();
+    let mut keymap = match Keymap::load() {
+        Ok(keymap) => keymap,
+        Err(error) => {
+            message = format!("Using the default controls, {}", error);
+            Keymap::default()
+        }
+    };
+
+    loop {
+        let items: Vec<String> = MAIN_ITEMS.iter().map(|item| item.to_string()).collect();
+        selected = match pick(terminal.as_mut(), "snake-game", &items, selected, &message, &keymap)? {
+            Choice::Select(selected) => selected,
+            Choice::Quit => {
+                return None;
+            }
+        };
+
+        message.clear();
+        let args: Vec<String> = match selected {
+            0 => [vec!["play".into()], settings.game_args()].concat(),
+            1 | 2 => {
+                let prompt = if selected == 1 { "Address to host at, e.g. 0.0.0.0:9999" } else { "Address to join, e.g. 192.168.1.2:9999" };
+                let address = ask(terminal.as_mut(), prompt)?;
+                if address.parse::<SocketAddr>().is_err() {
+                    message = format!("Not an address: {}", address);
+                    continue;
+                }
+
+                [vec![if selected == 1 { "host" } else { "join" }.into(), address], settings.game_args()].concat()
+            },
+            3 => {
+                configure(terminal.as_mut(), &mut settings, &keymap)?;
+                continue;
+            },
+            4 => {
+                controls(terminal.as_mut(), &mut keymap)?;
+                continue;
+            },
+            5 => {
+                let path = ask(terminal.as_mut(), "Replay file")?;
+                if path.is_empty() {
+                    continue;
+                }
+
+                [vec!["replay".into(), path], settings.display_args()].concat()
+            },
+            _ => {
+                return None;
+            }
+        };
+
+        terminal.show_cursor();
+        return Some(args);
+    }
+}
+
+fn configure(terminal: &mut dyn Terminal, settings: &mut Settings, keymap: &Keymap) -> Option<()> {
+    let mut selected = 0;
+    loop {
+        selected = match pick(terminal, "Options", &settings.items(), selected, "", keymap)? {
+            Choice::Select(selected) => selected,
+            Choice::Quit => {
+                return Some(());
+            }
+        };
+
+        match selected {
+            0 => settings.difficulty = (settings.difficulty + 1) % DIFFICULTIES.len(),
+            1 => settings.walls = !settings.walls,
+            2 => settings.color = !settings.color,
+            3 => settings.emoji = !settings.emoji,
+            _ => {
+                return Some(());
+            }
+        }
+    }
+}
+
+fn controls(terminal: &mut dyn Terminal, keymap: &mut Keymap) -> Option<()> {
+    let mut selected = 0;
+    let mut message = String::new();
+    loop {
+        let mut items: Vec<String> = ACTIONS.iter().map(|action| format!("{}: {}", action.name(), keymap.key(*action))).collect();
+        items.push("Reset to defaults".into());
+        items.push("Back".into());
+        selected = match pick(terminal, "Controls", &items, selected, &message, keymap)? {
+            Choice::Select(selected) => selected,
+            Choice::Quit => {
+                return Some(());
+            }
+        };
+
+        let bound = match ACTIONS.get(selected) {
+            Some(action) => {
+                let line = ask(terminal, &format!("Type the new key for {} and press Enter", action.name()))?;
+                let mut chars = line.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(key), None) => keymap.bind(*action, key),
+                    _ => Err("Type exactly one key".into())
+                }
+            },
+            None if selected == ACTIONS.len() => {
+                *keymap = Keymap::default();
+                Ok(())
+            },
+            None => {
+                return Some(());
+            }
+        };
+
+        message = match bound.and_then(|_| keymap.save().map_err(|error| format!("Could not save the controls ({})", error))) {
+            Ok(_) => format!("Saved to {}", KEYS_FILE),
+            Err(error) => error
+        };
+    }
+}
+
+fn pick(terminal: &mut dyn Terminal, title: &str, items: &[String], mut selected: usize, message: &str, keymap: &Keymap) -> Option<Choice> {
+    let mut parser = InputParser::new(keymap.clone());
+    loop {
+        let mut frame = format!("{}\n\n", title);
+        for (i, item) in items.iter().enumerate() {
+            frame.push_str(if i == selected { "> " } else { "  " });
+            frame.push_str(item);
+            frame.push('\n');
+        }
+
+        frame.push('\n');
+        if !message.is_empty() {
+            frame.push_str(message);
+            frame.push('\n');
+        }
+
+        frame.push_str(&format!(
+            "{}/{} or arrow keys + Enter to move, Enter to select, {} to leave\n",
+            keymap.key(Action::Up), keymap.key(Action::Down), keymap.key(Action::Quit)
+        ));
+        terminal.show_cursor();
+        terminal.draw(&frame);
+
+        let line = read_line()?;
+        if line.trim().is_empty() {
+            return Some(Choice::Select(selected));
+        }
+
+        for key in parser.parse(&line) {
+            match key {
+                Key::Direction(_, Direction::Up) => {
+                    selected = (selected + items.len() - 1) % items.len();
+                },
+                Key::Direction(_, Direction::Down) => {
+                    selected = (selected + 1) % items.len();
+                },
+                Key::Quit => {
+                    return Some(Choice::Quit);
+                },
+                _ => {}
+            }
+        }
+    }
+}
+
+fn ask(terminal: &mut dyn Terminal, prompt: &str) -> Option<String> {
+    terminal.show_cursor();
+    terminal.draw(&format!("{}:\n", prompt));
+    Some(read_line()?.trim().to_string())
+}
+
+fn read_line() -> Option<String> {
+    let mut line = String::new();
+    match stdin().read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line)
+    }
+}
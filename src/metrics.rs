@@ -0,0 +1,99 @@
+use std::{
+    net::{
+        Ipv4Addr, TcpListener
+    },
+    sync::{
+        atomic::{
+            AtomicU64, Ordering
+        },
+        Arc
+    },
+    thread::spawn
+};
+
+use crate::{
+    error::SnakeError,
+    web::{
+        read_request, respond
+    }
+};
+
+#[derive(Default)]
+pub struct Metrics {
+    games_active: AtomicU64,
+    games_waiting: AtomicU64,
+    games_total: AtomicU64,
+    packets_total: AtomicU64,
+    errors_total: AtomicU64
+}
+
+impl Metrics {
+    pub fn game_started(&self) {
+        self.games_active.fetch_add(1, Ordering::Relaxed);
+        self.games_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn game_ended(&self) {
+        self.games_active.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn set_waiting(&self, count: usize) {
+        self.games_waiting.store(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn packet_relayed(&self) {
+        self.packets_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn error(&self) {
+        self.errors_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn render(&self) -> String {
+        let active = self.games_active.load(Ordering::Relaxed);
+        let waiting = self.games_waiting.load(Ordering::Relaxed);
+        let metrics = [
+            ("snake_relay_games_active", "gauge", "Games currently being relayed", active),
+            ("snake_relay_games_waiting", "gauge", "Games waiting for their second player", waiting),
+            ("snake_relay_players_connected", "gauge", "Players connected to the relay", active * 2 + waiting),
+            ("snake_relay_games_total", "counter", "Games started since the relay came up", self.games_total.load(Ordering::Relaxed)),
+            ("snake_relay_packets_total", "counter", "Packets forwarded between players", self.packets_total.load(Ordering::Relaxed)),
+            ("snake_relay_errors_total", "counter", "Failed joins and games that ended with an error", self.errors_total.load(Ordering::Relaxed))
+        ];
+
+        let mut text = String::new();
+        for (name, kind, help, value) in metrics {
+            text.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n{} {}\n", name, help, name, kind, name, value));
+        }
+
+        text
+    }
+}
+
+pub fn serve(port: u16, metrics: Arc<Metrics>) -> Result<(), SnakeError> {
+    let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port))?;
+    println!("Serving metrics at {}/metrics", listener.local_addr()?);
+
+    spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => {
+                    continue;
+                }
+            };
+
+            match read_request(&mut stream) {
+                Some((method, path)) if method == "GET" && path == "/metrics" => {
+                    respond(&mut stream, "200 OK", "text/plain; version=0.0.4", &metrics.render());
+                },
+                Some(_) => {
+                    respond(&mut stream, "404 Not Found", "text/plain", "not found\n");
+                },
+                None => {}
+            }
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,1555 @@
+use std::{
+    collections::VecDeque,
+    io::{
+        ErrorKind, Read, Write
+    },
+    mem::replace,
+    net::{
+        IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream
+    },
+    sync::{
+        atomic::{
+            AtomicUsize, Ordering
+        },
+        mpsc::{
+            channel, sync_channel, Receiver, RecvTimeoutError, Sender, SyncSender, TryRecvError, TrySendError
+        },
+        Arc
+    },
+    thread::{
+        sleep, spawn, JoinHandle
+    },
+    time::{
+        Duration, Instant
+    }
+};
+
+use crate::{
+    config::GameConfig,
+    discovery::{
+        advertise, Listing
+    },
+    error::{
+        ProtocolError, SnakeError
+    },
+    mdns,
+    packet::{
+        Opcode, Packet, PacketReader, PROTOCOL_ID
+    },
+    punch::{
+        connect_from, listen_from, SUPPORTED
+    },
+    qr::QrCode,
+    sync::{
+        Lockstep, SyncStrategy
+    },
+    token::JoinToken,
+    util::{
+        host_name, is_private, random_bytes, sha1
+    },
+    websocket::WebSocket
+};
+
+pub const HOST_ID: usize = 0;
+pub const MAX_PLAYERS: usize = 4;
+pub const MAX_NAME_LENGTH: usize = 16;
+pub const REJECT_CODE: u8 = 1;
+pub const REJECT_SECRET: u8 = 2;
+pub const REJECT_PASSWORD: u8 = 3;
+pub const REJECT_BANNED: u8 = 4;
+pub const REJECT_SETTINGS: u8 = 5;
+pub const FEATURE_CHAT: u32 = 1;
+pub const FEATURE_SNAPSHOTS: u32 = 2;
+pub const FEATURE_BATCH: u32 = 4;
+pub const FEATURE_KICK: u32 = 8;
+pub const FEATURE_GAME_OVER: u32 = 16;
+pub const FEATURE_PAUSE_REQUEST: u32 = 32;
+pub const FEATURE_SETTINGS: u32 = 64;
+pub const FEATURES: u32 = FEATURE_CHAT | FEATURE_SNAPSHOTS | FEATURE_BATCH | FEATURE_KICK | FEATURE_GAME_OVER | FEATURE_PAUSE_REQUEST | FEATURE_SETTINGS;
+
+const WELCOME_SIZE: usize = 13;
+const DIGEST_SIZE: usize = 20;
+pub const LEGACY_FEATURES: u32 = FEATURE_CHAT | FEATURE_SNAPSHOTS | FEATURE_BATCH | FEATURE_KICK;
+const SPECTATOR_TIMEOUT: Duration = Duration::from_millis(50);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+const NONCE_SIZE: usize = 16;
+const HOST_PROOF: u8 = 1;
+const CLIENT_PROOF: u8 = 2;
+pub const POLL_TIMEOUT: Duration = Duration::from_millis(100);
+const PING_INTERVAL: Duration = Duration::from_secs(1);
+pub const PEER_TIMEOUT: Duration = Duration::from_secs(5);
+const READ_CHUNK_SIZE: usize = 4096;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const PUNCH_TIMEOUT: Duration = Duration::from_secs(3);
+const PUNCH_ATTEMPT_TIMEOUT: Duration = Duration::from_millis(500);
+const PUNCH_RETRY: Duration = Duration::from_millis(100);
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(50);
+const LINK_INTERVAL: Duration = Duration::from_millis(5);
+const LINK_QUEUE_SIZE: usize = 256;
+const READY_INTERVAL: Duration = Duration::from_millis(20);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Member {
+    Player(usize),
+    Spectator(usize)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Lobby {
+    Register,
+    Join(String)
+}
+
+pub trait Connection: Read + Write + Send {}
+
+impl<T: Read + Write + Send> Connection for T {}
+
+pub struct Channel {
+    socket: Box<dyn Connection>,
+    buffer: Vec<u8>,
+    batched: VecDeque<Packet>,
+    sent: u32,
+    received: u32
+}
+
+impl Channel {
+    pub fn new(socket: Box<dyn Connection>) -> Self {
+        Channel { socket, buffer: Vec::new(), batched: VecDeque::new(), sent: 0, received: 0 }
+    }
+
+    pub fn send(&mut self, packet: &Packet) -> Result<(), SnakeError> {
+        packet.send(&mut self.socket, self.sent)?;
+        self.sent = self.sent.wrapping_add(1);
+        Ok(())
+    }
+
+    #[allow(clippy::single_match)]
+    pub fn recv(&mut self) -> Result<Option<Packet>, SnakeError> {
+        match self.batched.pop_front() {
+            Some(packet) => {
+                return Ok(Some(packet));
+            },
+            None => {}
+        }
+
+        let size = loop {
+            match Packet::frame_size(&self.buffer) {
+                Some(size) => {
+                    break size;
+                },
+                None => {}
+            }
+
+            let mut chunk = [0; READ_CHUNK_SIZE];
+            match self.socket.read(&mut chunk) {
+                Ok(0) => {
+                    return Err(SnakeError::Disconnected);
+                },
+                Ok(n) => {
+                    self.buffer.extend_from_slice(&chunk[..n]);
+                },
+                Err(error) => {
+                    if error.kind() != ErrorKind::WouldBlock && error.kind() != ErrorKind::TimedOut {
+                        return Err(SnakeError::Io(error));
+                    }
+
+                    return Ok(None);
+                }
+            }
+        };
+
+        let packet = Packet::decode(&self.buffer[..size])?;
+        self.buffer.drain(..size);
+
+        let sequence = packet.sequence();
+        if sequence != self.received {
+            if (self.received.wrapping_sub(sequence) as i32) > 0 {
+                return Ok(None);
+            }
+
+            return Err(SnakeError::SequenceGap(self.received, sequence));
+        }
+
+        self.received = self.received.wrapping_add(1);
+        if packet.opcode() == Opcode::Batch {
+            self.batched.extend(packet.unbatch()?);
+            return Ok(self.batched.pop_front());
+        }
+
+        Ok(Some(packet))
+    }
+}
+
+pub struct Link {
+    outbox: Option<SyncSender<Packet>>,
+    inbox: Receiver<Result<Packet, SnakeError>>,
+    thread: Option<JoinHandle<()>>
+}
+
+impl Link {
+    pub fn spawn(mut socket: Channel, stream: &TcpStream) -> Result<Self, SnakeError> {
+        stream.set_read_timeout(Some(LINK_INTERVAL))?;
+        if stream.write_timeout()?.is_none() {
+            stream.set_write_timeout(Some(PEER_TIMEOUT))?;
+        }
+
+        let (outbox, outgoing) = sync_channel::<Packet>(LINK_QUEUE_SIZE);
+        let (incoming, inbox): (Sender<Result<Packet, SnakeError>>, _) = channel();
+        let thread = spawn(move || {
+            loop {
+                loop {
+                    match outgoing.try_recv() {
+                        Ok(packet) => {
+                            if socket.send(&packet).is_err() {
+                                let _ = incoming.send(Err(SnakeError::ConnectionLost));
+                                return;
+                            }
+                        },
+                        Err(TryRecvError::Empty) => {
+                            break;
+                        },
+                        Err(TryRecvError::Disconnected) => {
+                            return;
+                        }
+                    }
+                }
+
+                match socket.recv() {
+                    Ok(Some(packet)) => {
+                        if incoming.send(Ok(packet)).is_err() {
+                            return;
+                        }
+                    },
+                    Ok(None) => {},
+                    Err(error) => {
+                        let _ = incoming.send(Err(error));
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(Link { outbox: Some(outbox), inbox, thread: Some(thread) })
+    }
+
+    pub fn send(&self, packet: &Packet) -> Result<(), SnakeError> {
+        let outbox = match &self.outbox {
+            Some(outbox) => outbox,
+            None => {
+                return Err(SnakeError::Disconnected);
+            }
+        };
+
+        match outbox.try_send(packet.clone()) {
+            Ok(()) | Err(TrySendError::Disconnected(_)) => Ok(()),
+            Err(TrySendError::Full(_)) => Err(SnakeError::ConnectionLost)
+        }
+    }
+
+    pub fn recv(&self, timeout: Duration) -> Result<Option<Packet>, SnakeError> {
+        match self.inbox.recv_timeout(timeout) {
+            Ok(result) => result.map(Some),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(SnakeError::Disconnected)
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        match &self.thread {
+            Some(thread) => thread.is_finished(),
+            None => true
+        }
+    }
+}
+
+impl Drop for Link {
+    #[allow(clippy::single_match)]
+    fn drop(&mut self) {
+        self.outbox = None;
+        match self.thread.take() {
+            Some(thread) => {
+                let _ = thread.join();
+            },
+            None => {}
+        }
+    }
+}
+
+pub struct JoinRequest<'a> {
+    pub code: String,
+    pub pace: Duration,
+    pub features: Option<u32>,
+    pub settings: Option<u64>,
+    pub nonce: &'a [u8]
+}
+
+struct Peer {
+    id: usize,
+    address: IpAddr,
+    socket: Link,
+    pending: VecDeque<Packet>,
+    heard: Instant,
+    rtt: Option<Duration>
+}
+
+pub struct Network {
+    id: usize,
+    count: usize,
+    peers: Vec<Peer>,
+    listener: Option<TcpListener>,
+    spectators: Vec<(Link, IpAddr)>,
+    banned: Vec<IpAddr>,
+    held: Option<Vec<Packet>>,
+    features: u32,
+    strategy: Box<dyn SyncStrategy>,
+    authoritative: bool,
+    pace: Duration,
+    secret: Option<String>,
+    password: Option<String>,
+    epoch: Instant,
+    pinged: Instant,
+    rtt_total: Duration,
+    rtt_samples: u32,
+    desync: Option<u64>,
+    malformed: Option<(usize, ProtocolError)>,
+    names: Vec<String>,
+    seed: u64
+}
+
+impl Network {
+    #[allow(clippy::single_match)]
+    pub fn host(local: SocketAddr, config: &GameConfig, mut listing: Listing, settings: u64, cancelled: &dyn Fn() -> bool) -> Result<Self, SnakeError> {
+        if !config.allow_public && !is_private(local.ip()) {
+            return Err(SnakeError::NotPrivateAddress);
+        }
+
+        let server = TcpListener::bind(local)?;
+        let local = server.local_addr()?;
+        println!("Accepting connections at {} (type q and press Enter to stop)", local);
+        show_token(local, config, &listing);
+
+        let joined = Arc::new(AtomicUsize::new(1));
+        listing.port = local.port();
+        advertise(listing.clone(), joined.clone());
+        mdns::advertise(listing, local.ip(), joined.clone());
+
+        let mut peers = Vec::new();
+        let mut spectators = Vec::new();
+        let mut features = FEATURES;
+        let mut legacy = Vec::new();
+        let count = config.players;
+        let started = Instant::now();
+        server.set_nonblocking(true)?;
+        while peers.len() + 1 < count {
+            if cancelled() {
+                return Err(SnakeError::Cancelled);
+            }
+
+            match config.timeout {
+                Some(timeout) if started.elapsed() >= timeout => {
+                    return Err(SnakeError::TimedOut(format!("{} of {} players joined in {}s", peers.len() + 1, count, timeout.as_secs())));
+                },
+                _ => {}
+            }
+
+            let (stream, remote) = match server.accept() {
+                Ok(connection) => connection,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                    sleep(ACCEPT_INTERVAL);
+                    continue;
+                },
+                Err(error) => {
+                    return Err(SnakeError::Io(error));
+                }
+            };
+
+            let raw = match stream.set_nonblocking(false).and_then(|_| stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))).and_then(|_| stream.try_clone()) {
+                Ok(raw) => raw,
+                Err(_) => {
+                    continue;
+                }
+            };
+
+            let mut socket = match accept(stream) {
+                Ok(socket) => Channel::new(socket),
+                Err(_) => {
+                    continue;
+                }
+            };
+
+            let packet = match socket.recv() {
+                Ok(Some(packet)) => packet,
+                _ => {
+                    continue;
+                }
+            };
+
+            let request = match packet.opcode() {
+                Opcode::Join | Opcode::Spectate => admit(&mut socket, &packet, config, settings),
+                _ => {
+                    continue;
+                }
+            };
+
+            let offered = match request {
+                Ok(Ok(offered)) => offered,
+                Ok(Err(reason)) => {
+                    match reject_packet(reason) {
+                        Ok(reject) => {
+                            let _ = socket.send(&reject);
+                        },
+                        Err(_) => {}
+                    }
+
+                    let why = match reason {
+                        REJECT_SECRET => "wrong secret",
+                        REJECT_PASSWORD => "wrong password",
+                        _ => "different game settings"
+                    };
+
+                    println!("Rejected {} ({})", remote, why);
+                    continue;
+                },
+                Err(_) => {
+                    continue;
+                }
+            };
+
+            match packet.opcode() {
+                Opcode::Join => {
+                    let socket = match Link::spawn(socket, &raw) {
+                        Ok(socket) => socket,
+                        Err(_) => {
+                            continue;
+                        }
+                    };
+
+                    let id = peers.len() + 1;
+                    match offered {
+                        Some(offered) => {
+                            features &= offered;
+                        },
+                        None => {
+                            features &= LEGACY_FEATURES;
+                            legacy.push(id);
+                        }
+                    }
+
+                    peers.push(Peer { id, address: remote.ip(), socket, pending: VecDeque::new(), heard: Instant::now(), rtt: None });
+                    joined.store(peers.len() + 1, Ordering::SeqCst);
+                    println!("Player {} joined ({} of {})", id + 1, peers.len() + 1, count);
+                },
+                _ => {
+                    match raw.set_write_timeout(Some(SPECTATOR_TIMEOUT)).map_err(SnakeError::from).and_then(|_| Link::spawn(socket, &raw)) {
+                        Ok(socket) => {
+                            spectators.push((socket, remote.ip()));
+                            println!("Spectator joined ({} watching)", spectators.len());
+                        },
+                        Err(_) => {}
+                    }
+                }
+            }
+        }
+
+        show_features(features);
+        let authoritative = config.authoritative && features & FEATURE_SNAPSHOTS != 0;
+        if config.authoritative && !authoritative {
+            println!("Running the game on every instance instead of authoritatively");
+        }
+
+        for peer in &peers {
+            let offered = if legacy.contains(&peer.id) { None } else { Some(features) };
+            peer.socket.send(&welcome_packet(peer.id, count, authoritative, config.pace, config.seed, offered)?)?;
+        }
+
+        let secret = config.secret.clone();
+        let password = config.password.clone();
+        let mut network = Network { id: HOST_ID, count, peers, listener: Some(server), spectators, banned: Vec::new(), held: None, features, strategy: strategy(config, authoritative), authoritative, pace: config.pace, secret, password, epoch: Instant::now(), pinged: Instant::now(), rtt_total: Duration::ZERO, rtt_samples: 0, desync: None, malformed: None, names: Vec::new(), seed: config.seed };
+        network.exchange_names(&config.name)?;
+        Ok(network)
+    }
+
+    #[allow(clippy::identity_op, clippy::single_match)]
+    pub fn connect(remote: SocketAddr, config: &GameConfig, settings: u64, cancelled: &dyn Fn() -> bool) -> Result<Self, SnakeError> {
+        let direct = config.direct && SUPPORTED;
+        let (mut stream, mut socket) = open(remote, config)?;
+        match &config.lobby {
+            Some(Lobby::Register) => {
+                let listing = Listing {
+                    port: 0,
+                    width: config.width,
+                    height: config.height,
+                    walls: config.walls,
+                    pace: config.pace.as_millis() as u64,
+                    players: 2,
+                    joined: 1,
+                    code: String::new(),
+                    name: config.name.clone().unwrap_or_else(host_name)
+                };
+
+                socket.send(&listing.encode()?)?;
+            },
+            _ => {}
+        }
+
+        let nonce = secret_nonce(&config.secret);
+        let packet = match &config.lobby {
+            Some(Lobby::Register) => join_packet(Opcode::Register, "", config.pace, settings, &nonce)?,
+            Some(Lobby::Join(code)) => join_packet(Opcode::Join, code, config.pace, settings, &nonce)?,
+            None => join_packet(Opcode::Join, "", config.pace, settings, &nonce)?
+        };
+        socket.send(&packet)?;
+
+        println!("Waiting for the other players (type q and press Enter to stop)");
+        stream.set_read_timeout(Some(POLL_TIMEOUT))?;
+        let started = Instant::now();
+        let mut proven = false;
+        loop {
+            if cancelled() {
+                return Err(SnakeError::Cancelled);
+            }
+
+            match config.timeout {
+                Some(timeout) if started.elapsed() >= timeout => {
+                    return Err(SnakeError::TimedOut(format!("the game did not start in {}s", timeout.as_secs())));
+                },
+                _ => {}
+            }
+
+            match socket.recv()? {
+                Some(packet) if packet.opcode() == Opcode::Code => {
+                    println!("Lobby code: {}", String::from_utf8_lossy(packet.data()));
+                },
+                Some(packet) if packet.opcode() == Opcode::AuthChallenge => {
+                    socket.send(&auth_response(&packet, &config.password)?)?;
+                },
+                Some(packet) if packet.opcode() == Opcode::SecretChallenge => {
+                    socket.send(&secret_response(&packet, &nonce, &config.secret)?)?;
+                    proven = true;
+                },
+                Some(packet) if packet.opcode() == Opcode::Goodbye => {
+                    return Err(rejection(&packet));
+                },
+                Some(packet) if packet.opcode() == Opcode::Welcome => {
+                    let data = packet.data();
+                    if data.len() < WELCOME_SIZE {
+                        return Err(ProtocolError::Truncated.into());
+                    }
+
+                    if config.secret.is_some() && !proven {
+                        return Err(SnakeError::BadSecret);
+                    }
+
+                    let (features, _) = read_features(&data[WELCOME_SIZE..])?;
+
+                    let id = data[0] as usize;
+                    let count = data[1] as usize;
+                    let authoritative = data[2] != 0;
+                    let pace = Duration::from_millis(((data[3] as u64) << 8) | ((data[4] as u64) << 0));
+                    let seed = PacketReader::new(&data[5..WELCOME_SIZE]).read_u64()?;
+                    if pace.is_zero() || id >= count || count > MAX_PLAYERS || (id == HOST_ID && count != 2) {
+                        return Err(ProtocolError::Malformed("welcome").into());
+                    }
+
+                    let features = features.unwrap_or(LEGACY_FEATURES) & FEATURES;
+                    show_features(features);
+
+                    let mut pending = VecDeque::new();
+                    if direct {
+                        match rendezvous(&mut socket, stream.local_addr()?.port(), id == HOST_ID, &mut pending)? {
+                            Some(punched) => {
+                                punched.set_read_timeout(Some(POLL_TIMEOUT))?;
+                                stream = punched.try_clone()?;
+                                socket = Channel::new(Box::new(punched));
+                                println!("Connected directly to {}", stream.peer_addr()?);
+                            },
+                            None => {
+                                println!("Direct connection failed, playing through the relay");
+                            }
+                        }
+                    }
+
+                    let peer = if id == HOST_ID { HOST_ID + 1 } else { HOST_ID };
+                    let peers = vec![Peer { id: peer, address: stream.peer_addr()?.ip(), socket: Link::spawn(socket, &stream)?, pending, heard: Instant::now(), rtt: None }];
+                    let mut network = Network { id, count, peers, listener: None, spectators: Vec::new(), banned: Vec::new(), held: None, features, strategy: strategy(config, authoritative), authoritative, pace, secret: None, password: None, epoch: Instant::now(), pinged: Instant::now(), rtt_total: Duration::ZERO, rtt_samples: 0, desync: None, malformed: None, names: Vec::new(), seed };
+                    network.exchange_names(&config.name)?;
+                    return Ok(network);
+                },
+                _ => {}
+            }
+        }
+    }
+
+    pub fn rooms(remote: SocketAddr, config: &GameConfig) -> Result<Vec<Listing>, SnakeError> {
+        let (stream, mut socket) = open(remote, config)?;
+        let nonce = secret_nonce(&config.secret);
+        socket.send(&join_packet(Opcode::Discover, "", config.pace, 0, &nonce)?)?;
+        stream.set_read_timeout(Some(POLL_TIMEOUT))?;
+
+        let timeout = config.timeout.unwrap_or(CONNECT_TIMEOUT);
+        let started = Instant::now();
+        let mut rooms = Vec::new();
+        let mut proven = config.secret.is_none();
+        while started.elapsed() < timeout {
+            match socket.recv() {
+                Ok(Some(packet)) if packet.opcode() == Opcode::SecretChallenge => {
+                    socket.send(&secret_response(&packet, &nonce, &config.secret)?)?;
+                    proven = true;
+                },
+                Ok(Some(packet)) if packet.opcode() == Opcode::Announce => {
+                    if !proven {
+                        return Err(SnakeError::BadSecret);
+                    }
+
+                    rooms.push(Listing::decode(&packet)?);
+                },
+                Ok(Some(packet)) if packet.opcode() == Opcode::Goodbye => {
+                    return Err(rejection(&packet));
+                },
+                Ok(_) => {},
+                Err(SnakeError::Disconnected) => {
+                    return Ok(rooms);
+                },
+                Err(error) => {
+                    return Err(error);
+                }
+            }
+        }
+
+        Err(SnakeError::TimedOut(format!("no room list from {} in {}s", remote, timeout.as_secs())))
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.id == HOST_ID
+    }
+
+    pub fn is_authoritative(&self) -> bool {
+        self.authoritative
+    }
+
+    pub fn has(&self, feature: u32) -> bool {
+        self.features & feature != 0
+    }
+
+    pub fn pace(&self) -> Duration {
+        self.pace
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn names(&self) -> &Vec<String> {
+        &self.names
+    }
+
+    pub fn rtt(&self) -> Option<Duration> {
+        self.peers.iter().filter_map(|peer| peer.rtt).max()
+    }
+
+    pub fn average_rtt(&self) -> Option<Duration> {
+        if self.rtt_samples == 0 {
+            return None;
+        }
+
+        Some(self.rtt_total / self.rtt_samples)
+    }
+
+    pub fn send(&mut self, packet: &Packet) -> Result<(), SnakeError> {
+        match &mut self.held {
+            Some(held) => {
+                held.push(packet.clone());
+                Ok(())
+            },
+            None => self.deliver(packet)
+        }
+    }
+
+    pub fn send_to(&mut self, id: usize, packet: &Packet) -> Result<(), SnakeError> {
+        match self.peers.iter().find(|peer| peer.id == id) {
+            Some(peer) => peer.socket.send(packet),
+            None => Ok(())
+        }
+    }
+
+    pub fn hold(&mut self) {
+        if self.held.is_none() {
+            self.held = Some(Vec::new());
+        }
+    }
+
+    pub fn flush(&mut self) -> Result<(), SnakeError> {
+        let packets = match self.held.take() {
+            Some(packets) => packets,
+            None => {
+                return Ok(());
+            }
+        };
+
+        match packets.len() {
+            0 => Ok(()),
+            1 => self.deliver(&packets[0]),
+            _ => {
+                match Packet::batch(&packets) {
+                    Ok(batch) if self.has(FEATURE_BATCH) => self.deliver(&batch),
+                    _ => {
+                        for packet in &packets {
+                            self.deliver(packet)?;
+                        }
+
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+
+    fn deliver(&mut self, packet: &Packet) -> Result<(), SnakeError> {
+        for peer in &mut self.peers {
+            peer.socket.send(packet)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        if self.is_host() {
+            self.peers.len() + 1 == self.count
+        } else {
+            self.peers.len() == 1
+        }
+    }
+
+    pub fn synchronize(&mut self, tick_id: u64, hash: u64, stalled: &mut dyn FnMut(Duration)) -> Result<Vec<Packet>, SnakeError> {
+        self.ping()?;
+        if self.authoritative && self.is_host() {
+            return self.poll();
+        }
+
+        let mut strategy = replace(&mut self.strategy, Box::new(Lockstep));
+        let packets = strategy.synchronize(self, tick_id, hash, stalled);
+        self.strategy = strategy;
+        packets
+    }
+
+    pub fn release(&mut self, tick_id: u64, hash: u64) -> Result<(), SnakeError> {
+        let mut strategy = replace(&mut self.strategy, Box::new(Lockstep));
+        let released = strategy.release(self, tick_id, hash);
+        self.strategy = strategy;
+        released
+    }
+
+    pub fn peer_count(&self) -> usize {
+        self.peers.len()
+    }
+
+    pub fn silence(&self, i: usize) -> Duration {
+        self.peers[i].heard.elapsed()
+    }
+
+    pub fn drop_peer(&mut self, i: usize) {
+        self.peers.remove(i);
+    }
+
+    pub fn report_desync(&mut self, tick_id: u64) {
+        self.desync = Some(tick_id);
+    }
+
+    pub fn take_desync(&mut self) -> Option<u64> {
+        self.desync.take()
+    }
+
+    pub fn take_malformed(&mut self) -> Option<(usize, ProtocolError)> {
+        self.malformed.take()
+    }
+
+    fn exchange_names(&mut self, name: &Option<String>) -> Result<(), SnakeError> {
+        let name = name.clone().unwrap_or_default();
+        if !self.is_host() {
+            self.send(&names_packet(&[name])?)?;
+            let names = read_names(&self.await_names(0)?)?;
+            if names.len() != self.count {
+                return Err(ProtocolError::Malformed("player names").into());
+            }
+
+            self.names = names;
+            return Ok(());
+        }
+
+        let mut names = vec![String::new(); self.count];
+        names[HOST_ID] = name;
+        for i in 0..self.peers.len() {
+            let packet = self.await_names(i)?;
+            let id = self.peers[i].id;
+            names[id] = read_names(&packet)?.into_iter().next().unwrap_or_default();
+        }
+
+        for (id, name) in names.iter_mut().enumerate() {
+            if name.is_empty() {
+                *name = format!("P{}", id + 1);
+            }
+        }
+
+        self.send(&names_packet(&names)?)?;
+        self.names = names;
+        Ok(())
+    }
+
+    pub fn ready_check(&mut self, input: &dyn Fn() -> Option<bool>) -> Result<(), SnakeError> {
+        println!("Everyone joined, type y and press Enter when you're ready (q to leave)");
+        let mut ready = false;
+        let mut peers = vec![false; self.peers.len()];
+        loop {
+            match input() {
+                Some(true) if !ready => {
+                    ready = true;
+                    if !self.is_host() {
+                        self.send(&ready_packet(self.id)?)?;
+                    }
+
+                    println!("Waiting for the others to be ready");
+                },
+                Some(false) => {
+                    let _ = self.send(&goodbye_packet(self.id)?);
+                    return Err(SnakeError::Cancelled);
+                },
+                _ => {}
+            }
+
+            for i in 0..self.peers.len() {
+                match self.peers[i].socket.recv(READY_INTERVAL)? {
+                    Some(packet) if packet.opcode() == Opcode::Ready => {
+                        if self.is_host() && !peers[i] {
+                            println!("Player {} is ready", self.peers[i].id + 1);
+                        }
+
+                        peers[i] = true;
+                    },
+                    Some(packet) if packet.opcode() == Opcode::Goodbye => {
+                        println!("Player {} left", self.peers[i].id + 1);
+                        return Err(SnakeError::Disconnected);
+                    },
+                    Some(packet) => {
+                        self.peers[i].pending.push_back(packet);
+                    },
+                    None => {}
+                }
+            }
+
+            if ready && !peers.contains(&false) {
+                if self.is_host() {
+                    self.send(&ready_packet(self.id)?)?;
+                }
+
+                return Ok(());
+            }
+        }
+    }
+
+    fn await_names(&mut self, i: usize) -> Result<Packet, SnakeError> {
+        let started = Instant::now();
+        while started.elapsed() < HANDSHAKE_TIMEOUT {
+            match self.peers[i].socket.recv(POLL_TIMEOUT)? {
+                Some(packet) if packet.opcode() == Opcode::Names => {
+                    return Ok(packet);
+                },
+                Some(packet) => {
+                    self.peers[i].pending.push_back(packet);
+                },
+                None => {}
+            }
+        }
+
+        Err(SnakeError::ConnectionLost)
+    }
+
+    pub fn ping(&mut self) -> Result<(), SnakeError> {
+        if self.pinged.elapsed() < PING_INTERVAL {
+            return Ok(());
+        }
+
+        self.pinged = Instant::now();
+        self.deliver(&id_packet(Opcode::Ping, self.epoch.elapsed().as_micros() as u64)?)
+    }
+
+    #[allow(clippy::single_match)]
+    pub fn poll(&mut self) -> Result<Vec<Packet>, SnakeError> {
+        let mut packets = Vec::new();
+        let mut i = 0;
+        while i < self.peers.len() {
+            let count = self.peers.len();
+            match self.receive(i, Duration::ZERO)? {
+                Some(packet) => {
+                    match packet.opcode() {
+                        Opcode::Ping | Opcode::Pong => {},
+                        Opcode::Sync => {
+                            self.peers[i].pending.push_back(packet);
+                        },
+                        _ => {
+                            packets.push(packet);
+                        }
+                    }
+                },
+                None => {}
+            }
+
+            if self.peers.len() == count {
+                i += 1;
+            }
+        }
+
+        Ok(packets)
+    }
+
+    #[allow(clippy::single_match)]
+    pub fn receive(&mut self, i: usize, wait: Duration) -> Result<Option<Packet>, SnakeError> {
+        let sender = self.peers[i].id;
+        match self.peers[i].pending.pop_front() {
+            Some(packet) => {
+                return Ok(Some(packet));
+            },
+            None => {}
+        }
+
+        let packet = match self.peers[i].socket.recv(wait) {
+            Ok(Some(packet)) => packet,
+            Ok(None) => {
+                return Ok(None);
+            },
+            Err(SnakeError::Disconnected) => goodbye_packet(sender)?,
+            Err(SnakeError::Protocol(error)) => {
+                self.malformed = Some((sender, error));
+                goodbye_packet(sender)?
+            },
+            Err(error) => {
+                return Err(error);
+            }
+        };
+
+        self.peers[i].heard = Instant::now();
+        match packet.opcode() {
+            Opcode::Sync => {
+                return Ok(Some(packet));
+            },
+            Opcode::Ping => {
+                let mut pong = Packet::new(Opcode::Pong, packet.data().len());
+                pong.push_data(packet.data())?;
+                self.peers[i].socket.send(&pong)?;
+                return Ok(Some(packet));
+            },
+            Opcode::Pong => {
+                let now = self.epoch.elapsed().as_micros() as u64;
+                match PacketReader::new(packet.data()).read_u64() {
+                    Ok(sent) if sent <= now => {
+                        let rtt = Duration::from_micros(now - sent);
+                        self.peers[i].rtt = Some(rtt);
+                        self.rtt_total += rtt;
+                        self.rtt_samples += 1;
+                    },
+                    _ => {}
+                }
+
+                return Ok(Some(packet));
+            },
+            _ => {}
+        }
+
+        if packet.opcode() == Opcode::Goodbye && packet.data().first() == Some(&(sender as u8)) {
+            self.peers.remove(i);
+        }
+
+        if self.is_host() {
+            for peer in &mut self.peers {
+                if peer.id != sender {
+                    peer.socket.send(&packet)?;
+                }
+            }
+        }
+
+        Ok(Some(packet))
+    }
+
+    #[allow(clippy::single_match)]
+    pub fn broadcast_snapshot(&mut self, packet: &dyn Fn() -> Result<Packet, SnakeError>) -> Result<(), SnakeError> {
+        let listener = match &self.listener {
+            Some(listener) => listener,
+            None => {
+                return Ok(());
+            }
+        };
+
+        loop {
+            match listener.accept() {
+                Ok((stream, remote)) => {
+                    match spectator(stream, &self.secret, &self.password, self.banned.contains(&remote.ip())) {
+                        Ok(Some(socket)) => {
+                            self.spectators.push((socket, remote.ip()));
+                        },
+                        _ => {}
+                    }
+                },
+                Err(error) => {
+                    if error.kind() != ErrorKind::WouldBlock {
+                        return Err(SnakeError::Io(error));
+                    }
+
+                    break;
+                }
+            }
+        }
+
+        if self.spectators.is_empty() {
+            return Ok(());
+        }
+
+        let packet = packet()?;
+        self.spectators.retain(|(spectator, _)| spectator.send(&packet).is_ok() && !spectator.is_closed());
+        Ok(())
+    }
+
+    pub fn kick(&mut self, member: Member, ban: bool) -> Result<bool, SnakeError> {
+        if !self.is_host() {
+            return Ok(false);
+        }
+
+        let address = match member {
+            Member::Player(id) => {
+                let i = match self.peers.iter().position(|peer| peer.id == id) {
+                    Some(i) => i,
+                    None => {
+                        return Ok(false);
+                    }
+                };
+
+                let peer = self.peers.remove(i);
+                if self.has(FEATURE_KICK) {
+                    let _ = peer.socket.send(&kick_packet(ban)?);
+                }
+
+                peer.address
+            },
+            Member::Spectator(i) if i < self.spectators.len() => {
+                let (spectator, address) = self.spectators.remove(i);
+                let _ = spectator.send(&kick_packet(ban)?);
+                address
+            },
+            Member::Spectator(_) => {
+                return Ok(false);
+            }
+        };
+
+        if ban && !self.banned.contains(&address) {
+            self.banned.push(address);
+        }
+
+        match member {
+            Member::Player(id) => {
+                self.send(&goodbye_packet(id)?)?;
+            },
+            Member::Spectator(_) => {}
+        }
+
+        Ok(true)
+    }
+}
+
+fn show_token(local: SocketAddr, config: &GameConfig, listing: &Listing) {
+    let ip = match local.ip() {
+        ip if !ip.is_unspecified() => ip,
+        _ => {
+            match mdns::outbound_address() {
+                Some(ip) => IpAddr::V4(ip),
+                None => {
+                    return;
+                }
+            }
+        }
+    };
+
+    let token = JoinToken {
+        address: SocketAddr::new(ip, local.port()),
+        width: listing.width,
+        height: listing.height,
+        walls: listing.walls,
+        password: config.password.clone(),
+        secret: config.secret.clone()
+    }.encode();
+
+    println!("Join token: {}", token);
+    if !config.qr {
+        return;
+    }
+
+    match QrCode::encode(&token) {
+        Some(qr) => {
+            print!("{}", qr.draw());
+        },
+        None => {
+            println!("The join token is too long for a QR code");
+        }
+    }
+}
+
+pub fn open(remote: SocketAddr, config: &GameConfig) -> Result<(TcpStream, Channel), SnakeError> {
+    if !config.allow_public && !is_private(remote.ip()) {
+        return Err(SnakeError::NotPrivateAddress);
+    }
+
+    println!("Connecting to {}", remote);
+    let timeout = config.timeout.unwrap_or(CONNECT_TIMEOUT);
+    let connected = if config.direct && SUPPORTED { connect_from(0, remote, timeout) } else { TcpStream::connect_timeout(&remote, timeout) };
+    let stream = match connected {
+        Ok(stream) => stream,
+        Err(error) if error.kind() == ErrorKind::TimedOut || error.kind() == ErrorKind::WouldBlock => {
+            return Err(SnakeError::TimedOut(format!("could not reach {} in {}s", remote, timeout.as_secs())));
+        },
+        Err(error) => {
+            return Err(SnakeError::Io(error));
+        }
+    };
+    let raw = stream.try_clone()?;
+    if config.websocket {
+        return Ok((raw, Channel::new(Box::new(WebSocket::connect(stream, &remote.to_string())?))));
+    }
+
+    Ok((raw, Channel::new(Box::new(stream))))
+}
+
+#[allow(clippy::single_match)]
+pub fn rendezvous(socket: &mut Channel, port: u16, listen: bool, pending: &mut VecDeque<Packet>) -> Result<Option<TcpStream>, SnakeError> {
+    socket.send(&Packet::new(Opcode::Rendezvous, 0))?;
+
+    let started = Instant::now();
+    let remote = loop {
+        if started.elapsed() >= PUNCH_TIMEOUT {
+            return Ok(None);
+        }
+
+        match socket.recv()? {
+            Some(packet) if packet.opcode() == Opcode::Rendezvous => {
+                break read_address(&packet)?;
+            },
+            Some(packet) => {
+                pending.push_back(packet);
+            },
+            None => {}
+        }
+    };
+
+    println!("Connecting directly to {}", remote);
+    let listener = if listen {
+        let listener = listen_from(port, remote)?;
+        listener.set_nonblocking(true)?;
+        Some(listener)
+    } else {
+        None
+    };
+
+    while started.elapsed() < PUNCH_TIMEOUT {
+        match &listener {
+            Some(listener) => {
+                match listener.accept() {
+                    Ok((stream, address)) if address.ip() == remote.ip() => {
+                        stream.set_nonblocking(false)?;
+                        return Ok(Some(stream));
+                    },
+                    _ => {}
+                }
+            },
+            None => {}
+        }
+
+        match connect_from(port, remote, PUNCH_ATTEMPT_TIMEOUT) {
+            Ok(stream) => {
+                return Ok(Some(stream));
+            },
+            Err(_) => {
+                sleep(PUNCH_RETRY);
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+#[allow(clippy::identity_op)]
+pub fn address_packet(address: SocketAddr) -> Result<Packet, SnakeError> {
+    let mut packet = Packet::new(Opcode::Rendezvous, 19);
+    match address.ip() {
+        IpAddr::V4(ip) => {
+            packet.push_data(&[4])?;
+            packet.push_data(&ip.octets())?;
+        },
+        IpAddr::V6(ip) => {
+            packet.push_data(&[6])?;
+            packet.push_data(&ip.octets())?;
+        }
+    }
+
+    packet.push_data(&[(address.port() >> 8) as u8, (address.port() >> 0) as u8])?;
+    Ok(packet)
+}
+
+fn read_address(packet: &Packet) -> Result<SocketAddr, SnakeError> {
+    let mut reader = PacketReader::new(packet.data());
+    let ip = match reader.read_u8()? {
+        4 => {
+            let mut octets = [0; 4];
+            for octet in &mut octets {
+                *octet = reader.read_u8()?;
+            }
+
+            IpAddr::V4(Ipv4Addr::from(octets))
+        },
+        6 => {
+            let mut octets = [0; 16];
+            for octet in &mut octets {
+                *octet = reader.read_u8()?;
+            }
+
+            IpAddr::V6(Ipv6Addr::from(octets))
+        },
+        _ => {
+            return Err(ProtocolError::Malformed("address").into());
+        }
+    };
+
+    let port = reader.read_u16()?;
+    if !reader.is_empty() {
+        return Err(ProtocolError::Malformed("address").into());
+    }
+
+    Ok(SocketAddr::new(ip, port))
+}
+
+pub fn accept(stream: TcpStream) -> Result<Box<dyn Connection>, SnakeError> {
+    if WebSocket::is_handshake(&stream)? {
+        return Ok(Box::new(WebSocket::accept(stream)?));
+    }
+
+    Ok(Box::new(stream))
+}
+
+fn spectator(stream: TcpStream, secret: &Option<String>, password: &Option<String>, banned: bool) -> Result<Option<Link>, SnakeError> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(SPECTATOR_TIMEOUT))?;
+    stream.set_write_timeout(Some(SPECTATOR_TIMEOUT))?;
+
+    let raw = stream.try_clone()?;
+    let mut socket = Channel::new(accept(stream)?);
+    match socket.recv()? {
+        Some(packet) if packet.opcode() == Opcode::Spectate => {
+            if !check_secret(&mut socket, secret, &packet)? {
+                let _ = socket.send(&reject_packet(REJECT_SECRET)?);
+                return Ok(None);
+            }
+
+            if banned {
+                let _ = socket.send(&reject_packet(REJECT_BANNED)?);
+                return Ok(None);
+            }
+
+            if !authenticate(&mut socket, password)? {
+                let _ = socket.send(&reject_packet(REJECT_PASSWORD)?);
+                return Ok(None);
+            }
+
+            Ok(Some(Link::spawn(socket, &raw)?))
+        },
+        _ => Ok(None)
+    }
+}
+
+fn admit(socket: &mut Channel, packet: &Packet, config: &GameConfig, settings: u64) -> Result<Result<Option<u32>, u8>, SnakeError> {
+    if !check_secret(socket, &config.secret, packet)? {
+        return Ok(Err(REJECT_SECRET));
+    }
+
+    if !authenticate(socket, &config.password)? {
+        return Ok(Err(REJECT_PASSWORD));
+    }
+
+    if packet.opcode() != Opcode::Join {
+        return Ok(Ok(None));
+    }
+
+    let request = read_join(packet)?;
+    match request.settings {
+        Some(offered) if offered != settings => Ok(Err(REJECT_SETTINGS)),
+        _ => Ok(Ok(request.features))
+    }
+}
+
+fn authenticate(socket: &mut Channel, password: &Option<String>) -> Result<bool, SnakeError> {
+    let password = match password {
+        Some(password) => password,
+        None => {
+            return Ok(true);
+        }
+    };
+
+    let nonce = random_bytes(NONCE_SIZE);
+    let mut packet = Packet::new(Opcode::AuthChallenge, NONCE_SIZE);
+    packet.push_data(&nonce)?;
+    socket.send(&packet)?;
+
+    match socket.recv() {
+        Ok(Some(packet)) if packet.opcode() == Opcode::AuthResponse => {
+            Ok(same_digest(packet.data(), &password_digest(&nonce, password)))
+        },
+        _ => Ok(false)
+    }
+}
+
+pub fn auth_response(challenge: &Packet, password: &Option<String>) -> Result<Packet, SnakeError> {
+    let password = match password {
+        Some(password) => password,
+        None => {
+            return Err(SnakeError::BadPassword);
+        }
+    };
+
+    let mut packet = Packet::new(Opcode::AuthResponse, 20);
+    packet.push_data(&password_digest(challenge.data(), password))?;
+    Ok(packet)
+}
+
+pub fn rejection(packet: &Packet) -> SnakeError {
+    match packet.data().get(1) {
+        Some(&REJECT_SECRET) => SnakeError::BadSecret,
+        Some(&REJECT_PASSWORD) => SnakeError::BadPassword,
+        Some(&REJECT_BANNED) => SnakeError::Banned,
+        Some(&REJECT_SETTINGS) => SnakeError::BadSettings,
+        _ => SnakeError::BadCode
+    }
+}
+
+fn password_digest(nonce: &[u8], password: &str) -> [u8; 20] {
+    let mut data = nonce.to_vec();
+    data.extend_from_slice(password.as_bytes());
+    sha1(&data)
+}
+
+#[allow(clippy::identity_op, clippy::single_match)]
+pub fn join_packet(opcode: Opcode, code: &str, pace: Duration, settings: u64, nonce: &[u8]) -> Result<Packet, SnakeError> {
+    let pace = pace.as_millis() as u16;
+    let mut packet = Packet::new(opcode, 1 + code.len() + 2 + 4 + 8 + nonce.len());
+    packet.push_data(&[code.len() as u8])?;
+    packet.push_data(code.as_bytes())?;
+    packet.push_data(&[(pace >> 8) as u8, (pace >> 0) as u8])?;
+    packet.push_data(&FEATURES.to_be_bytes())?;
+    packet.push_data(&settings.to_be_bytes())?;
+    packet.push_data(nonce)?;
+    Ok(packet)
+}
+
+#[allow(clippy::identity_op)]
+pub fn read_join(packet: &Packet) -> Result<JoinRequest<'_>, SnakeError> {
+    let data = packet.data();
+    let size = match data.first() {
+        Some(size) => *size as usize,
+        None => {
+            return Err(ProtocolError::Truncated.into());
+        }
+    };
+
+    if data.len() < 1 + size + 2 {
+        return Err(ProtocolError::Truncated.into());
+    }
+
+    let code = String::from_utf8_lossy(&data[1..1 + size]).to_uppercase();
+    let pace = ((data[1 + size] as u64) << 8) | ((data[2 + size] as u64) << 0);
+    let (features, rest) = read_features(&data[3 + size..])?;
+    let (settings, nonce) = match features {
+        Some(features) if features & FEATURE_SETTINGS != 0 => (Some(PacketReader::new(rest).read_u64()?), &rest[8..]),
+        _ => (None, rest)
+    };
+
+    Ok(JoinRequest { code, pace: Duration::from_millis(pace), features, settings, nonce })
+}
+
+fn read_features(data: &[u8]) -> Result<(Option<u32>, &[u8]), ProtocolError> {
+    match data.len() {
+        0 | DIGEST_SIZE => Ok((None, data)),
+        _ => {
+            let features = PacketReader::new(data).read_u32()?;
+            Ok((Some(features), &data[4..]))
+        }
+    }
+}
+
+fn show_features(features: u32) {
+    let names = [(FEATURE_CHAT, "chat"), (FEATURE_SNAPSHOTS, "state snapshots"), (FEATURE_BATCH, "batching"), (FEATURE_KICK, "kicking"), (FEATURE_GAME_OVER, "shared results"), (FEATURE_PAUSE_REQUEST, "pause requests"), (FEATURE_SETTINGS, "settings checks")];
+    let missing: Vec<&str> = names.iter().filter(|(feature, _)| features & feature == 0).map(|(_, name)| *name).collect();
+    if !missing.is_empty() {
+        println!("A player runs an older version, playing without {}", missing.join(", "));
+    }
+}
+
+pub fn check_secret(socket: &mut Channel, secret: &Option<String>, packet: &Packet) -> Result<bool, SnakeError> {
+    let secret = match secret {
+        Some(secret) => secret,
+        None => {
+            return Ok(true);
+        }
+    };
+
+    let nonce = read_join(packet)?.nonce.to_vec();
+    if nonce.len() != NONCE_SIZE {
+        return Ok(false);
+    }
+
+    let challenge = random_bytes(NONCE_SIZE);
+    let mut packet = Packet::new(Opcode::SecretChallenge, NONCE_SIZE + DIGEST_SIZE);
+    packet.push_data(&challenge)?;
+    packet.push_data(&secret_proof(HOST_PROOF, &nonce, &challenge, secret))?;
+    socket.send(&packet)?;
+
+    match socket.recv() {
+        Ok(Some(packet)) if packet.opcode() == Opcode::SecretResponse => {
+            Ok(same_digest(packet.data(), &secret_proof(CLIENT_PROOF, &challenge, &nonce, secret)))
+        },
+        _ => Ok(false)
+    }
+}
+
+pub fn secret_nonce(secret: &Option<String>) -> Vec<u8> {
+    match secret {
+        Some(_) => random_bytes(NONCE_SIZE),
+        None => Vec::new()
+    }
+}
+
+pub fn secret_response(challenge: &Packet, nonce: &[u8], secret: &Option<String>) -> Result<Packet, SnakeError> {
+    let secret = match secret {
+        Some(secret) => secret,
+        None => {
+            return Err(SnakeError::BadSecret);
+        }
+    };
+
+    let data = challenge.data();
+    if data.len() != NONCE_SIZE + DIGEST_SIZE || !same_digest(&data[NONCE_SIZE..], &secret_proof(HOST_PROOF, nonce, &data[..NONCE_SIZE], secret)) {
+        return Err(SnakeError::BadSecret);
+    }
+
+    let mut packet = Packet::new(Opcode::SecretResponse, DIGEST_SIZE);
+    packet.push_data(&secret_proof(CLIENT_PROOF, &data[..NONCE_SIZE], nonce, secret))?;
+    Ok(packet)
+}
+
+#[allow(clippy::identity_op, clippy::single_match)]
+pub fn welcome_packet(id: usize, count: usize, authoritative: bool, pace: Duration, seed: u64, features: Option<u32>) -> Result<Packet, SnakeError> {
+    let pace = pace.as_millis() as u16;
+    let mut packet = Packet::new(Opcode::Welcome, WELCOME_SIZE + 4);
+    packet.push_data(&[id as u8, count as u8, authoritative as u8, (pace >> 8) as u8, (pace >> 0) as u8])?;
+    packet.push_data(&seed.to_be_bytes())?;
+    match features {
+        Some(features) => {
+            packet.push_data(&features.to_be_bytes())?;
+        },
+        None => {}
+    }
+
+    Ok(packet)
+}
+
+fn names_packet(names: &[String]) -> Result<Packet, SnakeError> {
+    let mut packet = Packet::new(Opcode::Names, names.len() * (1 + MAX_NAME_LENGTH));
+    for name in names {
+        packet.push_data(&[name.len() as u8])?;
+        packet.push_data(name.as_bytes())?;
+    }
+
+    Ok(packet)
+}
+
+fn read_names(packet: &Packet) -> Result<Vec<String>, SnakeError> {
+    let data = packet.data();
+    let mut names = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let size = data[offset] as usize;
+        if offset + 1 + size > data.len() || names.len() == MAX_PLAYERS {
+            return Err(ProtocolError::Malformed("player names").into());
+        }
+
+        let name = String::from_utf8_lossy(&data[offset + 1..offset + 1 + size]);
+        names.push(name.chars().filter(|c| !c.is_control()).take(MAX_NAME_LENGTH).collect());
+        offset += 1 + size;
+    }
+
+    Ok(names)
+}
+
+pub fn reject_packet(reason: u8) -> Result<Packet, SnakeError> {
+    let mut packet = Packet::new(Opcode::Goodbye, 2);
+    packet.push_data(&[HOST_ID as u8, reason])?;
+    Ok(packet)
+}
+
+fn secret_proof(role: u8, first: &[u8], second: &[u8], secret: &str) -> [u8; 20] {
+    let mut data = PROTOCOL_ID.to_be_bytes().to_vec();
+    data.push(role);
+    data.extend_from_slice(first);
+    data.extend_from_slice(second);
+    data.extend_from_slice(secret.as_bytes());
+    sha1(&data)
+}
+
+fn same_digest(received: &[u8], expected: &[u8]) -> bool {
+    received.len() == expected.len() && received.iter().zip(expected).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+pub fn goodbye_packet(id: usize) -> Result<Packet, SnakeError> {
+    let mut packet = Packet::new(Opcode::Goodbye, 1);
+    packet.push_data(&[id as u8])?;
+    Ok(packet)
+}
+
+fn kick_packet(ban: bool) -> Result<Packet, SnakeError> {
+    let mut packet = Packet::new(Opcode::Kick, 1);
+    packet.push_data(&[ban as u8])?;
+    Ok(packet)
+}
+
+fn strategy(config: &GameConfig, authoritative: bool) -> Box<dyn SyncStrategy> {
+    if authoritative {
+        return Box::new(Lockstep);
+    }
+
+    config.sync.strategy()
+}
+
+fn ready_packet(id: usize) -> Result<Packet, SnakeError> {
+    let mut packet = Packet::new(Opcode::Ready, 1);
+    packet.push_data(&[id as u8])?;
+    Ok(packet)
+}
+
+#[allow(clippy::identity_op)]
+pub fn id_packet(opcode: Opcode, id: u64) -> Result<Packet, SnakeError> {
+    let mut packet = Packet::new(opcode, 8);
+
+    let mut data = [0; 8];
+    data[0] = (id >> 56) as u8;
+    data[1] = (id >> 48) as u8;
+    data[2] = (id >> 40) as u8;
+    data[3] = (id >> 32) as u8;
+    data[4] = (id >> 24) as u8;
+    data[5] = (id >> 16) as u8;
+    data[6] = (id >> 8) as u8;
+    data[7] = (id >> 0) as u8;
+
+    packet.push_data(&data)?;
+    Ok(packet)
+}
+
@@ -1,40 +1,162 @@
+use std::io::Write;
+
+use crate::error::{
+    ProtocolError, SnakeError
+};
+
 pub const PROTOCOL_ID: u64 = 0xaefdb87fe753ba07;
-pub const HEADER_SIZE: usize = 12;
+pub const HEADER_SIZE: usize = 16;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Opcode {
     Sync = 0x01,
     NewDirection,
-    NewTarget
+    NewTarget,
+    Pause,
+    Snapshot,
+    Spectate,
+    Join,
+    Welcome,
+    Ping,
+    Goodbye,
+    RematchRequest,
+    RematchAccept,
+    Chat,
+    StateSnapshot,
+    Register,
+    Code,
+    Discover,
+    Announce,
+    Rendezvous,
+    AuthChallenge,
+    AuthResponse,
+    Pong,
+    Names,
+    Ready,
+    Kick,
+    Batch,
+    GameOver,
+    PauseRequest,
+    SecretChallenge,
+    SecretResponse
+}
+
+impl Opcode {
+    pub fn from(value: u16) -> Option<Opcode> {
+        match value {
+            0x01 => Some(Opcode::Sync),
+            0x02 => Some(Opcode::NewDirection),
+            0x03 => Some(Opcode::NewTarget),
+            0x04 => Some(Opcode::Pause),
+            0x05 => Some(Opcode::Snapshot),
+            0x06 => Some(Opcode::Spectate),
+            0x07 => Some(Opcode::Join),
+            0x08 => Some(Opcode::Welcome),
+            0x09 => Some(Opcode::Ping),
+            0x0a => Some(Opcode::Goodbye),
+            0x0b => Some(Opcode::RematchRequest),
+            0x0c => Some(Opcode::RematchAccept),
+            0x0d => Some(Opcode::Chat),
+            0x0e => Some(Opcode::StateSnapshot),
+            0x0f => Some(Opcode::Register),
+            0x10 => Some(Opcode::Code),
+            0x11 => Some(Opcode::Discover),
+            0x12 => Some(Opcode::Announce),
+            0x13 => Some(Opcode::Rendezvous),
+            0x14 => Some(Opcode::AuthChallenge),
+            0x15 => Some(Opcode::AuthResponse),
+            0x16 => Some(Opcode::Pong),
+            0x17 => Some(Opcode::Names),
+            0x18 => Some(Opcode::Ready),
+            0x19 => Some(Opcode::Kick),
+            0x1a => Some(Opcode::Batch),
+            0x1b => Some(Opcode::GameOver),
+            0x1c => Some(Opcode::PauseRequest),
+            0x1d => Some(Opcode::SecretChallenge),
+            0x1e => Some(Opcode::SecretResponse),
+            _ => None
+        }
+    }
 }
 
+#[derive(Clone)]
 pub struct Packet {
     opcode: Opcode,
+    sequence: u32,
     data: Vec<u8>
 }
 
 impl Packet {
     pub fn new(opcode: Opcode, size: usize) -> Packet {
-        Packet { opcode, data: Vec::with_capacity(size) }
+        Packet { opcode, sequence: 0, data: Vec::with_capacity(size) }
     }
 
-    pub fn push_data(&mut self, data: &[u8]) {
+    pub fn push_data(&mut self, data: &[u8]) -> Result<(), SnakeError> {
         if self.data.len() + data.len() > u16::MAX as usize {
-            panic!("bad data size [Packet::push_data()]");
+            return Err(SnakeError::BadDataSize);
         }
 
         self.data.extend_from_slice(data);
+        Ok(())
+    }
+
+    #[allow(clippy::identity_op)]
+    pub fn batch(packets: &[Packet]) -> Result<Packet, SnakeError> {
+        if packets.len() > u8::MAX as usize {
+            return Err(SnakeError::BadDataSize);
+        }
+
+        let mut batch = Packet::new(Opcode::Batch, 1 + packets.iter().map(|packet| 4 + packet.data.len()).sum::<usize>());
+        batch.push_data(&[packets.len() as u8])?;
+        for packet in packets {
+            let (opcode, size) = (packet.opcode as u16, packet.data.len() as u16);
+            batch.push_data(&[(opcode >> 8) as u8, (opcode >> 0) as u8, (size >> 8) as u8, (size >> 0) as u8])?;
+            batch.push_data(&packet.data)?;
+        }
+
+        Ok(batch)
+    }
+
+    pub fn unbatch(&self) -> Result<Vec<Packet>, ProtocolError> {
+        let mut reader = PacketReader::new(&self.data);
+        let count = reader.read_u8()? as usize;
+        let mut packets = Vec::with_capacity(count);
+        for _ in 0..count {
+            let opcode = reader.read_u16()?;
+            let opcode = match Opcode::from(opcode) {
+                Some(Opcode::Batch) => {
+                    return Err(ProtocolError::NestedBatch);
+                },
+                Some(opcode) => opcode,
+                None => {
+                    return Err(ProtocolError::UnknownOpcode(opcode));
+                }
+            };
+
+            let size = reader.read_u16()? as usize;
+            let mut packet = Packet::new(opcode, size);
+            packet.sequence = self.sequence;
+            packet.data.extend_from_slice(reader.read_bytes(size)?);
+            packets.push(packet);
+        }
+
+        Ok(packets)
     }
 
     pub fn opcode(&self) -> Opcode {
         self.opcode
     }
 
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
     pub fn data(&self) -> &Vec<u8> {
         &self.data
     }
 
-    pub fn encode(&self) -> Vec<u8> {
+    #[allow(clippy::identity_op)]
+    pub fn encode(&self, sequence: u32) -> Vec<u8> {
         let size = HEADER_SIZE + self.data.len();
         let mut buffer = Vec::with_capacity(size);
 
@@ -50,6 +172,11 @@ impl Packet {
         buffer.push((self.opcode as u16 >> 8) as u8);
         buffer.push((self.opcode as u16 >> 0) as u8);
 
+        buffer.push((sequence >> 24) as u8);
+        buffer.push((sequence >> 16) as u8);
+        buffer.push((sequence >> 8) as u8);
+        buffer.push((sequence >> 0) as u8);
+
         let size = self.data.len();
         buffer.push((size >> 8) as u8);
         buffer.push((size >> 0) as u8);
@@ -58,11 +185,35 @@ impl Packet {
         buffer
     }
 
-    pub fn decode(buffer: &[u8]) -> Option<Packet> {
+    pub fn send<W: Write + ?Sized>(&self, socket: &mut W, sequence: u32) -> Result<(), SnakeError> {
+        socket.write_all(&self.encode(sequence))?;
+        Ok(())
+    }
+
+    #[allow(clippy::identity_op)]
+    pub fn frame_size(buffer: &[u8]) -> Option<usize> {
         if buffer.len() < HEADER_SIZE {
             return None;
         }
 
+        let mut size: u16 = 0;
+        size |= (buffer[14] as u16) << 8;
+        size |= (buffer[15] as u16) << 0;
+
+        let size = HEADER_SIZE + size as usize;
+        if buffer.len() < size {
+            return None;
+        }
+
+        Some(size)
+    }
+
+    #[allow(clippy::identity_op)]
+    pub fn decode(buffer: &[u8]) -> Result<Packet, ProtocolError> {
+        if buffer.len() < HEADER_SIZE {
+            return Err(ProtocolError::Truncated);
+        }
+
         let mut protocol_id: u64 = 0;
         protocol_id |= (buffer[0] as u64) << 56;
         protocol_id |= (buffer[1] as u64) << 48;
@@ -74,38 +225,97 @@ impl Packet {
         protocol_id |= (buffer[7] as u64) << 0;
 
         if protocol_id != PROTOCOL_ID {
-            return None;
+            return Err(ProtocolError::BadProtocolId(protocol_id));
         }
 
         let mut opcode: u16 = 0;
         opcode |= (buffer[8] as u16) << 8;
         opcode |= (buffer[9] as u16) << 0;
 
-        let opcode = match opcode {
-            0x01 => {
-                Opcode::Sync
-            },
-            0x02 => {
-                Opcode::NewDirection
-            },
-            0x03 => {
-                Opcode::NewTarget
-            },
-            _ => {
-                return None;
+        let opcode = match Opcode::from(opcode) {
+            Some(opcode) => opcode,
+            None => {
+                return Err(ProtocolError::UnknownOpcode(opcode));
             }
         };
 
+        let mut sequence: u32 = 0;
+        sequence |= (buffer[10] as u32) << 24;
+        sequence |= (buffer[11] as u32) << 16;
+        sequence |= (buffer[12] as u32) << 8;
+        sequence |= (buffer[13] as u32) << 0;
+
         let mut size: u16 = 0;
-        size |= (buffer[10] as u16) << 8;
-        size |= (buffer[11] as u16) << 0;
+        size |= (buffer[14] as u16) << 8;
+        size |= (buffer[15] as u16) << 0;
 
         if size as usize != buffer.len() - HEADER_SIZE {
-            return None;
+            return Err(ProtocolError::BadSize(size as usize, buffer.len() - HEADER_SIZE));
         }
 
         let mut packet = Packet::new(opcode, size as usize);
+        packet.sequence = sequence;
         packet.data.extend_from_slice(&buffer[HEADER_SIZE..]);
-        Some(packet)
+        Ok(packet)
+    }
+}
+
+pub struct PacketReader<'a> {
+    data: &'a [u8],
+    offset: usize
+}
+
+impl<'a> PacketReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        PacketReader { data, offset: 0 }
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, ProtocolError> {
+        if self.offset >= self.data.len() {
+            return Err(ProtocolError::Truncated);
+        }
+
+        let value = self.data[self.offset];
+        self.offset += 1;
+        Ok(value)
+    }
+
+    #[allow(clippy::identity_op)]
+    pub fn read_u16(&mut self) -> Result<u16, ProtocolError> {
+        let mut value: u16 = 0;
+        value |= (self.read_u8()? as u16) << 8;
+        value |= (self.read_u8()? as u16) << 0;
+        Ok(value)
+    }
+
+    #[allow(clippy::identity_op)]
+    pub fn read_u32(&mut self) -> Result<u32, ProtocolError> {
+        let mut value: u32 = 0;
+        value |= (self.read_u16()? as u32) << 16;
+        value |= (self.read_u16()? as u32) << 0;
+        Ok(value)
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, ProtocolError> {
+        let mut value: u64 = 0;
+        for _ in 0..8 {
+            value = (value << 8) | self.read_u8()? as u64;
+        }
+
+        Ok(value)
+    }
+
+    pub fn read_bytes(&mut self, size: usize) -> Result<&'a [u8], ProtocolError> {
+        if size > self.data.len() - self.offset {
+            return Err(ProtocolError::Truncated);
+        }
+
+        let bytes = &self.data[self.offset..self.offset + size];
+        self.offset += size;
+        Ok(bytes)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offset == self.data.len()
     }
 }
@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use crate::{
+    config::{
+        GameConfig, MAX_PACE, MIN_PACE
+    },
+    direction::Direction,
+    input::{
+        Key, CHAT_KEY
+    },
+    keymap::{
+        Action, Keymap
+    }
+};
+
+const PACE_STEP: u64 = 25;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Item {
+    Pace,
+    Color,
+    Sound,
+    Controls,
+    Forfeit,
+    Resume
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Setting {
+    Pace(Duration),
+    Color(bool),
+    Sound(bool)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PauseAction {
+    Apply(Setting),
+    Forfeit,
+    Resume
+}
+
+pub struct PauseMenu {
+    items: Vec<Item>,
+    selected: usize,
+    controls: bool,
+    keymap: Keymap
+}
+
+impl PauseMenu {
+    pub fn new(pace: bool, keymap: Keymap) -> Self {
+        let mut items = vec![Item::Color, Item::Sound, Item::Controls, Item::Forfeit, Item::Resume];
+        if pace {
+            items.insert(0, Item::Pace);
+        }
+
+        PauseMenu { items, selected: 0, controls: false, keymap }
+    }
+
+    pub fn key(&mut self, key: &Key, config: &GameConfig) -> Option<PauseAction> {
+        let item = self.items[self.selected];
+        match key {
+            Key::Direction(_, Direction::Up) => {
+                self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+                None
+            },
+            Key::Direction(_, Direction::Down) => {
+                self.selected = (self.selected + 1) % self.items.len();
+                None
+            },
+            Key::Direction(_, direction) if item == Item::Pace => {
+                let pace = config.pace.as_millis() as u64;
+                let pace = if *direction == Direction::Left { pace.saturating_sub(PACE_STEP) } else { pace + PACE_STEP };
+                Some(PauseAction::Apply(Setting::Pace(Duration::from_millis(pace.clamp(MIN_PACE, MAX_PACE)))))
+            },
+            Key::Direction(_, _) | Key::Select => {
+                match item {
+                    Item::Pace => None,
+                    Item::Color => Some(PauseAction::Apply(Setting::Color(!config.color))),
+                    Item::Sound => Some(PauseAction::Apply(Setting::Sound(!config.sound))),
+                    Item::Controls => {
+                        self.controls = !self.controls;
+                        None
+                    },
+                    Item::Forfeit if *key == Key::Select => Some(PauseAction::Forfeit),
+                    Item::Resume if *key == Key::Select => Some(PauseAction::Resume),
+                    _ => None
+                }
+            },
+            _ => None
+        }
+    }
+
+    pub fn draw(&self, config: &GameConfig) -> String {
+        let key = |action: Action| self.keymap.key(action);
+        let mut s = String::from("PAUSED\n");
+        for (i, item) in self.items.iter().enumerate() {
+            let label = match item {
+                Item::Pace => format!("Pace: {} ms ({}/{} or left/right)", config.pace.as_millis(), key(Action::Left), key(Action::Right)),
+                Item::Color => format!("Colors: {}", if config.color { "on" } else { "off" }),
+                Item::Sound => format!("Sound: {}", if config.sound { "on" } else { "off" }),
+                Item::Controls => "Controls".into(),
+                Item::Forfeit => "Forfeit".into(),
+                Item::Resume => "Resume".into()
+            };
+
+            s.push_str(if i == self.selected { "> " } else { "  " });
+            s.push_str(&label);
+            s.push('\n');
+        }
+
+        if self.controls {
+            s.push_str(&format!(
+                "\n{} {} {} {} or the arrow keys steer (P3 i j k l, P4 8 4 5 6), each line needs Enter\n",
+                key(Action::Up), key(Action::Left), key(Action::Down), key(Action::Right)
+            ));
+            s.push_str(&format!("{} pauses, {} quits, {} <message> chats in multiplayer\n", key(Action::Pause), key(Action::Quit), CHAT_KEY));
+        }
+
+        s.push_str(&format!(
+            "{}/{} or arrow keys + Enter to move, Enter to select, {} to resume\n",
+            key(Action::Up), key(Action::Down), key(Action::Pause)
+        ));
+        s
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    io,
+    net::{
+        SocketAddr, TcpListener, TcpStream
+    },
+    time::Duration
+};
+
+#[cfg(target_os = "linux")]
+pub const SUPPORTED: bool = true;
+
+#[cfg(not(target_os = "linux"))]
+pub const SUPPORTED: bool = false;
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use std::{
+        ffi::c_void,
+        io,
+        net::{
+            IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr
+        },
+        os::fd::{
+            AsRawFd, FromRawFd, OwnedFd
+        }
+    };
+
+    const AF_INET: u16 = 2;
+    const AF_INET6: u16 = 10;
+    const SOCK_STREAM: i32 = 1;
+    const SOCK_CLOEXEC: i32 = 0o2000000;
+    const SOL_SOCKET: i32 = 1;
+    const SO_REUSEADDR: i32 = 2;
+    const SO_REUSEPORT: i32 = 15;
+    const BACKLOG: i32 = 4;
+
+    extern "C" {
+        fn socket(domain: i32, kind: i32, protocol: i32) -> i32;
+        fn setsockopt(fd: i32, level: i32, name: i32, value: *const c_void, length: u32) -> i32;
+        fn bind(fd: i32, address: *const u8, length: u32) -> i32;
+        fn connect(fd: i32, address: *const u8, length: u32) -> i32;
+        fn listen(fd: i32, backlog: i32) -> i32;
+    }
+
+    pub fn bound_socket(port: u16, remote: SocketAddr) -> io::Result<OwnedFd> {
+        let (domain, local) = match remote {
+            SocketAddr::V4(_) => (AF_INET, SocketAddr::from((Ipv4Addr::UNSPECIFIED, port))),
+            SocketAddr::V6(_) => (AF_INET6, SocketAddr::from((Ipv6Addr::UNSPECIFIED, port)))
+        };
+
+        let fd = unsafe { socket(domain as i32, SOCK_STREAM | SOCK_CLOEXEC, 0) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        let enable: i32 = 1;
+        for option in [SO_REUSEADDR, SO_REUSEPORT] {
+            if unsafe { setsockopt(fd.as_raw_fd(), SOL_SOCKET, option, &enable as *const i32 as *const c_void, 4) } < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        let local = sockaddr(local);
+        if unsafe { bind(fd.as_raw_fd(), local.as_ptr(), local.len() as u32) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(fd)
+    }
+
+    pub fn connect_to<F: AsRawFd>(fd: &F, remote: SocketAddr) -> io::Result<()> {
+        let remote = sockaddr(remote);
+        if unsafe { connect(fd.as_raw_fd(), remote.as_ptr(), remote.len() as u32) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    pub fn listen_on<F: AsRawFd>(fd: &F) -> io::Result<()> {
+        if unsafe { listen(fd.as_raw_fd(), BACKLOG) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    fn sockaddr(address: SocketAddr) -> Vec<u8> {
+        let mut raw = Vec::with_capacity(28);
+        match address.ip() {
+            IpAddr::V4(ip) => {
+                raw.extend_from_slice(&AF_INET.to_ne_bytes());
+                raw.extend_from_slice(&address.port().to_be_bytes());
+                raw.extend_from_slice(&ip.octets());
+                raw.extend_from_slice(&[0; 8]);
+            },
+            IpAddr::V6(ip) => {
+                raw.extend_from_slice(&AF_INET6.to_ne_bytes());
+                raw.extend_from_slice(&address.port().to_be_bytes());
+                raw.extend_from_slice(&[0; 4]);
+                raw.extend_from_slice(&ip.octets());
+                raw.extend_from_slice(&[0; 4]);
+            }
+        }
+
+        raw
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn connect_from(port: u16, remote: SocketAddr, timeout: Duration) -> io::Result<TcpStream> {
+    let stream = TcpStream::from(sys::bound_socket(port, remote)?);
+    stream.set_write_timeout(Some(timeout))?;
+    sys::connect_to(&stream, remote)?;
+    stream.set_write_timeout(None)?;
+    Ok(stream)
+}
+
+#[cfg(target_os = "linux")]
+pub fn listen_from(port: u16, remote: SocketAddr) -> io::Result<TcpListener> {
+    let listener = TcpListener::from(sys::bound_socket(port, remote)?);
+    sys::listen_on(&listener)?;
+    Ok(listener)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn connect_from(_: u16, _: SocketAddr, _: Duration) -> io::Result<TcpStream> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "hole punching is not supported on this platform"))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn listen_from(_: u16, _: SocketAddr) -> io::Result<TcpListener> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "hole punching is not supported on this platform"))
+}
@@ -0,0 +1,329 @@
+const ALPHANUMERIC: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+const QUIET_ZONE: usize = 2;
+const FORMAT_MASK: u32 = 0x5412;
+const FORMAT_GENERATOR: u32 = 0x537;
+const ECC_LOW: u32 = 1;
+
+const VERSIONS: [(usize, usize, usize); 6] = [(26, 7, 1), (44, 10, 1), (70, 15, 1), (100, 20, 1), (134, 26, 1), (172, 18, 2)];
+const ALIGNMENT: [usize; 6] = [0, 18, 22, 26, 30, 34];
+
+pub struct QrCode {
+    size: usize,
+    modules: Vec<Vec<bool>>,
+    reserved: Vec<Vec<bool>>
+}
+
+impl QrCode {
+    pub fn encode(text: &str) -> Option<Self> {
+        let values: Vec<usize> = text.chars().map(|c| ALPHANUMERIC.find(c)).collect::<Option<Vec<usize>>>()?;
+        let bits = 4 + 9 + values.len() / 2 * 11 + values.len() % 2 * 6;
+        let version = (1..=VERSIONS.len()).find(|version| bits <= data_codewords(*version) * 8)?;
+
+        let mut data = Bits::default();
+        data.push(0b0010, 4);
+        data.push(values.len() as u32, 9);
+        for pair in values.chunks(2) {
+            match pair {
+                [a, b] => data.push((a * 45 + b) as u32, 11),
+                [a] => data.push(*a as u32, 6),
+                _ => {}
+            }
+        }
+
+        let capacity = data_codewords(version) * 8;
+        data.push(0, (capacity - data.len()).min(4));
+        data.push(0, (8 - data.len() % 8) % 8);
+        for pad in [0xec, 0x11].iter().cycle() {
+            if data.len() >= capacity {
+                break;
+            }
+
+            data.push(*pad, 8);
+        }
+
+        let size = version * 4 + 17;
+        let mut qr = QrCode { size, modules: vec![vec![false; size]; size], reserved: vec![vec![false; size]; size] };
+        qr.draw_patterns(version);
+        qr.draw_codewords(&interleave(version, &data.bytes()));
+
+        let mask = (0..8).min_by_key(|mask| {
+            qr.apply_mask(*mask);
+            qr.draw_format(*mask);
+            let penalty = qr.penalty();
+            qr.apply_mask(*mask);
+            penalty
+        })?;
+
+        qr.apply_mask(mask);
+        qr.draw_format(mask);
+        Some(qr)
+    }
+
+    pub fn draw(&self) -> String {
+        let size = self.size + QUIET_ZONE * 2;
+        let dark = |x: usize, y: usize| {
+            x >= QUIET_ZONE && y >= QUIET_ZONE && x < self.size + QUIET_ZONE && y < self.size + QUIET_ZONE
+                && self.modules[y - QUIET_ZONE][x - QUIET_ZONE]
+        };
+
+        let mut s = String::new();
+        for y in (0..size).step_by(2) {
+            for x in 0..size {
+                let color = |dark: bool| if dark { 30 } else { 97 };
+                let background = |dark: bool| if dark { 40 } else { 107 };
+                s.push_str(&format!("\x1b[{};{}m\u{2580}", color(dark(x, y)), background(y + 1 < size && dark(x, y + 1))));
+            }
+
+            s.push_str("\x1b[0m\n");
+        }
+
+        s
+    }
+
+    fn set(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y][x] = dark;
+        self.reserved[y][x] = true;
+    }
+
+    fn draw_patterns(&mut self, version: usize) {
+        for i in 0..self.size {
+            self.set(6, i, i % 2 == 0);
+            self.set(i, 6, i % 2 == 0);
+        }
+
+        let far = self.size - 4;
+        for (x, y) in [(3, 3), (far, 3), (3, far)] {
+            for dy in -4..=4_isize {
+                for dx in -4..=4_isize {
+                    let (px, py) = (x as isize + dx, y as isize + dy);
+                    if px >= 0 && py >= 0 && (px as usize) < self.size && (py as usize) < self.size {
+                        let distance = dx.abs().max(dy.abs());
+                        self.set(px as usize, py as usize, distance != 2 && distance != 4);
+                    }
+                }
+            }
+        }
+
+        let position = ALIGNMENT[version - 1];
+        if position > 0 {
+            for dy in -2..=2_isize {
+                for dx in -2..=2_isize {
+                    self.set((position as isize + dx) as usize, (position as isize + dy) as usize, dx.abs().max(dy.abs()) != 1);
+                }
+            }
+        }
+
+        self.draw_format(0);
+    }
+
+    fn draw_format(&mut self, mask: u32) {
+        let data = ECC_LOW << 3 | mask;
+        let mut remainder = data;
+        for _ in 0..10 {
+            remainder = (remainder << 1) ^ ((remainder >> 9) * FORMAT_GENERATOR);
+        }
+
+        let bits = (data << 10 | remainder) ^ FORMAT_MASK;
+        let bit = |i: usize| (bits >> i) & 1 != 0;
+        for i in 0..6 {
+            self.set(8, i, bit(i));
+        }
+
+        self.set(8, 7, bit(6));
+        self.set(8, 8, bit(7));
+        self.set(7, 8, bit(8));
+        for i in 9..15 {
+            self.set(14 - i, 8, bit(i));
+        }
+
+        for i in 0..8 {
+            self.set(self.size - 1 - i, 8, bit(i));
+        }
+
+        for i in 8..15 {
+            self.set(8, self.size - 15 + i, bit(i));
+        }
+
+        self.set(8, self.size - 8, true);
+    }
+
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let mut i = 0;
+        let mut right = self.size - 1;
+        loop {
+            if right == 6 {
+                right = 5;
+            }
+
+            for vertical in 0..self.size {
+                for j in 0..2 {
+                    let x = right - j;
+                    let y = if (right + 1) & 2 == 0 { self.size - 1 - vertical } else { vertical };
+                    if !self.reserved[y][x] && i < codewords.len() * 8 {
+                        self.modules[y][x] = (codewords[i >> 3] >> (7 - (i & 7))) & 1 != 0;
+                        i += 1;
+                    }
+                }
+            }
+
+            if right < 2 {
+                break;
+            }
+
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0
+                };
+
+                if invert && !self.reserved[y][x] {
+                    self.modules[y][x] = !self.modules[y][x];
+                }
+            }
+        }
+    }
+
+    fn penalty(&self) -> usize {
+        let mut penalty = 0;
+        let finder = [true, false, true, true, true, false, true, false, false, false, false];
+        for transposed in [false, true] {
+            let line = |i: usize| -> Vec<bool> {
+                (0..self.size).map(|j| if transposed { self.modules[j][i] } else { self.modules[i][j] }).collect()
+            };
+
+            for i in 0..self.size {
+                let line = line(i);
+                let mut run = 1;
+                for j in 1..=self.size {
+                    if j < self.size && line[j] == line[j - 1] {
+                        run += 1;
+                        continue;
+                    }
+
+                    if run >= 5 {
+                        penalty += run - 2;
+                    }
+
+                    run = 1;
+                }
+
+                for window in line.windows(finder.len()) {
+                    if window == finder || window.iter().rev().eq(finder.iter()) {
+                        penalty += 40;
+                    }
+                }
+            }
+        }
+
+        for y in 1..self.size {
+            for x in 1..self.size {
+                let dark = self.modules[y][x];
+                if self.modules[y - 1][x] == dark && self.modules[y][x - 1] == dark && self.modules[y - 1][x - 1] == dark {
+                    penalty += 3;
+                }
+            }
+        }
+
+        let total = self.size * self.size;
+        let dark = self.modules.iter().flatten().filter(|dark| **dark).count();
+        penalty + (dark * 20).abs_diff(total * 10).div_ceil(total).saturating_sub(1) * 10
+    }
+}
+
+#[derive(Default)]
+struct Bits {
+    bits: Vec<bool>
+}
+
+impl Bits {
+    fn push(&mut self, value: u32, count: usize) {
+        for i in (0..count).rev() {
+            self.bits.push((value >> i) & 1 != 0);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        self.bits.chunks(8).map(|byte| byte.iter().fold(0, |value, bit| value << 1 | *bit as u8)).collect()
+    }
+}
+
+fn data_codewords(version: usize) -> usize {
+    let (total, ecc, blocks) = VERSIONS[version - 1];
+    total - ecc * blocks
+}
+
+fn interleave(version: usize, data: &[u8]) -> Vec<u8> {
+    let (total, ecc, blocks) = VERSIONS[version - 1];
+    let length = total / blocks - ecc;
+    let divisor = generator(ecc);
+    let blocks: Vec<(&[u8], Vec<u8>)> = data.chunks(length).map(|block| (block, remainder(block, &divisor))).collect();
+
+    let mut codewords = Vec::with_capacity(total);
+    for i in 0..length {
+        codewords.extend(blocks.iter().map(|(block, _)| block[i]));
+    }
+
+    for i in 0..ecc {
+        codewords.extend(blocks.iter().map(|(_, ecc)| ecc[i]));
+    }
+
+    codewords
+}
+
+fn generator(degree: usize) -> Vec<u8> {
+    let mut divisor = vec![0; degree];
+    divisor[degree - 1] = 1;
+
+    let mut root = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            divisor[j] = multiply(divisor[j], root);
+            if j + 1 < degree {
+                divisor[j] ^= divisor[j + 1];
+            }
+        }
+
+        root = multiply(root, 0x02);
+    }
+
+    divisor
+}
+
+fn remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0; divisor.len()];
+    for byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (r, d) in result.iter_mut().zip(divisor) {
+            *r ^= multiply(*d, factor);
+        }
+    }
+
+    result
+}
+
+fn multiply(x: u8, y: u8) -> u8 {
+    let mut z: u32 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11d);
+        z ^= ((y as u32 >> i) & 1) * x as u32;
+    }
+
+    z as u8
+}
@@ -0,0 +1,287 @@
+use std::{
+    collections::HashMap,
+    net::{
+        Ipv4Addr, SocketAddr, TcpListener, TcpStream
+    },
+    sync::{
+        mpsc::{
+            channel, Receiver
+        },
+        Arc
+    },
+    thread::spawn,
+    time::Duration
+};
+
+use crate::{
+    discovery::Listing,
+    error::SnakeError,
+    metrics::{
+        self, Metrics
+    },
+    net::{
+        accept, address_packet, check_secret, goodbye_packet, read_join, reject_packet, welcome_packet, Channel, FEATURES, HOST_ID, LEGACY_FEATURES, REJECT_CODE, REJECT_SECRET, REJECT_SETTINGS
+    },
+    packet::{
+        Opcode, Packet
+    },
+    util::{
+        time_seed, Rng
+    }
+};
+
+pub const MAX_CODE_LENGTH: usize = 8;
+
+const JOIN_TIMEOUT: Duration = Duration::from_secs(5);
+const RELAY_TIMEOUT: Duration = Duration::from_millis(10);
+const CODE_LENGTH: usize = 4;
+const CODE_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+#[allow(clippy::single_match)]
+pub fn serve(port: u16, secret: Option<String>, metrics_port: Option<u16>) -> Result<(), SnakeError> {
+    let server = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port))?;
+    println!("Relaying games at {}", server.local_addr()?);
+
+    let metrics = Arc::new(Metrics::default());
+    match metrics_port {
+        Some(port) => {
+            metrics::serve(port, metrics.clone())?;
+        },
+        None => {}
+    }
+
+    let (joined_tx, joined_rx) = channel();
+    {
+        let metrics = metrics.clone();
+        spawn(move || {
+            match matchmake(joined_rx, metrics) {
+                Ok(_) => {},
+                Err(error) => {
+                    eprintln!("Error: {} (matchmaking stopped)", error);
+                }
+            }
+        });
+    }
+
+    loop {
+        let (stream, remote) = server.accept()?;
+        let secret = secret.clone();
+        let metrics = metrics.clone();
+        let joined_tx = joined_tx.clone();
+        spawn(move || {
+            match join(stream, &secret) {
+                Ok(Some((socket, packet, listing))) => {
+                    let _ = joined_tx.send((socket, remote, packet, listing));
+                },
+                Ok(None) => {},
+                Err(error) => {
+                    eprintln!("Error: {} ({})", error, remote);
+                    metrics.error();
+                }
+            }
+        });
+    }
+}
+
+struct Player {
+    socket: Channel,
+    address: SocketAddr,
+    pace: Duration,
+    features: Option<u32>,
+    settings: Option<u64>
+}
+
+fn agrees(host: &Player, settings: Option<u64>) -> bool {
+    match (host.settings, settings) {
+        (Some(expected), Some(offered)) => expected == offered,
+        _ => true
+    }
+}
+
+#[allow(clippy::single_match)]
+fn matchmake(joined: Receiver<(Channel, SocketAddr, Packet, Option<Listing>)>, metrics: Arc<Metrics>) -> Result<(), SnakeError> {
+    let mut rng = Rng::new(time_seed());
+    let mut waiting: Option<Player> = None;
+    let mut games: HashMap<String, (Player, Option<Listing>)> = HashMap::new();
+    let mut started = 0;
+    while let Ok((mut socket, remote, packet, listing)) = joined.recv() {
+        let (code, pace, features, settings) = match read_join(&packet) {
+            Ok(request) => (request.code, request.pace, request.features, request.settings),
+            Err(error) => {
+                eprintln!("Error: {} ({})", error, remote);
+                metrics.error();
+                continue;
+            }
+        };
+
+        match packet.opcode() {
+            Opcode::Register => {
+                let code = loop {
+                    let code = lobby_code(&mut rng);
+                    if !games.contains_key(&code) {
+                        break code;
+                    }
+                };
+
+                let mut packet = Packet::new(Opcode::Code, code.len());
+                packet.push_data(code.as_bytes())?;
+                match socket.send(&packet) {
+                    Ok(_) => {
+                        println!("Game {} registered from {}", code, remote);
+                        games.insert(code, (Player { socket, address: remote, pace, features, settings }, listing));
+                    },
+                    Err(error) => {
+                        eprintln!("Error: {} ({})", error, remote);
+                        metrics.error();
+                    }
+                }
+            },
+            Opcode::Join if code.is_empty() => {
+                match waiting.take() {
+                    Some(host) if !agrees(&host, settings) => {
+                        println!("Rejected {} (different game settings)", remote);
+                        let _ = socket.send(&reject_packet(REJECT_SETTINGS)?);
+                        waiting = Some(host);
+                    },
+                    Some(host) => {
+                        started += 1;
+                        println!("Player joined from {} (2 of 2), game #{} started", remote, started);
+                        start(started, host, Player { socket, address: remote, pace, features, settings }, metrics.clone());
+                    },
+                    None => {
+                        println!("Player joined from {} (1 of 2)", remote);
+                        waiting = Some(Player { socket, address: remote, pace, features, settings });
+                    }
+                }
+            },
+            Opcode::Join => {
+                match games.remove(&code) {
+                    Some((host, listing)) if !agrees(&host, settings) => {
+                        println!("Rejected {} (different game settings)", remote);
+                        let _ = socket.send(&reject_packet(REJECT_SETTINGS)?);
+                        games.insert(code, (host, listing));
+                    },
+                    Some((host, _)) => {
+                        started += 1;
+                        println!("Game {} joined from {}, game #{} started", code, remote, started);
+                        start(started, host, Player { socket, address: remote, pace, features, settings }, metrics.clone());
+                    },
+                    None => {
+                        let _ = socket.send(&reject_packet(REJECT_CODE)?);
+                    }
+                }
+            },
+            Opcode::Discover => {
+                for (code, (_, listing)) in &games {
+                    match listing {
+                        Some(listing) => {
+                            let listing = Listing { code: code.clone(), ..listing.clone() };
+                            match listing.encode().and_then(|packet| socket.send(&packet)) {
+                                Ok(_) => {},
+                                Err(error) => {
+                                    eprintln!("Error: {} ({})", error, remote);
+                                    metrics.error();
+                                    break;
+                                }
+                            }
+                        },
+                        None => {}
+                    }
+                }
+            },
+            _ => {}
+        }
+
+        metrics.set_waiting(games.len() + waiting.is_some() as usize);
+    }
+
+    Ok(())
+}
+
+fn join(stream: TcpStream, secret: &Option<String>) -> Result<Option<(Channel, Packet, Option<Listing>)>, SnakeError> {
+    stream.set_read_timeout(Some(JOIN_TIMEOUT))?;
+    let raw = stream.try_clone()?;
+    let mut socket = Channel::new(accept(stream)?);
+    let mut listing = None;
+    let mut packet = socket.recv()?;
+    match &packet {
+        Some(announce) if announce.opcode() == Opcode::Announce => {
+            listing = Some(Listing::decode(announce)?);
+            packet = socket.recv()?;
+        },
+        _ => {}
+    }
+
+    match packet {
+        Some(packet) if packet.opcode() == Opcode::Join || packet.opcode() == Opcode::Register || packet.opcode() == Opcode::Discover => {
+            if !check_secret(&mut socket, secret, &packet)? {
+                let _ = socket.send(&reject_packet(REJECT_SECRET)?);
+                return Ok(None);
+            }
+
+            raw.set_read_timeout(Some(RELAY_TIMEOUT))?;
+            Ok(Some((socket, packet, listing)))
+        },
+        _ => Ok(None)
+    }
+}
+
+fn start(number: usize, host: Player, guest: Player, metrics: Arc<Metrics>) {
+    metrics.game_started();
+    spawn(move || {
+        match relay([host.socket, guest.socket], [host.address, guest.address], host.pace, [host.features, guest.features], &metrics) {
+            Ok(_) => {
+                println!("Game #{} ended", number);
+            },
+            Err(error) => {
+                eprintln!("Game #{} ended ({})", number, error);
+                metrics.error();
+            }
+        }
+
+        metrics.game_ended();
+    });
+}
+
+fn lobby_code(rng: &mut Rng) -> String {
+    let mut code = String::with_capacity(CODE_LENGTH);
+    for _ in 0..CODE_LENGTH {
+        code.push(CODE_CHARS[rng.next() as usize % CODE_CHARS.len()] as char);
+    }
+
+    code
+}
+
+fn relay(mut sockets: [Channel; 2], addresses: [SocketAddr; 2], pace: Duration, offers: [Option<u32>; 2], metrics: &Metrics) -> Result<(), SnakeError> {
+    let seed = time_seed();
+    let features = offers.iter().fold(FEATURES, |features, offered| features & offered.unwrap_or(LEGACY_FEATURES));
+    for (id, socket) in sockets.iter_mut().enumerate() {
+        socket.send(&welcome_packet(HOST_ID + id, 2, false, pace, seed, offers[id].map(|_| features))?)?;
+    }
+
+    loop {
+        for id in 0..2 {
+            let packet = match sockets[id].recv() {
+                Ok(Some(packet)) => packet,
+                Ok(None) => {
+                    continue;
+                },
+                Err(error) => {
+                    let _ = sockets[1 - id].send(&goodbye_packet(HOST_ID + id)?);
+                    return match error {
+                        SnakeError::Disconnected => Ok(()),
+                        error => Err(error)
+                    };
+                }
+            };
+
+            if packet.opcode() == Opcode::Rendezvous {
+                sockets[id].send(&address_packet(addresses[1 - id])?)?;
+                continue;
+            }
+
+            sockets[1 - id].send(&packet)?;
+            metrics.packet_relayed();
+        }
+    }
+}
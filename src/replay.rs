@@ -0,0 +1,237 @@
+use std::{
+    fs::{
+        read, read_to_string, write
+    },
+    time::Duration
+};
+
+use crate::{
+    config::GameConfig,
+    direction::Direction,
+    error::{
+        ProtocolError, SnakeError
+    },
+    game::{
+        GameMode, SnakeGame
+    },
+    level::is_valid_size,
+    packet::PacketReader,
+    util
+};
+
+pub const REPLAY_MAGIC: &[u8; 8] = b"SNAKEREP";
+pub const REPLAY_VERSION: u16 = 1;
+
+const FIELD_WIDTH: u8 = 0x01;
+const FIELD_HEIGHT: u8 = 0x02;
+const FIELD_WALLS: u8 = 0x03;
+const FIELD_OBSTACLES: u8 = 0x04;
+const FIELD_GROWTH: u8 = 0x05;
+const FIELD_PACE: u8 = 0x06;
+const FIELD_START_LENGTH: u8 = 0x07;
+const FIELD_TIME_LIMIT: u8 = 0x08;
+const FIELD_ZEN: u8 = 0x09;
+const FIELD_LEVEL: u8 = 0x0a;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    version: u16,
+    seed: u64,
+    width: usize,
+    height: usize,
+    walls: bool,
+    obstacles: usize,
+    growth: usize,
+    pace: Duration,
+    start_length: usize,
+    time_limit: Option<Duration>,
+    zen: bool,
+    level: Option<String>,
+    ticks: u64,
+    inputs: Vec<(u64, usize, Direction)>
+}
+
+impl Replay {
+    pub fn new(config: &GameConfig, ticks: u64, inputs: Vec<(u64, usize, Direction)>) -> Result<Self, SnakeError> {
+        let level = match &config.level {
+            Some(path) => Some(read_to_string(path)?),
+            None => None
+        };
+
+        Ok(Replay {
+            version: REPLAY_VERSION,
+            seed: config.seed,
+            width: config.width,
+            height: config.height,
+            walls: config.walls,
+            obstacles: config.obstacles,
+            growth: config.growth,
+            pace: config.pace,
+            start_length: config.start_length,
+            time_limit: config.time_limit,
+            zen: config.zen,
+            level,
+            ticks,
+            inputs
+        })
+    }
+
+    pub fn load(path: &str) -> Result<Self, SnakeError> {
+        Replay::decode(&read(path)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), SnakeError> {
+        write(path, self.encode())?;
+        Ok(())
+    }
+
+    pub fn apply(&self, config: &mut GameConfig) {
+        config.seed = self.seed;
+        config.width = self.width;
+        config.height = self.height;
+        config.walls = self.walls;
+        config.obstacles = self.obstacles;
+        config.growth = self.growth;
+        config.pace = self.pace;
+        config.start_length = self.start_length;
+        config.time_limit = self.time_limit;
+        config.zen = self.zen;
+        config.level = None;
+        config.bot_cmd = None;
+        config.record = None;
+        config.submit_score = None;
+    }
+
+    pub fn level(&self) -> Option<&str> {
+        self.level.as_deref()
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn inputs(&self) -> &Vec<(u64, usize, Direction)> {
+        &self.inputs
+    }
+
+    #[allow(clippy::single_match)]
+    fn encode(&self) -> Vec<u8> {
+        let mut fields = vec![
+            (FIELD_WIDTH, (self.width as u16).to_be_bytes().to_vec()),
+            (FIELD_HEIGHT, (self.height as u16).to_be_bytes().to_vec()),
+            (FIELD_WALLS, vec![self.walls as u8]),
+            (FIELD_OBSTACLES, vec![self.obstacles as u8]),
+            (FIELD_GROWTH, vec![self.growth as u8]),
+            (FIELD_PACE, (self.pace.as_millis() as u16).to_be_bytes().to_vec()),
+            (FIELD_START_LENGTH, (self.start_length as u16).to_be_bytes().to_vec()),
+            (FIELD_ZEN, vec![self.zen as u8])
+        ];
+
+        match self.time_limit {
+            Some(limit) => {
+                fields.push((FIELD_TIME_LIMIT, (limit.as_secs() as u16).to_be_bytes().to_vec()));
+            },
+            None => {}
+        }
+
+        match &self.level {
+            Some(level) => {
+                fields.push((FIELD_LEVEL, level.as_bytes().to_vec()));
+            },
+            None => {}
+        }
+
+        let mut data = REPLAY_MAGIC.to_vec();
+        data.extend_from_slice(&self.version.to_be_bytes());
+        data.extend_from_slice(&self.seed.to_be_bytes());
+        data.push(fields.len() as u8);
+        for (tag, value) in fields {
+            data.push(tag);
+            data.extend_from_slice(&(value.len() as u32).to_be_bytes());
+            data.extend_from_slice(&value);
+        }
+
+        data.extend_from_slice(&self.ticks.to_be_bytes());
+        data.extend_from_slice(&(self.inputs.len() as u32).to_be_bytes());
+        for (tick_id, id, direction) in &self.inputs {
+            data.extend_from_slice(&tick_id.to_be_bytes());
+            data.push(*id as u8);
+            data.push(*direction as u8);
+        }
+
+        data
+    }
+
+    fn decode(data: &[u8]) -> Result<Self, SnakeError> {
+        if data.len() < REPLAY_MAGIC.len() || &data[..REPLAY_MAGIC.len()] != REPLAY_MAGIC {
+            return Err(SnakeError::BadReplay("not a replay file".into()));
+        }
+
+        let mut reader = PacketReader::new(&data[REPLAY_MAGIC.len()..]);
+        let version = reader.read_u16().map_err(truncated)?;
+        if version == 0 || version > REPLAY_VERSION {
+            return Err(SnakeError::BadReplay(format!("format version {} is not supported", version)));
+        }
+
+        let seed = reader.read_u64().map_err(truncated)?;
+        let mut replay = Replay::new(&GameConfig::normal(), 0, Vec::new())?;
+        replay.version = version;
+        replay.seed = seed;
+
+        let count = reader.read_u8().map_err(truncated)?;
+        for _ in 0..count {
+            let tag = reader.read_u8().map_err(truncated)?;
+            let size = reader.read_u32().map_err(truncated)? as usize;
+            let value = reader.read_bytes(size).map_err(truncated)?;
+            let number = value.iter().fold(0u64, |number, byte| (number << 8) | *byte as u64);
+            match tag {
+                FIELD_WIDTH => replay.width = number as usize,
+                FIELD_HEIGHT => replay.height = number as usize,
+                FIELD_WALLS => replay.walls = number != 0,
+                FIELD_OBSTACLES => replay.obstacles = number as usize,
+                FIELD_GROWTH => replay.growth = number as usize,
+                FIELD_PACE => replay.pace = Duration::from_millis(number),
+                FIELD_START_LENGTH => replay.start_length = number as usize,
+                FIELD_TIME_LIMIT => replay.time_limit = Some(Duration::from_secs(number)),
+                FIELD_ZEN => replay.zen = number != 0,
+                FIELD_LEVEL => replay.level = Some(String::from_utf8_lossy(value).into_owned()),
+                _ => {}
+            }
+        }
+
+        replay.ticks = reader.read_u64().map_err(truncated)?;
+        let count = reader.read_u32().map_err(truncated)?;
+        for _ in 0..count {
+            let tick_id = reader.read_u64().map_err(truncated)?;
+            let id = reader.read_u8().map_err(truncated)? as usize;
+            let direction = Direction::from(reader.read_u8().map_err(truncated)?);
+            match direction {
+                Some(direction) if id == 0 && tick_id <= replay.ticks => {
+                    replay.inputs.push((tick_id, id, direction));
+                },
+                _ => {
+                    return Err(SnakeError::BadReplay("bad input".into()));
+                }
+            }
+        }
+
+        if !is_valid_size(replay.width, replay.height) || replay.pace.is_zero() || replay.growth == 0 || replay.start_length == 0 {
+            return Err(SnakeError::BadReplay("bad settings".into()));
+        }
+
+        Ok(replay)
+    }
+}
+
+pub fn watch(path: &str, mut config: GameConfig) -> Result<(), SnakeError> {
+    let replay = Replay::load(path)?;
+    util::seed(replay.seed);
+    replay.apply(&mut config);
+
+    let mut game = SnakeGame::new(GameMode::Replay(replay), config)?;
+    game.play()
+}
+
+fn truncated(_: ProtocolError) -> SnakeError {
+    SnakeError::BadReplay("truncated file".into())
+}
@@ -0,0 +1,38 @@
+use crate::target::TargetKind;
+
+pub const TARGET_POINTS: u64 = 10;
+pub const GOLDEN_POINTS: u64 = 50;
+pub const LENGTH_BONUS_STEP: usize = 5;
+pub const LENGTH_BONUS_POINTS: u64 = 5;
+pub const CRASH_PENALTY: u64 = 20;
+
+pub struct Score {
+    points: u64
+}
+
+impl Score {
+    pub fn new() -> Self {
+        Score { points: 0 }
+    }
+
+    pub fn restore(points: u64) -> Self {
+        Score { points }
+    }
+
+    pub fn points(&self) -> u64 {
+        self.points
+    }
+
+    pub fn target_eaten(&mut self, kind: TargetKind, size: usize) {
+        let points = match kind {
+            TargetKind::Normal => TARGET_POINTS,
+            TargetKind::Golden => GOLDEN_POINTS
+        };
+
+        self.points += points + (size / LENGTH_BONUS_STEP) as u64 * LENGTH_BONUS_POINTS;
+    }
+
+    pub fn crashed(&mut self) {
+        self.points = self.points.saturating_sub(CRASH_PENALTY);
+    }
+}
@@ -0,0 +1,50 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+#[cfg(unix)]
+pub fn catch_interrupt() {
+    const SIGINT: i32 = 2;
+
+    extern "C" {
+        fn signal(signum: i32, handler: extern "C" fn(i32)) -> usize;
+    }
+
+    extern "C" fn handler(_: i32) {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+    }
+
+    unsafe {
+        signal(SIGINT, handler);
+    }
+}
+
+#[cfg(windows)]
+pub fn catch_interrupt() {
+    const CTRL_C_EVENT: u32 = 0;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn SetConsoleCtrlHandler(handler: extern "system" fn(u32) -> i32, add: i32) -> i32;
+    }
+
+    extern "system" fn handler(event: u32) -> i32 {
+        if event != CTRL_C_EVENT {
+            return 0;
+        }
+
+        INTERRUPTED.store(true, Ordering::SeqCst);
+        1
+    }
+
+    unsafe {
+        SetConsoleCtrlHandler(handler, 1);
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn catch_interrupt() {}
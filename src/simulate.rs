@@ -0,0 +1,52 @@
+use crate::{
+    config::GameConfig,
+    error::SnakeError,
+    game::{
+        GameMode, GameResult, SnakeGame, TickOutcome
+    },
+    util
+};
+
+pub const MAX_GAMES: usize = 1000;
+const MAX_TICKS: u64 = 10000;
+
+pub fn simulate(mut config: GameConfig, seed: u64, games: usize) -> Result<(), SnakeError> {
+    let mut wins = vec![0; config.players];
+    let mut draws = 0;
+    println!("Seed {}", seed);
+    for game in 0..games {
+        config.seed = seed.wrapping_add(game as u64);
+        util::seed(config.seed);
+
+        let (result, ticks) = play(config.clone())?;
+        match result {
+            GameResult::Winner(id, reason) => {
+                wins[id] += 1;
+                println!("Game {}: P{} won after {} ticks ({})", game + 1, id + 1, ticks, reason);
+            },
+            result => {
+                draws += 1;
+                println!("Game {}: draw after {} ticks ({})", game + 1, ticks, result.reason());
+            }
+        }
+    }
+
+    let mut summary: Vec<String> = wins.iter().enumerate().map(|(id, wins)| format!("P{} {} wins", id + 1, wins)).collect();
+    summary.push(format!("{} draws", draws));
+    println!("{}", summary.join("  "));
+    Ok(())
+}
+
+fn play(config: GameConfig) -> Result<(GameResult, u64), SnakeError> {
+    let mut game = SnakeGame::headless(GameMode::Local, config)?;
+    for tick in 1..=MAX_TICKS {
+        match game.step(&game.ai_inputs())? {
+            TickOutcome::Running => {},
+            TickOutcome::Over(result) => {
+                return Ok((result, tick));
+            }
+        }
+    }
+
+    Ok((GameResult::Draw("tick limit".into()), MAX_TICKS))
+}
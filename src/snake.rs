@@ -1,25 +1,109 @@
-use crate::{board::BOARD_SIZE, direction::Direction};
+use std::collections::VecDeque;
 
+use crate::{
+    board::Board,
+    direction::Direction,
+    level::Level
+};
+
+pub const MAX_QUEUED_TURNS: usize = 3;
+
+#[derive(Clone)]
 pub struct Snake {
-    body: Vec<(usize, usize)>,
-    direction: Direction
+    body: VecDeque<(usize, usize)>,
+    direction: Direction,
+    turns: VecDeque<Direction>,
+    pending: usize
 }
 
 impl Snake {
     pub fn new(head: (usize, usize), direction: Direction) -> Self {
-        Snake { body: vec![head], direction }
+        Snake { body: VecDeque::from([head]), direction, turns: VecDeque::new(), pending: 0 }
+    }
+
+    pub fn spawn(board: &Board, head: (usize, usize), direction: Direction, length: usize, walls: bool) -> Option<Self> {
+        if board.value(head) != ' ' {
+            return None;
+        }
+
+        let mut body = VecDeque::from([head]);
+        let mut heading = direction.opposite();
+        while body.len() < length {
+            let last = body[body.len() - 1];
+            let [side, other] = heading.perpendicular();
+            let mut next = None;
+            for turn in [heading, side, other] {
+                match Snake::step(last, turn, board.width(), board.height(), walls) {
+                    Some(pos) if board.value(pos) == ' ' && !body.contains(&pos) => {
+                        next = Some((pos, turn));
+                        break;
+                    },
+                    _ => {}
+                }
+            }
+
+            match next {
+                Some((pos, turn)) => {
+                    body.push_back(pos);
+                    heading = turn;
+                },
+                None => {
+                    return None;
+                }
+            }
+        }
+
+        Some(Snake { body, direction, turns: VecDeque::new(), pending: 0 })
+    }
+
+    pub fn restore(body: VecDeque<(usize, usize)>, direction: Direction, turns: VecDeque<Direction>, pending: usize) -> Self {
+        if body.is_empty() {
+            panic!("empty body [Snake::restore()]");
+        }
+
+        Snake { body, direction, turns, pending }
     }
 
     pub fn head(&self) -> (usize, usize) {
         self.body[0]
     }
 
+    pub fn body(&self) -> &VecDeque<(usize, usize)> {
+        &self.body
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn turns(&self) -> &VecDeque<Direction> {
+        &self.turns
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+
     pub fn tail(&self) -> (usize, usize) {
         self.body[self.body.len() - 1]
     }
 
     pub fn grow(&mut self, tail: (usize, usize)) {
-        self.body.push(tail);
+        self.body.push_back(tail);
+    }
+
+    pub fn feed(&mut self, amount: usize) {
+        self.pending += amount;
+    }
+
+    pub fn grow_pending(&mut self, tail: (usize, usize)) -> bool {
+        if self.pending == 0 {
+            return false;
+        }
+
+        self.pending -= 1;
+        self.grow(tail);
+        true
     }
 
     pub fn size(&self) -> usize {
@@ -27,43 +111,74 @@ impl Snake {
     }
 
     pub fn control(&mut self, direction: Direction) {
-        match self.direction {
-            Direction::Right => {
-                if direction != Direction::Left {
-                    self.direction = direction;
-                }
-            },
-            Direction::Down => {
-                if direction != Direction::Up {
-                    self.direction = direction;
-                }
+        let last = match self.turns.back() {
+            Some(last) => *last,
+            None => self.direction
+        };
+
+        if direction == last || direction == last.opposite() || self.turns.len() >= MAX_QUEUED_TURNS {
+            return;
+        }
+
+        self.turns.push_back(direction);
+    }
+
+    fn turn(&mut self) {
+        match self.turns.pop_front() {
+            Some(direction) if direction != self.direction.opposite() => {
+                self.direction = direction;
             },
-            Direction::Left => {
-                if direction != Direction::Right {
-                    self.direction = direction;
+            _ => {}
+        }
+    }
+
+    pub fn update(&mut self, width: usize, height: usize, walls: bool, level: &Level) -> bool {
+        self.turn();
+        let head = match Snake::step(self.head(), self.direction, width, height, walls) {
+            Some(head) => head,
+            None => {
+                return false;
+            }
+        };
+
+        let head = match level.twin(head) {
+            Some(twin) => {
+                match Snake::step(twin, self.direction, width, height, walls) {
+                    Some(head) => head,
+                    None => {
+                        return false;
+                    }
                 }
             },
-            Direction::Up => {
-                if direction != Direction::Down {
-                    self.direction = direction;
-                }
-            }
-        }
+            None => head
+        };
+
+        self.body.pop_back();
+        self.body.push_front(head);
+
+        true
     }
 
-    pub fn update(&mut self) {
-        for i in 0..self.body.len() {
-            let i = self.body.len() - i - 1;
-            if i > 0 {
-                self.body[i] = self.body[i - 1];
-            } else {
-                let head = self.body[i];
-                self.body[i] = match self.direction {
-                    Direction::Right => (head.0, (head.1 + 1) % BOARD_SIZE),
-                    Direction::Down => ((head.0 + 1) % BOARD_SIZE, head.1),
-                    Direction::Left => (head.0, if head.1 > 0 { head.1 - 1 } else { BOARD_SIZE - 1 }),
-                    Direction::Up => (if head.0 > 0 { head.0 - 1 } else { BOARD_SIZE - 1 }, head.1)
-                };
+    fn step(pos: (usize, usize), direction: Direction, width: usize, height: usize, walls: bool) -> Option<(usize, usize)> {
+        if walls {
+            let pos = match direction {
+                Direction::Right => (pos.0, pos.1 + 1),
+                Direction::Down => (pos.0 + 1, pos.1),
+                Direction::Left => (pos.0, pos.1.wrapping_sub(1)),
+                Direction::Up => (pos.0.wrapping_sub(1), pos.1)
+            };
+
+            if pos.0 >= height || pos.1 >= width {
+                return None;
+            }
+
+            Some(pos)
+        } else {
+            match direction {
+                Direction::Right => Some((pos.0, (pos.1 + 1) % width)),
+                Direction::Down => Some(((pos.0 + 1) % height, pos.1)),
+                Direction::Left => Some((pos.0, if pos.1 > 0 { pos.1 - 1 } else { width - 1 })),
+                Direction::Up => Some((if pos.0 > 0 { pos.0 - 1 } else { height - 1 }, pos.1))
             }
         }
     }
@@ -0,0 +1,120 @@
+use std::net::SocketAddr;
+
+use crate::{
+    board::Board,
+    config::GameConfig,
+    error::{
+        ProtocolError, SnakeError
+    },
+    glyphs::Glyphs,
+    net::{
+        auth_response, join_packet, open, rejection, secret_nonce, secret_response, Channel
+    },
+    packet::{
+        Opcode, Packet, PacketReader
+    },
+    terminal::{
+        terminal, Terminal
+    }
+};
+
+pub fn snapshot(board: &Board, points: &[u64]) -> Result<Packet, SnakeError> {
+    let data = board.encode();
+    let mut packet = Packet::new(Opcode::Snapshot, data.len() + 8 * points.len());
+    packet.push_data(&data)?;
+    for points in points {
+        packet.push_data(&points.to_be_bytes())?;
+    }
+
+    Ok(packet)
+}
+
+#[allow(clippy::identity_op)]
+fn decode_snapshot(data: &[u8]) -> Result<(Board, Vec<u64>), ProtocolError> {
+    if data.len() < 4 {
+        return Err(ProtocolError::Truncated);
+    }
+
+    let width = ((data[0] as usize) << 8) | ((data[1] as usize) << 0);
+    let height = ((data[2] as usize) << 8) | ((data[3] as usize) << 0);
+    if data.len() < 4 + width * height {
+        return Err(ProtocolError::Truncated);
+    }
+
+    let (data, points) = data.split_at(4 + width * height);
+    if points.len() % 8 != 0 {
+        return Err(ProtocolError::Malformed("snapshot scores"));
+    }
+
+    let board = Board::decode(data)?;
+    let mut scores = Vec::new();
+    let mut reader = PacketReader::new(points);
+    while !reader.is_empty() {
+        scores.push(reader.read_u64()?);
+    }
+
+    Ok((board, scores))
+}
+
+pub fn spectate(remote: SocketAddr, config: &GameConfig) -> Result<(), SnakeError> {
+    let glyphs = if config.emoji { Glyphs::emoji() } else { Glyphs::load()? };
+    let (_, mut socket) = open(remote, config)?;
+    let nonce = secret_nonce(&config.secret);
+    socket.send(&join_packet(Opcode::Spectate, "", config.pace, 0, &nonce)?)?;
+
+    let mut terminal = terminal();
+    terminal.hide_cursor();
+    let result = watch(&mut socket, terminal.as_mut(), config, &nonce, &glyphs);
+    terminal.show_cursor();
+
+    match result {
+        Err(SnakeError::Disconnected) => {
+            println!("The game has ended");
+            Ok(())
+        },
+        _ => result
+    }
+}
+
+#[allow(clippy::single_match)]
+fn watch(socket: &mut Channel, terminal: &mut dyn Terminal, config: &GameConfig, nonce: &[u8], glyphs: &Glyphs) -> Result<(), SnakeError> {
+    let mut proven = config.secret.is_none();
+    loop {
+        match socket.recv()? {
+            Some(packet) => {
+                match packet.opcode() {
+                    Opcode::Snapshot if !proven => {
+                        return Err(SnakeError::BadSecret);
+                    },
+                    Opcode::Snapshot => {
+                        let (board, points) = decode_snapshot(packet.data())?;
+
+                        let mut frame = board.draw(config.color && terminal.has_color(), glyphs);
+                        let scores: Vec<String> = points.iter().enumerate().map(|(i, points)| format!("P{}: {}", i + 1, points)).collect();
+                        frame.push_str(&scores.join("  "));
+                        frame.push('\n');
+                        terminal.draw(&frame);
+                    },
+                    Opcode::AuthChallenge => {
+                        socket.send(&auth_response(&packet, &config.password)?)?;
+                    },
+                    Opcode::SecretChallenge => {
+                        socket.send(&secret_response(&packet, nonce, &config.secret)?)?;
+                        proven = true;
+                    },
+                    Opcode::Goodbye if packet.data().len() > 1 => {
+                        return Err(rejection(&packet));
+                    },
+                    Opcode::Kick if packet.data().first() == Some(&1) => {
+                        return Err(SnakeError::Banned);
+                    },
+                    Opcode::Kick => {
+                        return Err(SnakeError::Kicked);
+                    },
+                    _ => {}
+                }
+            },
+            None => {}
+        }
+    }
+}
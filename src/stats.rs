@@ -0,0 +1,48 @@
+use crate::{
+    direction::Direction,
+    snake::Snake
+};
+
+pub struct Stats {
+    eaten: u64,
+    ticks: u64,
+    max_length: usize,
+    turns: u64,
+    direction: Direction
+}
+
+impl Stats {
+    pub fn new(snake: &Snake) -> Self {
+        Stats { eaten: 0, ticks: 0, max_length: snake.size(), turns: 0, direction: snake.direction() }
+    }
+
+    pub fn eaten(&self) -> u64 {
+        self.eaten
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn max_length(&self) -> usize {
+        self.max_length
+    }
+
+    pub fn turns(&self) -> u64 {
+        self.turns
+    }
+
+    pub fn tick(&mut self, snake: &Snake) {
+        self.ticks += 1;
+        self.max_length = self.max_length.max(snake.size());
+        if snake.direction() != self.direction {
+            self.direction = snake.direction();
+            self.turns += 1;
+        }
+    }
+
+    pub fn target_eaten(&mut self, size: usize) {
+        self.eaten += 1;
+        self.max_length = self.max_length.max(size);
+    }
+}
@@ -0,0 +1,207 @@
+use std::{
+    collections::VecDeque,
+    time::{
+        Duration, Instant
+    }
+};
+
+use crate::{
+    error::{
+        ProtocolError, SnakeError
+    },
+    net::{
+        id_packet, Network, PEER_TIMEOUT, POLL_TIMEOUT
+    },
+    packet::{
+        Opcode, Packet, PacketReader
+    }
+};
+
+const HISTORY_SIZE: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SyncMode {
+    Lockstep,
+    FreeRunning
+}
+
+impl SyncMode {
+    pub fn parse(name: &str) -> Option<SyncMode> {
+        match name {
+            "lockstep" => Some(SyncMode::Lockstep),
+            "free" => Some(SyncMode::FreeRunning),
+            _ => None
+        }
+    }
+
+    pub fn strategy(&self) -> Box<dyn SyncStrategy> {
+        match self {
+            SyncMode::Lockstep => Box::new(Lockstep),
+            SyncMode::FreeRunning => Box::new(FreeRunning::default())
+        }
+    }
+}
+
+pub trait SyncStrategy {
+    fn synchronize(&mut self, network: &mut Network, tick_id: u64, hash: u64, stalled: &mut dyn FnMut(Duration)) -> Result<Vec<Packet>, SnakeError>;
+
+    fn release(&mut self, network: &mut Network, tick_id: u64, hash: u64) -> Result<(), SnakeError>;
+}
+
+pub struct Lockstep;
+
+impl SyncStrategy for Lockstep {
+    fn synchronize(&mut self, network: &mut Network, tick_id: u64, hash: u64, stalled: &mut dyn FnMut(Duration)) -> Result<Vec<Packet>, SnakeError> {
+        let started = Instant::now();
+        let mut packets = Vec::new();
+        if !network.is_host() && !network.is_authoritative() {
+            network.send(&sync_packet(tick_id, hash)?)?;
+        }
+
+        if !network.is_host() {
+            network.flush()?;
+        }
+
+        let mut i = 0;
+        while i < network.peer_count() {
+            let mut heard = Instant::now();
+            loop {
+                network.ping()?;
+
+                let count = network.peer_count();
+                let packet = match network.receive(i, POLL_TIMEOUT)? {
+                    Some(packet) => packet,
+                    None => {
+                        if heard.elapsed() >= PEER_TIMEOUT {
+                            network.drop_peer(i);
+                            return Err(SnakeError::ConnectionLost);
+                        }
+
+                        stalled(started.elapsed());
+                        continue;
+                    }
+                };
+
+                heard = Instant::now();
+                match packet.opcode() {
+                    Opcode::Ping | Opcode::Pong => {},
+                    Opcode::Sync => {
+                        let (synced, remote) = read_sync(&packet)?;
+                        if synced == tick_id {
+                            if remote != hash {
+                                network.report_desync(tick_id);
+                            }
+
+                            i += 1;
+                            break;
+                        }
+                    },
+                    Opcode::StateSnapshot if network.is_authoritative() => {
+                        packets.push(packet);
+                        i += 1;
+                        break;
+                    },
+                    _ => {
+                        packets.push(packet);
+                    }
+                }
+
+                if network.peer_count() != count {
+                    break;
+                }
+            }
+        }
+
+        Ok(packets)
+    }
+
+    fn release(&mut self, network: &mut Network, tick_id: u64, hash: u64) -> Result<(), SnakeError> {
+        if network.is_host() && !network.is_authoritative() {
+            network.send(&sync_packet(tick_id, hash)?)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct FreeRunning {
+    hashes: VecDeque<(u64, u64)>,
+    remote: Vec<(u64, u64)>
+}
+
+impl SyncStrategy for FreeRunning {
+    fn synchronize(&mut self, network: &mut Network, tick_id: u64, hash: u64, _stalled: &mut dyn FnMut(Duration)) -> Result<Vec<Packet>, SnakeError> {
+        self.hashes.retain(|(tick, _)| *tick < tick_id);
+        self.hashes.push_back((tick_id, hash));
+        if self.hashes.len() > HISTORY_SIZE {
+            self.hashes.pop_front();
+        }
+
+        network.send(&sync_packet(tick_id, hash)?)?;
+
+        let mut packets = Vec::new();
+        let mut i = 0;
+        while i < network.peer_count() {
+            let packet = match network.receive(i, Duration::ZERO)? {
+                Some(packet) => packet,
+                None => {
+                    if network.silence(i) >= PEER_TIMEOUT {
+                        network.drop_peer(i);
+                        return Err(SnakeError::ConnectionLost);
+                    }
+
+                    i += 1;
+                    continue;
+                }
+            };
+
+            match packet.opcode() {
+                Opcode::Ping | Opcode::Pong => {},
+                Opcode::Sync => {
+                    self.remote.push(read_sync(&packet)?);
+                },
+                _ => {
+                    packets.push(packet);
+                }
+            }
+        }
+
+        let hashes = &self.hashes;
+        self.remote.retain(|(tick, remote)| {
+            if *tick > tick_id {
+                return true;
+            }
+
+            match hashes.iter().find(|(own, _)| own == tick) {
+                Some((_, own)) if remote != own => {
+                    network.report_desync(*tick);
+                },
+                _ => {}
+            }
+
+            false
+        });
+
+        if self.remote.len() > HISTORY_SIZE {
+            self.remote.drain(..self.remote.len() - HISTORY_SIZE);
+        }
+
+        Ok(packets)
+    }
+
+    fn release(&mut self, _network: &mut Network, _tick_id: u64, _hash: u64) -> Result<(), SnakeError> {
+        Ok(())
+    }
+}
+
+fn sync_packet(tick_id: u64, hash: u64) -> Result<Packet, SnakeError> {
+    let mut packet = id_packet(Opcode::Sync, tick_id)?;
+    packet.push_data(&hash.to_be_bytes())?;
+    Ok(packet)
+}
+
+fn read_sync(packet: &Packet) -> Result<(u64, u64), ProtocolError> {
+    let mut reader = PacketReader::new(packet.data());
+    Ok((reader.read_u64()?, reader.read_u64()?))
+}
@@ -0,0 +1,62 @@
+use crate::board::{
+    GOLDEN_CHAR, TARGET_CHAR
+};
+
+pub const GOLDEN_ODDS: u64 = 8;
+pub const GOLDEN_GROWTH: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TargetKind {
+    Normal,
+    Golden
+}
+
+impl TargetKind {
+    pub fn from(value: u8) -> Option<TargetKind> {
+        match value {
+            0x00 => Some(TargetKind::Normal),
+            0x01 => Some(TargetKind::Golden),
+            _ => None
+        }
+    }
+
+    pub fn roll(value: u64) -> TargetKind {
+        match value % GOLDEN_ODDS {
+            0 => TargetKind::Golden,
+            _ => TargetKind::Normal
+        }
+    }
+
+    pub fn growth(&self) -> usize {
+        match self {
+            TargetKind::Normal => 1,
+            TargetKind::Golden => GOLDEN_GROWTH
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TargetKind::Normal => "normal",
+            TargetKind::Golden => "golden"
+        }
+    }
+
+    pub fn pixel(&self) -> char {
+        match self {
+            TargetKind::Normal => TARGET_CHAR,
+            TargetKind::Golden => GOLDEN_CHAR
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Target {
+    pub pos: (usize, usize),
+    pub kind: TargetKind
+}
+
+impl Target {
+    pub fn new(pos: (usize, usize), kind: TargetKind) -> Self {
+        Target { pos, kind }
+    }
+}
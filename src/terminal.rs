@@ -0,0 +1,307 @@
+use std::{
+    io::{
+        stdout, Write
+    },
+    mem::take
+};
+
+const RESET_STYLE: &str = "\x1b[0m";
+
+pub trait Terminal {
+    fn hide_cursor(&mut self);
+    fn show_cursor(&mut self);
+    fn draw(&mut self, frame: &str);
+    fn has_color(&self) -> bool;
+    fn bell(&mut self);
+}
+
+pub fn terminal() -> Box<dyn Terminal> {
+    #[cfg(windows)]
+    {
+        if !console::enable_virtual_terminal() {
+            return Box::new(console::ConsoleTerminal::new());
+        }
+    }
+
+    Box::new(AnsiTerminal::new())
+}
+
+pub struct NullTerminal;
+
+impl Terminal for NullTerminal {
+    fn hide_cursor(&mut self) {}
+
+    fn show_cursor(&mut self) {}
+
+    fn draw(&mut self, _frame: &str) {}
+
+    fn has_color(&self) -> bool {
+        false
+    }
+
+    fn bell(&mut self) {}
+}
+
+pub struct AnsiTerminal {
+    previous: Vec<String>
+}
+
+impl AnsiTerminal {
+    pub fn new() -> Self {
+        AnsiTerminal { previous: Vec::new() }
+    }
+
+    fn diff(&self, lines: &[String]) -> String {
+        let mut out = String::new();
+        for (row, line) in lines.iter().enumerate() {
+            let before = match self.previous.get(row) {
+                Some(before) if before == line => {
+                    continue;
+                },
+                Some(before) => before,
+                None => ""
+            };
+
+            if !line.is_ascii() || !before.is_ascii() {
+                out.push_str(&format!("\x1b[{};1H\x1b[2K{}", row + 1, line));
+                continue;
+            }
+
+            let current = split_cells(line);
+            let previous = split_cells(before);
+            let mut cursor = None;
+            for (column, cell) in current.iter().enumerate() {
+                if previous.get(column) == Some(cell) {
+                    continue;
+                }
+
+                if cursor != Some(column) {
+                    out.push_str(&format!("\x1b[{};{}H", row + 1, column + 1));
+                }
+
+                out.push_str(RESET_STYLE);
+                out.push_str(cell);
+                cursor = Some(column + 1);
+            }
+
+            if previous.len() > current.len() {
+                out.push_str(&format!("\x1b[{};{}H{}\x1b[K", row + 1, current.len() + 1, RESET_STYLE));
+            }
+        }
+
+        for row in lines.len()..self.previous.len() {
+            out.push_str(&format!("\x1b[{};1H\x1b[2K", row + 1));
+        }
+
+        out.push_str(&format!("\x1b[{};1H", lines.len() + 1));
+        out
+    }
+}
+
+impl Terminal for AnsiTerminal {
+    fn hide_cursor(&mut self) {
+        self.previous.clear();
+        println!("\x1b[?25l");
+    }
+
+    fn show_cursor(&mut self) {
+        self.previous.clear();
+        println!("\x1b[?25h");
+    }
+
+    fn draw(&mut self, frame: &str) {
+        let lines: Vec<String> = frame.lines().map(String::from).collect();
+        if lines == self.previous {
+            return;
+        }
+
+        let out = if self.previous.is_empty() {
+            format!("\x1b[2J\x1b[1;1H{}\n", lines.join("\n"))
+        } else {
+            self.diff(&lines)
+        };
+
+        let mut stdout = stdout().lock();
+        stdout.write_all(out.as_bytes()).unwrap();
+        stdout.flush().unwrap();
+        self.previous = lines;
+    }
+
+    fn has_color(&self) -> bool {
+        true
+    }
+
+    fn bell(&mut self) {
+        let mut stdout = stdout().lock();
+        stdout.write_all(b"\x07").unwrap();
+        stdout.flush().unwrap();
+    }
+}
+
+fn split_cells(line: &str) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        cell.push(c);
+        if c == '\x1b' {
+            for c in chars.by_ref() {
+                cell.push(c);
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+
+            continue;
+        }
+
+        cells.push(take(&mut cell));
+    }
+
+    cells
+}
+
+#[cfg(windows)]
+mod console {
+    use std::{
+        ffi::c_void,
+        io::{stdout, Write}
+    };
+
+    use super::Terminal;
+
+    const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+    const ENABLE_VIRTUAL_TERMINAL_PROCESSING: u32 = 0x0004;
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct Coord {
+        x: i16,
+        y: i16
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    #[allow(dead_code)]
+    struct SmallRect {
+        left: i16,
+        top: i16,
+        right: i16,
+        bottom: i16
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    #[allow(dead_code)]
+    struct ScreenBufferInfo {
+        size: Coord,
+        cursor_position: Coord,
+        attributes: u16,
+        window: SmallRect,
+        maximum_window_size: Coord
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    #[allow(dead_code)]
+    struct CursorInfo {
+        size: u32,
+        visible: i32
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetStdHandle(handle: u32) -> *mut c_void;
+        fn GetConsoleMode(handle: *mut c_void, mode: *mut u32) -> i32;
+        fn SetConsoleMode(handle: *mut c_void, mode: u32) -> i32;
+        fn GetConsoleScreenBufferInfo(handle: *mut c_void, info: *mut ScreenBufferInfo) -> i32;
+        fn FillConsoleOutputCharacterW(handle: *mut c_void, c: u16, len: u32, coord: Coord, written: *mut u32) -> i32;
+        fn FillConsoleOutputAttribute(handle: *mut c_void, attr: u16, len: u32, coord: Coord, written: *mut u32) -> i32;
+        fn SetConsoleCursorPosition(handle: *mut c_void, coord: Coord) -> i32;
+        fn GetConsoleCursorInfo(handle: *mut c_void, info: *mut CursorInfo) -> i32;
+        fn SetConsoleCursorInfo(handle: *mut c_void, info: *const CursorInfo) -> i32;
+    }
+
+    pub fn enable_virtual_terminal() -> bool {
+        unsafe {
+            let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+            let mut mode = 0;
+            if GetConsoleMode(handle, &mut mode) == 0 {
+                return false;
+            }
+
+            SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0
+        }
+    }
+
+    pub struct ConsoleTerminal {
+        handle: *mut c_void,
+        previous: String
+    }
+
+    impl ConsoleTerminal {
+        pub fn new() -> Self {
+            let handle = unsafe { GetStdHandle(STD_OUTPUT_HANDLE) };
+            ConsoleTerminal { handle, previous: String::new() }
+        }
+
+        fn set_cursor_visible(&mut self, visible: bool) {
+            stdout().flush().unwrap();
+            unsafe {
+                let mut info = CursorInfo::default();
+                if GetConsoleCursorInfo(self.handle, &mut info) != 0 {
+                    info.visible = visible as i32;
+                    SetConsoleCursorInfo(self.handle, &info);
+                }
+            }
+        }
+
+        fn clear(&mut self) {
+            stdout().flush().unwrap();
+            unsafe {
+                let mut info = ScreenBufferInfo::default();
+                if GetConsoleScreenBufferInfo(self.handle, &mut info) == 0 {
+                    return;
+                }
+
+                let origin = Coord::default();
+                let len = info.size.x as u32 * info.size.y as u32;
+                let mut written = 0;
+                FillConsoleOutputCharacterW(self.handle, b' ' as u16, len, origin, &mut written);
+                FillConsoleOutputAttribute(self.handle, info.attributes, len, origin, &mut written);
+                SetConsoleCursorPosition(self.handle, origin);
+            }
+        }
+    }
+
+    impl Terminal for ConsoleTerminal {
+        fn hide_cursor(&mut self) {
+            self.previous.clear();
+            self.set_cursor_visible(false);
+        }
+
+        fn show_cursor(&mut self) {
+            self.previous.clear();
+            self.set_cursor_visible(true);
+        }
+
+        fn draw(&mut self, frame: &str) {
+            if frame == self.previous {
+                return;
+            }
+
+            self.clear();
+            println!("{}", frame);
+            self.previous = frame.into();
+        }
+
+        fn has_color(&self) -> bool {
+            false
+        }
+
+        fn bell(&mut self) {
+            print!("\x07");
+            let _ = stdout().flush();
+        }
+    }
+}
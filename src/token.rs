@@ -0,0 +1,115 @@
+use std::net::{
+    IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr
+};
+
+use crate::packet::PacketReader;
+
+const TOKEN_VERSION: u8 = 1;
+const BASE32: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const WALLS: u8 = 1;
+const PASSWORD: u8 = 2;
+const SECRET: u8 = 4;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct JoinToken {
+    pub address: SocketAddr,
+    pub width: usize,
+    pub height: usize,
+    pub walls: bool,
+    pub password: Option<String>,
+    pub secret: Option<String>
+}
+
+impl JoinToken {
+    pub fn encode(&self) -> String {
+        let mut data = vec![TOKEN_VERSION];
+        match self.address.ip() {
+            IpAddr::V4(ip) => {
+                data.push(4);
+                data.extend_from_slice(&ip.octets());
+            },
+            IpAddr::V6(ip) => {
+                data.push(6);
+                data.extend_from_slice(&ip.octets());
+            }
+        }
+
+        data.extend_from_slice(&self.address.port().to_be_bytes());
+        data.extend_from_slice(&(self.width as u16).to_be_bytes());
+        data.extend_from_slice(&(self.height as u16).to_be_bytes());
+
+        let flags = if self.walls { WALLS } else { 0 } | if self.password.is_some() { PASSWORD } else { 0 } | if self.secret.is_some() { SECRET } else { 0 };
+        data.push(flags);
+        for text in [&self.password, &self.secret].into_iter().flatten() {
+            let text: Vec<u8> = text.bytes().take(u8::MAX as usize).collect();
+            data.push(text.len() as u8);
+            data.extend_from_slice(&text);
+        }
+
+        base32(&data)
+    }
+
+    pub fn decode(token: &str) -> Option<JoinToken> {
+        let data = unbase32(token.trim())?;
+        let mut reader = PacketReader::new(&data);
+        if reader.read_u8().ok()? != TOKEN_VERSION {
+            return None;
+        }
+
+        let ip = match reader.read_u8().ok()? {
+            4 => IpAddr::V4(Ipv4Addr::from(reader.read_u32().ok()?)),
+            6 => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(reader.read_bytes(16).ok()?).ok()?)),
+            _ => {
+                return None;
+            }
+        };
+
+        let port = reader.read_u16().ok()?;
+        let width = reader.read_u16().ok()? as usize;
+        let height = reader.read_u16().ok()? as usize;
+        let flags = reader.read_u8().ok()?;
+        let password = if flags & PASSWORD != 0 { Some(read_text(&mut reader)?) } else { None };
+        let secret = if flags & SECRET != 0 { Some(read_text(&mut reader)?) } else { None };
+        Some(JoinToken { address: SocketAddr::new(ip, port), width, height, walls: flags & WALLS != 0, password, secret })
+    }
+}
+
+fn read_text(reader: &mut PacketReader) -> Option<String> {
+    let size = reader.read_u8().ok()? as usize;
+    String::from_utf8(reader.read_bytes(size).ok()?.to_vec()).ok()
+}
+
+fn base32(data: &[u8]) -> String {
+    let mut s = String::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for byte in data {
+        buffer = buffer << 8 | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            s.push(BASE32[(buffer >> bits) as usize & 0x1f] as char);
+        }
+    }
+
+    if bits > 0 {
+        s.push(BASE32[(buffer << (5 - bits)) as usize & 0x1f] as char);
+    }
+
+    s
+}
+
+fn unbase32(text: &str) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in text.bytes() {
+        let value = BASE32.iter().position(|b| *b == c.to_ascii_uppercase())?;
+        buffer = buffer << 5 | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            data.push((buffer >> bits) as u8);
+        }
+    }
+
+    Some(data)
+}
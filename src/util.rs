@@ -1,18 +1,195 @@
-use std::{sync::Mutex, time::{Duration, SystemTime, UNIX_EPOCH}};
+use std::{collections::hash_map::RandomState, env::var_os, fs::{read_to_string, File}, hash::BuildHasher, io::Read, net::IpAddr, path::PathBuf, sync::Mutex, time::{Duration, SystemTime, UNIX_EPOCH}};
 
-static HASH: Mutex<u64> = Mutex::new(0xcbf29ce484222325);
+static RNG: Mutex<Option<Rng>> = Mutex::new(None);
+
+pub struct Rng {
+    state: u64
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+        value ^ (value >> 31)
+    }
+}
+
+pub fn time_seed() -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::new(0, 0));
+
+    now.as_secs() ^ ((now.subsec_nanos() as u64) << 32)
+}
+
+pub fn seed(value: u64) {
+    *RNG.lock().unwrap() = Some(Rng::new(value));
+}
 
 pub fn random_number() -> u64 {
-    let seed = SystemTime::now()
+    let mut rng = RNG.lock().unwrap();
+    rng.get_or_insert_with(|| Rng::new(time_seed())).next()
+}
+
+pub fn random_bytes(count: usize) -> Vec<u8> {
+    let mut bytes = vec![0; count];
+    let _ = File::open("/dev/urandom").and_then(|mut file| file.read_exact(&mut bytes));
+
+    let mut rng = Rng::new(time_seed() ^ RandomState::new().hash_one(count));
+    for byte in bytes.iter_mut() {
+        *byte ^= rng.next() as u8;
+    }
+
+    bytes
+}
+
+pub fn today() -> String {
+    let days = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or(Duration::new(0, 0))
-        .subsec_nanos();
+        .as_secs() as i64 / 86400;
+
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+pub fn data_dir() -> Option<PathBuf> {
+    let dir = match var_os("XDG_DATA_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            match var_os("APPDATA") {
+                Some(dir) => PathBuf::from(dir),
+                None => PathBuf::from(var_os("HOME")?).join(".local").join("share")
+            }
+        }
+    };
+
+    Some(dir.join("snake-game"))
+}
+
+pub fn is_private(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_loopback() || ip.is_private(),
+        IpAddr::V6(ip) => {
+            match ip.to_ipv4_mapped() {
+                Some(ip) => ip.is_loopback() || ip.is_private(),
+                None => ip.is_loopback() || ip.is_unique_local() || ip.is_unicast_link_local()
+            }
+        }
+    }
+}
+
+pub fn host_name() -> String {
+    for key in ["HOSTNAME", "COMPUTERNAME"] {
+        match var_os(key) {
+            Some(name) if !name.is_empty() => {
+                return name.to_string_lossy().into_owned();
+            },
+            _ => {}
+        }
+    }
+
+    match read_to_string("/etc/hostname") {
+        Ok(name) if !name.trim().is_empty() => name.trim().into(),
+        _ => "unknown".into()
+    }
+}
+
+pub fn escape(data: &str) -> String {
+    let mut s = String::with_capacity(data.len());
+    for c in data.chars() {
+        match c {
+            '"' => s.push_str("\\\""),
+            '\\' => s.push_str("\\\\"),
+            '\n' => s.push_str("\\n"),
+            '\r' => s.push_str("\\r"),
+            c if (c as u32) < 0x20 => s.push_str(&format!("\\u{:04x}", c as u32)),
+            c => s.push(c)
+        }
+    }
+
+    s
+}
+
+pub fn fnv1a(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in data {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    hash
+}
+
+pub fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for chunk in message.chunks_exact(64) {
+        let mut w = [0u32; 80];
+        for i in 0..16 {
+            w[i] = u32::from_be_bytes([chunk[4 * i], chunk[4 * i + 1], chunk[4 * i + 2], chunk[4 * i + 3]]);
+        }
+
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let (mut a, mut b, mut c, mut d, mut e) = (h[0], h[1], h[2], h[3], h[4]);
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a827999),
+                20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6)
+            };
+
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        h[0] = h[0].wrapping_add(a);
+        h[1] = h[1].wrapping_add(b);
+        h[2] = h[2].wrapping_add(c);
+        h[3] = h[3].wrapping_add(d);
+        h[4] = h[4].wrapping_add(e);
+    }
 
-    let mut value = HASH.lock().unwrap();
-    for i in 0..4 {
-        *value *= 0x100000001b3;
-        *value ^= ((seed >> (3 - i) * 8) as u8) as u64;
+    let mut digest = [0; 20];
+    for (i, word) in h.iter().enumerate() {
+        digest[4 * i..4 * i + 4].copy_from_slice(&word.to_be_bytes());
     }
 
-    *value
+    digest
 }
@@ -0,0 +1,163 @@
+use std::{
+    io::{
+        Read, Write
+    },
+    net::{
+        IpAddr, TcpListener, TcpStream
+    },
+    sync::mpsc::{
+        channel, Receiver, RecvTimeoutError, Sender
+    },
+    thread::spawn,
+    time::Duration
+};
+
+use crate::error::SnakeError;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const REQUEST_TIMEOUT: Duration = Duration::from_millis(500);
+const WRITE_TIMEOUT: Duration = Duration::from_millis(200);
+const MAX_REQUEST_SIZE: usize = 4096;
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>snake-game</title>
+<style>
+body { background: #111; color: #ddd; }
+pre { font-family: monospace; font-size: 16px; line-height: 1.15; }
+</style>
+</head>
+<body>
+<pre id="board">Waiting for the game to start...</pre>
+<script>
+const board = document.getElementById("board");
+const events = new EventSource("/events");
+events.onmessage = (event) => { board.textContent = event.data; };
+events.onerror = () => { board.textContent += "\nThe game has ended"; events.close(); };
+</script>
+</body>
+</html>
+"#;
+
+pub struct WebServer {
+    port: u16,
+    updates: Sender<(String, String)>
+}
+
+impl WebServer {
+    pub fn serve(ip: IpAddr, port: u16) -> Result<Self, SnakeError> {
+        let listener = TcpListener::bind((ip, port))?;
+        listener.set_nonblocking(true)?;
+        let port = listener.local_addr()?.port();
+
+        let (updates, inbox) = channel();
+        spawn(move || stream_updates(listener, inbox));
+        Ok(WebServer { port, updates })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn publish(&self, frame: &str, state: &str) {
+        let _ = self.updates.send((frame.into(), state.into()));
+    }
+}
+
+#[allow(clippy::single_match)]
+fn stream_updates(listener: TcpListener, updates: Receiver<(String, String)>) {
+    let mut viewers: Vec<TcpStream> = Vec::new();
+    let mut last = (String::new(), String::from("null"));
+    loop {
+        while let Ok((stream, _)) = listener.accept() {
+            match answer(stream, &last.0, &last.1) {
+                Some(viewer) => {
+                    viewers.push(viewer);
+                },
+                None => {}
+            }
+        }
+
+        match updates.recv_timeout(POLL_INTERVAL) {
+            Ok(update) => {
+                let message = event(&update.0);
+                viewers.retain_mut(|viewer| viewer.write_all(message.as_bytes()).is_ok());
+                last = update;
+            },
+            Err(RecvTimeoutError::Timeout) => {},
+            Err(RecvTimeoutError::Disconnected) => {
+                return;
+            }
+        }
+    }
+}
+
+fn answer(mut stream: TcpStream, frame: &str, state: &str) -> Option<TcpStream> {
+    let (method, path) = read_request(&mut stream)?;
+    match (method.as_str(), path.as_str()) {
+        ("GET", "/") => {
+            respond(&mut stream, "200 OK", "text/html; charset=utf-8", PAGE);
+            None
+        },
+        ("GET", "/events") => {
+            let mut head = String::from("HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n");
+            if !frame.is_empty() {
+                head.push_str(&event(frame));
+            }
+
+            stream.write_all(head.as_bytes()).ok()?;
+            Some(stream)
+        },
+        ("GET", "/state") => {
+            respond(&mut stream, "200 OK", "application/json", state);
+            None
+        },
+        _ => {
+            respond(&mut stream, "404 Not Found", "text/plain", "not found\n");
+            None
+        }
+    }
+}
+
+pub fn read_request(stream: &mut TcpStream) -> Option<(String, String)> {
+    stream.set_nonblocking(false).ok()?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT)).ok()?;
+
+    let mut request = Vec::new();
+    let mut chunk = [0; 512];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let n = stream.read(&mut chunk).ok()?;
+        if n == 0 || request.len() + n > MAX_REQUEST_SIZE {
+            return None;
+        }
+
+        request.extend_from_slice(&chunk[..n]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut words = request.lines().next()?.split_whitespace();
+    Some((words.next()?.into(), words.next()?.into()))
+}
+
+pub fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n{}",
+        status, content_type, body.len(), body
+    );
+    let _ = stream.write_all(response.as_bytes());
+}
+
+fn event(frame: &str) -> String {
+    let mut message = String::new();
+    for line in frame.lines() {
+        message.push_str("data: ");
+        message.push_str(line);
+        message.push('\n');
+    }
+
+    message.push('\n');
+    message
+}
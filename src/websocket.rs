@@ -0,0 +1,318 @@
+use std::{
+    io::{
+        self, Read, Write
+    },
+    net::TcpStream
+};
+
+use crate::{
+    error::SnakeError,
+    util::{
+        sha1, time_seed, Rng
+    }
+};
+
+pub const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+pub const MAX_HANDSHAKE_SIZE: usize = 8192;
+
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FrameOpcode {
+    Continuation = 0x0,
+    Text = 0x1,
+    Binary = 0x2,
+    Close = 0x8,
+    Ping = 0x9,
+    Pong = 0xa
+}
+
+impl FrameOpcode {
+    fn from(value: u8) -> Option<FrameOpcode> {
+        match value {
+            0x0 => Some(FrameOpcode::Continuation),
+            0x1 => Some(FrameOpcode::Text),
+            0x2 => Some(FrameOpcode::Binary),
+            0x8 => Some(FrameOpcode::Close),
+            0x9 => Some(FrameOpcode::Ping),
+            0xa => Some(FrameOpcode::Pong),
+            _ => None
+        }
+    }
+}
+
+pub struct WebSocket {
+    stream: TcpStream,
+    masked: bool,
+    raw: Vec<u8>,
+    buffer: Vec<u8>,
+    offset: usize,
+    rng: Rng
+}
+
+impl WebSocket {
+    pub fn is_handshake(stream: &TcpStream) -> Result<bool, SnakeError> {
+        let mut buffer = [0; 4];
+        let n = stream.peek(&mut buffer)?;
+        if n == 0 {
+            return Err(SnakeError::Disconnected);
+        }
+
+        Ok(buffer[..n] == b"GET "[..n])
+    }
+
+    pub fn accept(mut stream: TcpStream) -> Result<Self, SnakeError> {
+        let request = read_head(&mut stream)?;
+        let key = match header(&request, "sec-websocket-key") {
+            Some(key) => key,
+            None => {
+                return Err(SnakeError::BadHandshake);
+            }
+        };
+
+        let response = format!(
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+            accept_key(key)
+        );
+        stream.write_all(response.as_bytes())?;
+
+        Ok(WebSocket { stream, masked: false, raw: Vec::new(), buffer: Vec::new(), offset: 0, rng: Rng::new(time_seed()) })
+    }
+
+    pub fn connect(mut stream: TcpStream, host: &str) -> Result<Self, SnakeError> {
+        let mut rng = Rng::new(time_seed());
+        let mut nonce = Vec::with_capacity(16);
+        nonce.extend_from_slice(&rng.next().to_be_bytes());
+        nonce.extend_from_slice(&rng.next().to_be_bytes());
+        let key = base64(&nonce);
+
+        let request = format!(
+            "GET / HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+            host, key
+        );
+        stream.write_all(request.as_bytes())?;
+
+        let response = read_head(&mut stream)?;
+        let status = response.lines().next().unwrap_or("");
+        if status.split_whitespace().nth(1) != Some("101") || header(&response, "sec-websocket-accept") != Some(&accept_key(&key)) {
+            return Err(SnakeError::BadHandshake);
+        }
+
+        Ok(WebSocket { stream, masked: true, raw: Vec::new(), buffer: Vec::new(), offset: 0, rng })
+    }
+
+    #[allow(clippy::single_match)]
+    fn read_frame(&mut self) -> io::Result<Option<(FrameOpcode, Vec<u8>)>> {
+        loop {
+            match parse_frame(&self.raw)? {
+                Some((opcode, payload, size)) => {
+                    self.raw.drain(..size);
+                    return Ok(Some((opcode, payload)));
+                },
+                None => {}
+            }
+
+            let mut chunk = [0; 4096];
+            let n = self.stream.read(&mut chunk)?;
+            if n == 0 {
+                return Ok(None);
+            }
+
+            self.raw.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    #[allow(clippy::identity_op)]
+    fn write_frame(&mut self, opcode: FrameOpcode, payload: &[u8]) -> io::Result<()> {
+        let mut frame = Vec::with_capacity(payload.len() + 14);
+        frame.push(0x80 | opcode as u8);
+
+        let mask_bit = if self.masked { 0x80 } else { 0x00 };
+        if payload.len() < 126 {
+            frame.push(mask_bit | payload.len() as u8);
+        } else if payload.len() <= u16::MAX as usize {
+            frame.push(mask_bit | 126);
+            frame.push((payload.len() >> 8) as u8);
+            frame.push((payload.len() >> 0) as u8);
+        } else {
+            frame.push(mask_bit | 127);
+            frame.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+        }
+
+        if self.masked {
+            let mask = (self.rng.next() as u32).to_be_bytes();
+            frame.extend_from_slice(&mask);
+            for (i, byte) in payload.iter().enumerate() {
+                frame.push(byte ^ mask[i % 4]);
+            }
+        } else {
+            frame.extend_from_slice(payload);
+        }
+
+        self.stream.write_all(&frame)
+    }
+}
+
+impl Read for WebSocket {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.offset == self.buffer.len() {
+            let (opcode, payload) = match self.read_frame()? {
+                Some(frame) => frame,
+                None => {
+                    return Ok(0);
+                }
+            };
+
+            match opcode {
+                FrameOpcode::Continuation | FrameOpcode::Text | FrameOpcode::Binary => {
+                    self.buffer = payload;
+                    self.offset = 0;
+                },
+                FrameOpcode::Close => {
+                    return Ok(0);
+                },
+                FrameOpcode::Ping => {
+                    self.write_frame(FrameOpcode::Pong, &payload)?;
+                },
+                FrameOpcode::Pong => {}
+            }
+        }
+
+        let n = buf.len().min(self.buffer.len() - self.offset);
+        buf[..n].copy_from_slice(&self.buffer[self.offset..self.offset + n]);
+        self.offset += n;
+        Ok(n)
+    }
+}
+
+impl Write for WebSocket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_frame(FrameOpcode::Binary, buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl Drop for WebSocket {
+    fn drop(&mut self) {
+        let _ = self.write_frame(FrameOpcode::Close, &[]);
+    }
+}
+
+#[allow(clippy::identity_op)]
+fn parse_frame(raw: &[u8]) -> io::Result<Option<(FrameOpcode, Vec<u8>, usize)>> {
+    if raw.len() < 2 {
+        return Ok(None);
+    }
+
+    let opcode = match FrameOpcode::from(raw[0] & 0x0f) {
+        Some(opcode) => opcode,
+        None => {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "bad websocket frame"));
+        }
+    };
+
+    let mut offset = 2;
+    let mut size = (raw[1] & 0x7f) as u64;
+    if size == 126 {
+        if raw.len() < offset + 2 {
+            return Ok(None);
+        }
+
+        size = ((raw[2] as u64) << 8) | ((raw[3] as u64) << 0);
+        offset += 2;
+    } else if size == 127 {
+        if raw.len() < offset + 8 {
+            return Ok(None);
+        }
+
+        size = u64::from_be_bytes(raw[2..10].try_into().unwrap());
+        offset += 8;
+    }
+
+    if size > u16::MAX as u64 * 2 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "websocket frame too big"));
+    }
+
+    let mut mask = [0; 4];
+    if raw[1] & 0x80 != 0 {
+        if raw.len() < offset + 4 {
+            return Ok(None);
+        }
+
+        mask.copy_from_slice(&raw[offset..offset + 4]);
+        offset += 4;
+    }
+
+    let end = offset + size as usize;
+    if raw.len() < end {
+        return Ok(None);
+    }
+
+    let mut payload = raw[offset..end].to_vec();
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+
+    Ok(Some((opcode, payload, end)))
+}
+
+fn read_head(stream: &mut TcpStream) -> Result<String, SnakeError> {
+    let mut head = Vec::new();
+    let mut byte = [0; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() >= MAX_HANDSHAKE_SIZE {
+            return Err(SnakeError::BadHandshake);
+        }
+
+        let n = stream.read(&mut byte)?;
+        if n == 0 {
+            return Err(SnakeError::Disconnected);
+        }
+
+        head.push(byte[0]);
+    }
+
+    match String::from_utf8(head) {
+        Ok(head) => Ok(head),
+        Err(_) => Err(SnakeError::BadHandshake)
+    }
+}
+
+fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    for line in head.lines().skip(1) {
+        match line.split_once(':') {
+            Some((key, value)) if key.trim().eq_ignore_ascii_case(name) => {
+                return Some(value.trim());
+            },
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn accept_key(key: &str) -> String {
+    base64(&sha1(format!("{}{}", key, WEBSOCKET_GUID).as_bytes()))
+}
+
+#[allow(clippy::identity_op)]
+fn base64(data: &[u8]) -> String {
+    let mut s = String::new();
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = if chunk.len() > 1 { chunk[1] as u32 } else { 0 };
+        let b2 = if chunk.len() > 2 { chunk[2] as u32 } else { 0 };
+        let triple = (b0 << 16) | (b1 << 8) | (b2 << 0);
+
+        s.push(BASE64_CHARS[((triple >> 18) & 0x3f) as usize] as char);
+        s.push(BASE64_CHARS[((triple >> 12) & 0x3f) as usize] as char);
+        s.push(if chunk.len() > 1 { BASE64_CHARS[((triple >> 6) & 0x3f) as usize] as char } else { '=' });
+        s.push(if chunk.len() > 2 { BASE64_CHARS[((triple >> 0) & 0x3f) as usize] as char } else { '=' });
+    }
+
+    s
+}